@@ -140,24 +140,22 @@ fn main() {
 }
 
 fn get_models() -> Vec<render::ModelData> {
-    let pyramid = render::ModelData::new_with_normals(
-        "pyramid",
-        vec![
-            render::Vertex::new_position_color( 0.0,  0.5,  0.0, 1., 1., 0.),
-            render::Vertex::new_position_color(-0.5, -0.5, -0.5, 1., 0., 1.),
-            render::Vertex::new_position_color( 0.5, -0.5, -0.5, 1., 0., 0.),
-            render::Vertex::new_position_color( 0.5, -0.5,  0.5, 1., 1., 0.),
-            render::Vertex::new_position_color(-0.5, -0.5,  0.5, 1., 1., 1.),
-        ], vec![
-            0, 2, 1,
-            0, 3, 2,
-            0, 4, 3,
-            0, 1, 4,
-            2, 4, 1,
-            3, 4, 2,
-        ],
-        render::NormalMode::Smooth,
-    );
+    let vertex = render::Vertex::new_position_color;
+
+    let pyramid = render::ModelBuilder::new("pyramid")
+        .add_vertex(vertex( 0.0,  0.5,  0.0, 1., 1., 0.))
+        .add_vertex(vertex(-0.5, -0.5, -0.5, 1., 0., 1.))
+        .add_vertex(vertex( 0.5, -0.5, -0.5, 1., 0., 0.))
+        .add_vertex(vertex( 0.5, -0.5,  0.5, 1., 1., 0.))
+        .add_vertex(vertex(-0.5, -0.5,  0.5, 1., 1., 1.))
+        .add_triangle(0, 2, 1)
+        .add_triangle(0, 3, 2)
+        .add_triangle(0, 4, 3)
+        .add_triangle(0, 1, 4)
+        .add_triangle(2, 4, 1)
+        .add_triangle(3, 4, 2)
+        .normals(render::NormalMode::Smooth)
+        .build();
 
     vec![pyramid]
 }
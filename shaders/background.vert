@@ -0,0 +1,17 @@
+#version 450
+
+// Positions are given directly in clip space
+layout(location = 0) in vec3 inPosition;
+layout(location = 1) in vec3 inNormal;
+layout(location = 2) in vec3 inColor;
+
+layout(location = 0) out vec3 fragColor;
+
+out gl_PerVertex {
+  vec4 gl_Position;
+};
+
+void main() {
+  gl_Position = vec4(inPosition.xy, 1, 1);
+  fragColor = inColor;
+}
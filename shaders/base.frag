@@ -1,6 +1,8 @@
 #version 450
 
+#ifndef MAX_INSTANCE_LIGHTS
 #define MAX_INSTANCE_LIGHTS 4
+#endif
 #define AMBIENT 0.1
 
 struct Light {
@@ -18,6 +20,9 @@ layout(binding = 1, std140) uniform instance_ubo {
 layout(location = 0) in vec3 fragPosition;
 layout(location = 1) in vec3 fragNormal;
 layout(location = 2) in vec3 fragColor;
+layout(location = 3) in float fragDepth;
+layout(location = 4) flat in vec3 fragFogColor;
+layout(location = 5) flat in vec2 fragFogRange; // Start, end
 
 layout(location = 0) out vec4 outColor;
 
@@ -44,6 +49,12 @@ void main() {
         + (1 - t) * instance.lights[i].vector;
     }
 
+    // Area (soft, wrapped falloff)--no distance falloff,
+    // since the panel position is not part of the light data
+    else if (radius == -3) {
+      light *= max(0, 0.5 + 0.5 * dot(fragNormal, instance.lights[i].vector));
+    }
+
     else { // Point
       vec3 diff = instance.lights[i].vector - fragPosition;
       float dist = length(diff);
@@ -60,5 +71,17 @@ void main() {
   }
 
   total_light = max(ambient, total_light);
-  outColor = vec4(fragColor * total_light, 1);
+  vec3 color = fragColor * total_light;
+
+  // Linear distance fog; disabled with an empty range
+  if (fragFogRange.y > fragFogRange.x) {
+    float fog = clamp(
+      (fragDepth - fragFogRange.x) / (fragFogRange.y - fragFogRange.x),
+      0, 1
+    );
+
+    color = mix(color, fragFogColor, fog);
+  }
+
+  outColor = vec4(color, 1);
 }
@@ -1,11 +1,18 @@
 #version 450
 
+#ifndef MAX_SOFTBODY_VERT
 #define MAX_SOFTBODY_VERT 25
+#endif
+#ifndef MAX_INSTANCE_LIGHTS
 #define MAX_INSTANCE_LIGHTS 4
+#endif
 
 layout(binding = 0) uniform shared_ubo {
   mat4 view;
   mat4 projection;
+  vec3 fog_color;
+  float fog_start;
+  float fog_end;
 } shared_data;
 
 layout(binding = 1, std140) uniform instance_ubo {
@@ -19,15 +26,23 @@ layout(binding = 1, std140) uniform instance_ubo {
 layout(location = 0) in vec3 inPosition;
 layout(location = 1) in vec3 inNormal;
 layout(location = 2) in vec3 inColor;
+layout(location = 4) in uvec4 inBoneIndices; // Unused until skinning
+layout(location = 5) in vec4 inBoneWeights;
 
 layout(location = 0) out vec3 fragPosition;
 layout(location = 1) out vec3 fragNormal;
 layout(location = 2) out vec3 fragColor;
+layout(location = 3) out float fragDepth;
+layout(location = 4) flat out vec3 fragFogColor;
+layout(location = 5) flat out vec2 fragFogRange;
 
 out gl_PerVertex {
   vec4 gl_Position;
 };
 
+// Depth must match exactly between the prepass and shaded pipelines
+invariant gl_Position;
+
 void main() {
   uint index = gl_VertexIndex - instance.baseVertex;
 
@@ -43,5 +58,10 @@ void main() {
   ).xyz;
   fragNormal = normalize(fragNormal);
 
-  gl_Position = shared_data.projection * shared_data.view * position;
+  vec4 view_position = shared_data.view * position;
+  fragDepth = view_position.z;
+  fragFogColor = shared_data.fog_color;
+  fragFogRange = vec2(shared_data.fog_start, shared_data.fog_end);
+
+  gl_Position = shared_data.projection * view_position;
 }
@@ -0,0 +1,25 @@
+#version 450
+
+layout(binding = 0) uniform shared_ubo {
+  mat4 view;
+  mat4 projection;
+} shared_data;
+
+layout(location = 0) in vec3 inPosition;
+layout(location = 1) in vec3 inNormal; // View-space corner offset
+layout(location = 2) in vec3 inColor;
+
+layout(location = 0) out vec3 fragColor;
+
+out gl_PerVertex {
+  vec4 gl_Position;
+};
+
+void main() {
+  // Expand around the center in view space to face the camera
+  vec4 center = shared_data.view * vec4(inPosition, 1);
+  center.xy += inNormal.xy;
+
+  gl_Position = shared_data.projection * center;
+  fragColor = inColor;
+}
@@ -80,6 +80,10 @@ impl Vec2 {
         self.x * self.x + self.y * self.y
     }
 
+    pub fn mag(self) -> f32 {
+        self.mag_squared().sqrt()
+    }
+
     pub fn dist_squared(self, other: Vec2) -> f32 {
         (self - other).mag_squared()
     }
@@ -87,6 +91,21 @@ impl Vec2 {
     pub fn dist(self, other: Vec2) -> f32 {
         self.dist_squared(other).sqrt()
     }
+
+    #[inline]
+    pub fn lerp(self, other: Vec2, t: f32) -> Vec2 {
+        self * (1. - t) + other * t
+    }
+
+    /// Component-wise minimum
+    pub fn min(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    /// Component-wise maximum
+    pub fn max(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x.max(other.x), self.y.max(other.y))
+    }
 }
 
 impl std::ops::Add for Vec2 {
@@ -111,6 +130,14 @@ impl std::ops::Sub for Vec2 {
     }
 }
 
+impl std::ops::Neg for Vec2 {
+    type Output = Vec2;
+
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
 impl std::ops::Mul<f32> for Vec2 {
     type Output = Vec2;
 
@@ -122,6 +149,17 @@ impl std::ops::Mul<f32> for Vec2 {
     }
 }
 
+impl std::ops::Div<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn div(self, scalar: f32) -> Vec2 {
+        Vec2::new(
+            self.x / scalar,
+            self.y / scalar,
+        )
+    }
+}
+
 impl std::fmt::Display for Vec2 {
     fn fmt(&self, out: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
@@ -203,6 +241,13 @@ impl Vec3 {
         self.dist_squared(other).sqrt()
     }
 
+    /// Check if every component is within `epsilon` of `other`
+    pub fn approx_eq(self, other: Vec3, epsilon: f32) -> bool {
+           (self.x - other.x).abs() <= epsilon
+        && (self.y - other.y).abs() <= epsilon
+        && (self.z - other.z).abs() <= epsilon
+    }
+
     #[inline]
     pub fn cross(self, other: Vec3) -> Vec3 {
         Vec3 {
@@ -222,6 +267,47 @@ impl Vec3 {
         self * (1. - t) + other * t
     }
 
+    /// Component-wise minimum
+    pub fn min(self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    /// Component-wise maximum
+    pub fn max(self, other: Vec3) -> Vec3 {
+        Vec3::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
+    }
+
+    /// Reflect about the plane with (unit) normal `normal`
+    #[inline]
+    pub fn reflect(self, normal: Vec3) -> Vec3 {
+        self - normal * (2. * self.dot(normal))
+    }
+
+    /// Component of this vector parallel to `other`
+    #[inline]
+    pub fn project_onto(self, other: Vec3) -> Vec3 {
+        other * (self.dot(other) / other.mag_squared())
+    }
+
+    /// Component of this vector perpendicular to `other`
+    #[inline]
+    pub fn reject_from(self, other: Vec3) -> Vec3 {
+        self - self.project_onto(other)
+    }
+
+    #[inline]
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite() && self.z.is_finite()
+    }
+
     #[inline]
     pub fn is_uniform(self) -> bool {
         ((self.x - self.y) + (self.y - self.z)).abs()
@@ -438,6 +524,12 @@ impl Mat3 {
         + self.x2 * ((self.y0 * self.z1) - (self.y1 * self.z0))
     }
 
+    pub fn is_finite(self) -> bool {
+        Vec3::new(self.x0, self.x1, self.x2).is_finite()
+            && Vec3::new(self.y0, self.y1, self.y2).is_finite()
+            && Vec3::new(self.z0, self.z1, self.z2).is_finite()
+    }
+
     pub fn trace(self) -> f32 {
         // Add the diagonal
         self.x0 + self.y1 + self.z2
@@ -483,6 +575,11 @@ impl Mat3 {
         )
     }
 
+    /// Rotation of `rad` radians about (unit) `axis`
+    pub fn from_axis_angle(axis: Vec3, rad: f32) -> Mat3 {
+        Quat::axis_angle(axis, rad).to_mat()
+    }
+
     pub fn rotation(x: f32, y: f32, z: f32) -> Mat3 {
         Mat3::rotation_x(x) * Mat3::rotation_y(y) * Mat3::rotation_z(z)
     }
@@ -1024,6 +1121,65 @@ impl Mat4 {
                 0.0,      0.0,     1.0,      0.0, // Left-handed (scaling factor)
         )
     }
+
+    // Input: view volume extents along each axis
+    pub fn orthographic(
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    ) -> Mat4 {
+        let x_scale = 2. / (right - left);
+        let y_scale = 2. / (top - bottom);
+        let x_offset = -(right + left) / (right - left);
+        let y_offset = -(top + bottom) / (top - bottom);
+
+        // Fit into Vulkan clip space (0-1)
+        let z_scale = 1. / (far - near);
+        let z_offset = -near / (far - near);
+
+        Mat4::new(
+            x_scale,      0.0,     0.0,  x_offset,
+                0.0, -y_scale,     0.0, -y_offset, // Flip for Vulkan
+                0.0,      0.0, z_scale,  z_offset,
+                0.0,      0.0,     0.0,       1.0,
+        )
+    }
+
+    /// Check if every element is within `epsilon` of `other`
+    pub fn approx_eq(self, other: Mat4, epsilon: f32) -> bool {
+        let elements = |m: Mat4| [
+            m.x0, m.y0, m.z0, m.w0,
+            m.x1, m.y1, m.z1, m.w1,
+            m.x2, m.y2, m.z2, m.w2,
+            m.x3, m.y3, m.z3, m.w3,
+        ];
+
+        elements(self).iter().zip(elements(other).iter())
+            .all(|(a, b)| (a - b).abs() <= epsilon)
+    }
+
+    /// Extract the view volume of a (view-)projection matrix
+    /// as six inward-facing planes (left, right, bottom, top, near, far),
+    /// assuming Vulkan clip space. \
+    /// Normals are not normalized.
+    pub fn frustum_planes(self) -> [Plane; 6] {
+        let x = Vec3::new(self.x0, self.x1, self.x2);
+        let y = Vec3::new(self.y0, self.y1, self.y2);
+        let z = Vec3::new(self.z0, self.z1, self.z2);
+        let w = Vec3::new(self.w0, self.w1, self.w2);
+
+        [
+            Plane::new_raw(w + x, self.w3 + self.x3),
+            Plane::new_raw(w - x, self.w3 - self.x3),
+            Plane::new_raw(w + y, self.w3 + self.y3),
+            Plane::new_raw(w - y, self.w3 - self.y3),
+            Plane::new_raw(z, self.z3), // Depth range is 0-1
+            Plane::new_raw(w - z, self.w3 - self.z3),
+        ]
+    }
 }
 
 impl std::ops::Mul for Mat4 {
@@ -1120,6 +1276,12 @@ impl Quat {
         Quat::axis_angle_raw(axis.norm(), angle) // Normalize first
     }
 
+    /// Equivalent to `axis_angle()`
+    #[inline]
+    pub fn from_axis_angle(axis: Vec3, rad: f32) -> Quat {
+        Quat::axis_angle(axis, rad)
+    }
+
     pub fn axis_angle_raw(axis: Vec3, angle: f32) -> Quat {
         let half = 0.5 * angle;
         let half_sin = half.sin();
@@ -1140,26 +1302,32 @@ impl Quat {
         Mat3::axes(right, up, fwd).to_quat()
     }
 
+    /// Shortest-arc rotation taking direction `from` onto `to`. \
+    /// Antiparallel vectors rotate half a turn
+    /// around an arbitrary perpendicular axis.
     pub fn from_to(from: Vec3, to: Vec3) -> Quat {
         let cross = from.cross(to);
+        let scale = (from.mag_squared() * to.mag_squared()).sqrt();
         let dot = from.dot(to);
 
-        if dot > 1.0 - std::f32::EPSILON {
+        if dot > scale * (1.0 - std::f32::EPSILON) {
             Quat::id()
-        } else if dot < -1.0 + std::f32::EPSILON {
-            Quat {
-                x: 0.0,
-                y: 0.0,
-                z: 0.0,
-                w: 0.0,
-            }
+        } else if dot < scale * (-1.0 + std::f32::EPSILON) {
+            // Fall back to another axis if `from` lies along right
+            let from = from.norm();
+            let axis = from.cross(Vec3::right());
+            let axis = if axis.mag_squared() < 0.0001 {
+                from.cross(Vec3::up())
+            } else { axis };
+
+            Quat::axis_angle(axis, std::f32::consts::PI)
         } else {
             Quat {
                 x: cross.x,
                 y: cross.y,
                 z: cross.z,
-                w: (from.mag_squared() * to.mag_squared()).sqrt() + dot,
-            }
+                w: scale + dot,
+            }.norm()
         }
     }
 
@@ -1185,6 +1353,19 @@ impl Quat {
         }
     }
 
+    /// Check if both quaternions describe the same orientation,
+    /// each component within `epsilon` (`q` and `-q` compare equal)
+    pub fn approx_eq(self, other: Quat, epsilon: f32) -> bool {
+        let within = |sign: f32| {
+               (self.x - other.x * sign).abs() <= epsilon
+            && (self.y - other.y * sign).abs() <= epsilon
+            && (self.z - other.z * sign).abs() <= epsilon
+            && (self.w - other.w * sign).abs() <= epsilon
+        };
+
+        within(1.0) || within(-1.0)
+    }
+
     #[inline]
     pub fn dot(self, other: Quat) -> f32 {
         self.x * other.x
@@ -1258,6 +1439,14 @@ impl Quat {
         }
     }
 
+    /// Euler angles (radians) about the X, Y, and Z axes,
+    /// matching `Mat3::rotation()`. \
+    /// Z is zeroed at gimbal lock, with its rotation carried by X.
+    pub fn to_euler(self) -> Vec3 {
+        let (x, y, z) = self.to_mat().to_cardan();
+        Vec3::new(x, y, z)
+    }
+
     pub fn to_axis_angle(self) -> (Vec3, f32) {
         let this = if self.w > 1.0 { self.norm() } else { self };
         this.to_axis_angle_raw()
@@ -1330,6 +1519,28 @@ impl Quat {
     pub fn nlerp(self, target: Quat, t: f32) -> Quat {
         (self + (target - self) * t).norm()
     }
+
+    /// Spherically interpolate from self to target along the shortest arc
+    pub fn slerp(self, target: Quat, t: f32) -> Quat {
+        let mut cos = self.dot(target);
+
+        // Take the shortest path
+        let target = if cos < 0.0 {
+            cos = -cos;
+            target * -1.0
+        } else { target };
+
+        // Fall back to nlerp when nearly parallel
+        if cos > 0.9995 {
+            return self.nlerp(target, t);
+        }
+
+        let angle = cos.acos();
+        let inverse_sin = 1.0 / angle.sin();
+
+        self * (((1.0 - t) * angle).sin() * inverse_sin)
+            + target * ((t * angle).sin() * inverse_sin)
+    }
 }
 
 impl std::cmp::PartialEq for Quat {
@@ -1449,6 +1660,21 @@ impl Plane {
         Plane { normal, offset }
     }
 
+    /// Plane through `point` facing `normal`
+    pub fn from_point_normal(point: Vec3, normal: Vec3) -> Plane {
+        let normal = normal.norm();
+
+        Plane {
+            normal,
+            offset: -normal.dot(point),
+        }
+    }
+
+    /// Plane through three points, facing `(b - a) x (c - a)`
+    pub fn from_points(a: Vec3, b: Vec3, c: Vec3) -> Plane {
+        Plane::from_point_normal(a, Vec3::normal(a, b, c))
+    }
+
     #[inline]
     pub fn contains(self, point: Vec3) -> bool {
         self.normal.dot(point) > 0.0
@@ -1484,14 +1710,185 @@ impl Line {
             end,
         }
     }
+
+    /// Cast ray from `start` through `end` against `plane`. \
+    /// Returns the hit point and parameter `t` along `end - start`
+    /// (not clamped to the segment).
+    pub fn intersect_plane(self, plane: Plane) -> Option<(Vec3, f32)> {
+        let ray = self.end - self.start;
+        let slope = plane.normal.dot(ray);
+
+        // Parallel
+        if slope.abs() < std::f32::EPSILON {
+            return None;
+        }
+
+        let t = -plane.dist(self.start) / slope;
+
+        // Behind ray origin
+        if t < 0.0 {
+            return None;
+        }
+
+        Some((self.start + ray * t, t))
+    }
+
+    /// Cast ray from `start` through `end` against triangle `abc`
+    /// (Moller-Trumbore, double-sided). \
+    /// Returns the hit point and parameter `t` along `end - start`
+    /// (not clamped to the segment).
+    pub fn intersect_triangle(
+        self,
+        a: Vec3,
+        b: Vec3,
+        c: Vec3,
+    ) -> Option<(Vec3, f32)> {
+        let ray = self.end - self.start;
+        let (ab, ac) = (b - a, c - a);
+
+        let p = ray.cross(ac);
+        let det = ab.dot(p);
+
+        // Parallel (or degenerate); the determinant scales with
+        // the ray and edge lengths, so compare it relative to them
+        let scale = ray.mag() * ab.mag() * ac.mag();
+        if det.abs() <= std::f32::EPSILON * scale {
+            return None;
+        }
+
+        let inv_det = 1.0 / det;
+        let offset = self.start - a;
+
+        // Barycentric coordinates
+        let u = offset.dot(p) * inv_det;
+        if u < 0.0 || u > 1.0 {
+            return None;
+        }
+
+        let q = offset.cross(ab);
+        let v = ray.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = ac.dot(q) * inv_det;
+
+        // Behind ray origin
+        if t < 0.0 {
+            return None;
+        }
+
+        Some((self.start + ray * t, t))
+    }
+}
+
+/// World axis pointing up
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UpAxis { Y, Z }
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Handedness { Left, Right }
+
+/// World coordinate convention. \
+/// The engine natively uses Y-up and left-handed coordinates
+/// (right = +X, up = +Y, forward = +Z). \
+/// Right always points along +X.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Axes {
+    pub up_axis: UpAxis,
+    pub handedness: Handedness,
+}
+
+impl Axes {
+    pub fn new(up_axis: UpAxis, handedness: Handedness) -> Axes {
+        Axes {
+            up_axis,
+            handedness,
+        }
+    }
+
+    #[inline]
+    pub fn right(self) -> Vec3 {
+        Vec3::right()
+    }
+
+    #[inline]
+    pub fn up(self) -> Vec3 {
+        match self.up_axis {
+            UpAxis::Y => Vec3::up(),
+            UpAxis::Z => Vec3::fwd(),
+        }
+    }
+
+    /// Direction pointing into the screen for a camera
+    /// with `right()` to the right and `up()` upward
+    #[inline]
+    pub fn fwd(self) -> Vec3 {
+        match self.handedness {
+            Handedness::Left  =>  self.right().cross(self.up()),
+            Handedness::Right => -self.right().cross(self.up()),
+        }
+    }
+
+    /// Returns change of basis from this convention into `target`. \
+    /// Flips triangle winding if the handedness differs.
+    pub fn convert(self, target: Axes) -> Mat3 {
+        Mat3::axes(target.right(), target.up(), target.fwd())
+            * Mat3::inverse_axes(self.right(), self.up(), self.fwd())
+    }
+}
+
+impl Default for Axes {
+    fn default() -> Axes {
+        Axes::new(UpAxis::Y, Handedness::Left)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use alg::*;
 
+    /* Vec2 */
+
+    #[test]
+    fn vec2_helpers() {
+        let a = Vec2::new(3.0, 4.0);
+        let b = Vec2::new(-1.0, 1.0);
+
+        assert!(a.dot(b) == 1.0);
+        assert!(a.mag() == 5.0);
+        assert!(a.dist(b) == 5.0);
+
+        let error = (a.norm().mag() - 1.0).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        let error = a.norm().dist(Vec2::new(0.6, 0.8));
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        assert!(a.lerp(b, 0.0) == a);
+        assert!(a.lerp(b, 1.0) == b);
+        assert!(a.lerp(b, 0.5) == Vec2::new(1.0, 2.5));
+
+        assert!(a.min(b) == Vec2::new(-1.0, 1.0));
+        assert!(a.max(b) == Vec2::new(3.0, 4.0));
+
+        assert!(-a == Vec2::new(-3.0, -4.0));
+        assert!(a / 2.0 == Vec2::new(1.5, 2.0));
+    }
+
     /* Vec3 */
 
+    #[test]
+    fn min_max_vec() {
+        let a = Vec3::new(1.0, -2.0, 3.0);
+        let b = Vec3::new(0.0, 5.0, 3.0);
+
+        assert!(a.min(b) == Vec3::new(0.0, -2.0, 3.0));
+        assert!(a.max(b) == Vec3::new(1.0, 5.0, 3.0));
+    }
+
     #[test]
     fn norm_vec() {
         // Baseline
@@ -1512,6 +1909,38 @@ mod tests {
         assert!(Vec3::right().cross(Vec3::up()) == Vec3::fwd());
     }
 
+    #[test]
+    fn reflect_vec() {
+        let down_right = Vec3::new(1., -1., 0.);
+        let up_right = Vec3::new(1., 1., 0.);
+
+        let error = vec3_error(down_right.reflect(Vec3::up()), up_right);
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn project_vec() {
+        let vec = Vec3::new(3., -2., 5.);
+        let axis = Vec3::right() * 4.;
+
+        let error = vec3_error(vec.project_onto(axis), Vec3::right() * 3.)
+            + vec3_error(vec.reject_from(axis), Vec3::new(0., -2., 5.));
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn approx_eq_vec() {
+        let a = Vec3::new(1.0, 2.0, 3.0);
+
+        assert!(a.approx_eq(a + Vec3::one() * 0.00001, 0.0001));
+        assert!(!a.approx_eq(a + Vec3::up() * 0.01, 0.0001));
+        assert!(!a.approx_eq(-a, 0.0001));
+    }
+
     /* Mat3 */
 
     #[test]
@@ -1558,6 +1987,31 @@ mod tests {
 
     /* Mat4 */
 
+    #[test]
+    fn approx_eq_mat4() {
+        let position = Vec3::new(1.0, 2.0, 3.0);
+        let mat = Mat4::transform(position, Quat::id(), Vec3::one() * 0.3);
+
+        let expected = Mat4::translation_vec(position)
+            * Mat4::scale(0.3, 0.3, 0.3);
+
+        assert!(mat.approx_eq(expected, 0.0001));
+        assert!(!mat.approx_eq(Mat4::id(), 0.0001));
+
+        let rotated = Mat4::transform(
+            position,
+            Quat::axis_angle(Vec3::up(), 0.01),
+            Vec3::one() * 0.3,
+        );
+
+        assert!(!rotated.approx_eq(mat, 0.0001));
+
+        let mut shifted = mat;
+        shifted.x3 += 0.01;
+        assert!(!shifted.approx_eq(mat, 0.0001));
+        assert!(shifted.approx_eq(mat, 0.1));
+    }
+
     #[test]
     fn mul_mat4() {
         let translation = Mat4::translation(1.0, 2.0, 3.0);
@@ -1587,6 +2041,22 @@ mod tests {
         assert!(translation * Vec3::zero() == Vec3::new(2., -7., 0.5));
     }
 
+    #[test]
+    fn mat4_orthographic() {
+        let ortho = Mat4::orthographic(-2., 4., -1., 3., 1., 5.);
+
+        let error = vec3_error(
+            ortho * Vec3::new(-2., -1., 1.),
+            Vec3::new(-1., 1., 0.), // Flipped y
+        ) + vec3_error(
+            ortho * Vec3::new(4., 3., 5.),
+            Vec3::new(1., -1., 1.),
+        );
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
     #[test]
     fn mat4_to_pos() {
         let vec = Vec3::new(1.0, 2.0, 3.0);
@@ -1647,8 +2117,44 @@ mod tests {
         assert!(error < 0.0001);
     }
 
+    #[test]
+    fn mat4_frustum_planes() {
+        let planes = Mat4::perspective(90.0, 1.0, 0.1, 10.0)
+            .frustum_planes();
+
+        let inside = |point: Vec3| planes.iter().all(|p| p.dist(point) >= 0.0);
+
+        assert!(inside(Vec3::new(0.0, 0.0, 5.0)));
+        assert!(inside(Vec3::new(4.0, -4.0, 5.0)));
+        assert!(!inside(Vec3::new(6.0, 0.0, 5.0))); // Right
+        assert!(!inside(Vec3::new(0.0, -6.0, 5.0))); // Bottom
+        assert!(!inside(Vec3::new(0.0, 0.0, 0.05))); // Near
+        assert!(!inside(Vec3::new(0.0, 0.0, -5.0))); // Behind
+
+        let planes = Mat4::orthographic(-1.0, 1.0, -1.0, 1.0, 0.0, 10.0)
+            .frustum_planes();
+
+        let inside = |point: Vec3| planes.iter().all(|p| p.dist(point) >= 0.0);
+
+        assert!(inside(Vec3::new(0.5, 0.5, 9.0)));
+        assert!(!inside(Vec3::new(-2.0, 0.0, 5.0))); // Left
+        assert!(!inside(Vec3::new(0.0, 2.0, 5.0))); // Top
+        assert!(!inside(Vec3::new(0.0, 0.0, 11.0))); // Far
+    }
+
     /* Quaternion */
 
+    #[test]
+    fn approx_eq_quat() {
+        let q = Quat::axis_angle(Vec3::new(1.0, 2.0, 3.0), 1.2);
+        let negated = Quat::new(-q.x, -q.y, -q.z, -q.w);
+
+        assert!(q.approx_eq(negated, 0.0001));
+        assert!(q.approx_eq(q.slerp(Quat::id(), 0.00001), 0.0001));
+        assert!(!q.approx_eq(Quat::id(), 0.0001));
+        assert!(!q.approx_eq(Quat::new(q.x, q.y, q.z, -q.w), 0.0001));
+    }
+
     #[test]
     fn hamilton() {
         let i = Quat::new(1.0, 0.0, 0.0, 0.0);
@@ -1684,6 +2190,61 @@ mod tests {
         assert!(error < 0.001);
     }
 
+    #[test]
+    fn mat3_axis_angle() {
+        let rad = 30f32.to_radians();
+        let axis = Vec3::new(1., -2., 0.5).norm();
+
+        let error = mat3_error(
+            Mat3::from_axis_angle(Vec3::up(), rad),
+            Mat3::rotation_y(rad),
+        );
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        let error = vec3_error(
+            Mat3::from_axis_angle(axis, rad) * Vec3::fwd(),
+            Quat::from_axis_angle(axis, rad) * Vec3::fwd(),
+        );
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn quat_to_euler() {
+        let rotation = Quat::from_axis_angle(Vec3::up(), 90f32.to_radians());
+        let error = vec3_error(
+            rotation.to_euler(),
+            Vec3::new(0., 90f32.to_radians(), 0.),
+        );
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        let euler = Vec3::new(0.3, -0.2, 0.1);
+        let error = vec3_error(
+            Mat3::rotation(euler.x, euler.y, euler.z).to_quat().to_euler(),
+            euler,
+        );
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Gimbal lock: angles differ, but describe the same rotation
+        let locked = Mat3::rotation(0.4, 90f32.to_radians(), 0.3);
+        let euler = locked.to_quat().to_euler();
+        let error = mat3_error(
+            Mat3::rotation(euler.x, euler.y, euler.z),
+            locked,
+        );
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+        assert!(euler.z == 0.0);
+    }
+
     #[test]
     fn convert_axis_angle() {
         let rotation = Quat::axis_angle(
@@ -1725,6 +2286,30 @@ mod tests {
         assert_eq!(mat, mat.to_quat().to_mat());
     }
 
+    #[test]
+    fn from_to_quat() {
+        let rotation = Quat::from_to(Vec3::right(), Vec3::up() * 2.0);
+        let error = (rotation * Vec3::right()).dist(Vec3::up());
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Antiparallel
+        for &from in &[Vec3::right(), Vec3::up(), Vec3::fwd()] {
+            let rotation = Quat::from_to(from, -from);
+            let error = (rotation * from).dist(-from);
+            eprintln!("Error: {}", error);
+            assert!(error < 0.0001);
+
+            let error = (rotation.mag_squared() - 1.0).abs();
+            eprintln!("Error: {}", error);
+            assert!(error < 0.0001);
+        }
+
+        // Identical
+        let rotation = Quat::from_to(Vec3::fwd(), Vec3::fwd());
+        assert!(rotation.approx_eq(Quat::id(), 0.0001));
+    }
+
     #[test]
     fn mul_quat_vec() {
         let quat = Quat::axis_angle(Vec3::up(), 7.1);
@@ -1801,6 +2386,31 @@ mod tests {
         assert!(error < 0.1); // TODO
     }
 
+    #[test]
+    fn slerp_quat() {
+        let q0 = Quat::id();
+        let q1 = Quat::axis_angle(Vec3::up(), 2.0);
+
+        for &t in &[0.0, 0.25, 0.5, 1.0] {
+            let error = quat_error(
+                q0.slerp(q1, t),
+                Quat::axis_angle(Vec3::up(), 2.0 * t),
+            );
+
+            eprintln!("Error: {}", error);
+            assert!(error < 0.0001);
+        }
+
+        // Shortest arc
+        let error = quat_error(
+            q0.slerp(q1 * -1.0, 0.5),
+            Quat::axis_angle(Vec3::up(), 1.0),
+        );
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
     #[test]
     fn quat_eq() {
         let q1 = Quat::new(-1.0, -2.0, -3.0, -4.0).norm();
@@ -1835,6 +2445,144 @@ mod tests {
         assert!(error < 0.0001);
     }
 
+    /* Plane */
+
+    #[test]
+    fn plane_from_point_normal() {
+        let point = Vec3::new(1.0, 2.0, -3.0);
+        let plane = Plane::from_point_normal(point, Vec3::new(0.0, 2.0, 2.0));
+
+        let error = plane.dist(point).abs()
+            + (plane.normal.mag() - 1.0).abs()
+            + (plane.dist(point + plane.normal) - 1.0).abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn plane_from_points() {
+        let a = Vec3::new(0.0, 1.0, 0.0);
+        let b = Vec3::new(0.0, 1.0, 1.0);
+        let c = Vec3::new(1.0, 1.0, 0.0);
+
+        let plane = Plane::from_points(a, b, c);
+
+        let error = plane.dist(a).abs()
+            + plane.dist(b).abs()
+            + plane.dist(c).abs()
+            + plane.dist((a + b + c) * (1.0 / 3.0)).abs()
+            + vec3_error(plane.normal, Vec3::up());
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    /* Line */
+
+    #[test]
+    fn line_intersect_plane() {
+        let plane = Plane::new(Vec3::up(), -1.0); // y = 1
+        let ray = Line::new(Vec3::new(2.0, 3.0, 0.0), Vec3::new(2.0, 2.0, 0.0));
+
+        let (point, t) = ray.intersect_plane(plane).unwrap();
+        let error = vec3_error(point, Vec3::new(2.0, 1.0, 0.0))
+            + (t - 2.0).abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Pointing away
+        let ray = Line::new(Vec3::new(0.0, 3.0, 0.0), Vec3::new(0.0, 4.0, 0.0));
+        assert!(ray.intersect_plane(plane).is_none());
+
+        // Parallel
+        let ray = Line::new(Vec3::new(0.0, 3.0, 0.0), Vec3::new(1.0, 3.0, 0.0));
+        assert!(ray.intersect_plane(plane).is_none());
+    }
+
+    #[test]
+    fn line_intersect_triangle() {
+        let a = Vec3::new(-1.0, 0.0, -1.0);
+        let b = Vec3::new( 1.0, 0.0, -1.0);
+        let c = Vec3::new( 0.0, 0.0,  1.0);
+
+        let ray = Line::new(Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+
+        let (point, t) = ray.intersect_triangle(a, b, c).unwrap();
+        let error = vec3_error(point, Vec3::zero()) + (t - 2.0).abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Outside edge
+        let ray = Line::new(Vec3::new(0.9, 2.0, 0.9), Vec3::new(0.9, 1.0, 0.9));
+        assert!(ray.intersect_triangle(a, b, c).is_none());
+
+        // Behind ray origin
+        let ray = Line::new(Vec3::new(0.0, 2.0, 0.0), Vec3::new(0.0, 3.0, 0.0));
+        assert!(ray.intersect_triangle(a, b, c).is_none());
+
+        // Parallel
+        let ray = Line::new(Vec3::new(0.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        assert!(ray.intersect_triangle(a, b, c).is_none());
+
+        // Small triangle and short ray
+        let scale = 0.001;
+        let ray = Line::new(
+            Vec3::new(0.0, 2.0, 0.0) * scale,
+            Vec3::new(0.0, 1.0, 0.0) * scale,
+        );
+
+        let (point, t) = ray.intersect_triangle(a * scale, b * scale, c * scale)
+            .unwrap();
+        let error = vec3_error(point, Vec3::zero()) + (t - 2.0).abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Grazing a large triangle
+        let scale = 1000.0;
+        let ray = Line::new(
+            Vec3::new(0.0, -0.00001, 0.0),
+            Vec3::new(100.0, 0.0, 0.0),
+        );
+
+        let hit = ray.intersect_triangle(a * scale, b * scale, c * scale);
+        assert!(hit.is_none());
+    }
+
+    /* Axes */
+
+    #[test]
+    fn axes_convert() {
+        let native = Axes::default();
+        let z_up = Axes::new(UpAxis::Z, Handedness::Right);
+
+        let error = vec3_error(native.fwd(), Vec3::fwd())
+            + vec3_error(z_up.fwd(), Vec3::up());
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Z-up, right-handed to native and back
+        let to_native = z_up.convert(native);
+        let vec = Vec3::new(1.0, 2.0, 3.0);
+
+        let error = vec3_error(to_native * Vec3::fwd(), Vec3::up())
+            + vec3_error(to_native * Vec3::up(), Vec3::fwd())
+            + vec3_error(to_native * Vec3::right(), Vec3::right())
+            + vec3_error(native.convert(z_up) * (to_native * vec), vec);
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Handedness change mirrors
+        assert!(to_native.det() < 0.0);
+        assert!(native.convert(Axes::new(UpAxis::Z, Handedness::Left))
+            .det() > 0.0);
+    }
+
     /* Utility */
 
     fn mat4_error(a: Mat4, b: Mat4) -> f32 {
@@ -0,0 +1,371 @@
+use alg;
+
+/// Values that can be blended between keyframes
+pub trait Interpolate: Copy {
+    fn interpolate(self, target: Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(self, target: f32, t: f32) -> f32 {
+        self + (target - self) * t
+    }
+}
+
+impl Interpolate for alg::Vec3 {
+    fn interpolate(self, target: alg::Vec3, t: f32) -> alg::Vec3 {
+        self.lerp(target, t)
+    }
+}
+
+impl Interpolate for alg::Quat {
+    fn interpolate(self, target: alg::Quat, t: f32) -> alg::Quat {
+        self.slerp(target, t)
+    }
+}
+
+/// Critically damped spring smoothing toward a moving target
+pub trait SmoothDamp: Copy {
+    /// Returns the next value, updating the stored `velocity`. \
+    /// `smooth_time` is roughly the time taken to reach the target;
+    /// `max_speed` optionally clamps the rate of approach.
+    fn smooth_damp(
+        self,
+        target: Self,
+        velocity: &mut Self,
+        smooth_time: f32,
+        max_speed: Option<f32>,
+        delta: f32,
+    ) -> Self;
+}
+
+// Approximation of exp(-x) used by the critically damped spring
+fn damp_factor(smooth_time: f32, delta: f32) -> (f32, f32) {
+    let omega = 2.0 / smooth_time.max(0.0001);
+    let x = omega * delta;
+
+    (omega, 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x))
+}
+
+impl SmoothDamp for f32 {
+    fn smooth_damp(
+        self,
+        target: f32,
+        velocity: &mut f32,
+        smooth_time: f32,
+        max_speed: Option<f32>,
+        delta: f32,
+    ) -> f32 {
+        let (omega, factor) = damp_factor(smooth_time, delta);
+
+        let mut change = self - target;
+
+        if let Some(max_speed) = max_speed {
+            let max_change = max_speed * smooth_time;
+            change = change.max(-max_change).min(max_change);
+        }
+
+        let temp = (*velocity + omega * change) * delta;
+        *velocity = (*velocity - omega * temp) * factor;
+
+        let result = self - change + (change + temp) * factor;
+
+        // Prevent overshoot
+        if (target - self > 0.0) == (result > target) {
+            *velocity = 0.0;
+            return target;
+        }
+
+        result
+    }
+}
+
+impl SmoothDamp for alg::Vec3 {
+    fn smooth_damp(
+        self,
+        target: alg::Vec3,
+        velocity: &mut alg::Vec3,
+        smooth_time: f32,
+        max_speed: Option<f32>,
+        delta: f32,
+    ) -> alg::Vec3 {
+        let (omega, factor) = damp_factor(smooth_time, delta);
+
+        let mut change = self - target;
+
+        if let Some(max_speed) = max_speed {
+            let max_change = max_speed * smooth_time;
+
+            if change.mag_squared() > max_change * max_change {
+                change = change.norm() * max_change;
+            }
+        }
+
+        let temp = (*velocity + change * omega) * delta;
+        *velocity = (*velocity - temp * omega) * factor;
+
+        let result = self - change + (change + temp) * factor;
+
+        // Prevent overshoot
+        if (target - self).dot(result - target) > 0.0 {
+            *velocity = alg::Vec3::zero();
+            return target;
+        }
+
+        result
+    }
+}
+
+/// Easing between a keyframe and the next
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    Step, // Hold value until the next keyframe
+}
+
+impl Mode {
+    /// Remap normalized time in [0, 1]
+    pub fn ease(self, t: f32) -> f32 {
+        match self {
+            Mode::Linear => t,
+            Mode::EaseIn => t * t,
+            Mode::EaseOut => t * (2.0 - t),
+            Mode::EaseInOut => t * t * (3.0 - 2.0 * t),
+            Mode::Step => 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Keyframe<T> {
+    pub time: f32,
+    pub value: T,
+    pub mode: Mode, // Applied until the next keyframe
+}
+
+/// Keyframed value sampled by time
+#[derive(Clone, Debug)]
+pub struct Curve<T> {
+    keys: Vec<Keyframe<T>>,
+}
+
+impl<T: Interpolate> Curve<T> {
+    pub fn new() -> Curve<T> {
+        Curve { keys: Vec::new() }
+    }
+
+    /// Append keyframe; keyframes must be added in time order
+    pub fn key(mut self, time: f32, value: T, mode: Mode) -> Curve<T> {
+        #[cfg(debug_assertions)] {
+            if let Some(last) = self.keys.last() {
+                if time < last.time {
+                    panic!("Keyframe at {} added after {}", time, last.time);
+                }
+            }
+        }
+
+        self.keys.push(Keyframe { time, value, mode });
+        self
+    }
+
+    pub fn keys(&self) -> &[Keyframe<T>] {
+        &self.keys
+    }
+
+    /// Returns time of the first and last keyframes
+    pub fn span(&self) -> (f32, f32) {
+        debug_assert!(!self.keys.is_empty());
+
+        (self.keys[0].time, self.keys[self.keys.len() - 1].time)
+    }
+
+    /// Returns value at `time`,
+    /// clamped to the first and last keyframes
+    pub fn sample(&self, time: f32) -> T {
+        debug_assert!(!self.keys.is_empty());
+
+        // Index of the first keyframe after time
+        let next = self.keys.iter()
+            .position(|key| key.time > time)
+            .unwrap_or(self.keys.len());
+
+        if next == 0 {
+            return self.keys[0].value;
+        }
+
+        if next == self.keys.len() {
+            return self.keys[next - 1].value;
+        }
+
+        let (a, b) = (&self.keys[next - 1], &self.keys[next]);
+        let t = (time - a.time) / (b.time - a.time);
+
+        a.value.interpolate(b.value, a.mode.ease(t))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Wrap {
+    Once,
+    Loop,
+    PingPong,
+}
+
+/// Curve played back over a duration
+#[derive(Clone, Debug)]
+pub struct Playback<T> {
+    pub curve: Curve<T>,
+    pub duration: f32, // Seconds to play the curve span once
+    pub wrap: Wrap,
+    time: f32,
+}
+
+impl<T: Interpolate> Playback<T> {
+    pub fn new(curve: Curve<T>, duration: f32, wrap: Wrap) -> Playback<T> {
+        debug_assert!(duration > 0.0);
+
+        Playback {
+            curve,
+            duration,
+            wrap,
+            time: 0.0,
+        }
+    }
+
+    pub fn advance(&mut self, delta: f32) {
+        self.time += delta;
+    }
+
+    pub fn restart(&mut self) {
+        self.time = 0.0;
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Check if a non-repeating playback has reached its end
+    pub fn finished(&self) -> bool {
+        self.wrap == Wrap::Once && self.time >= self.duration
+    }
+
+    /// Returns value at the current playback time
+    pub fn value(&self) -> T {
+        let t = self.time / self.duration;
+
+        let t = match self.wrap {
+            Wrap::Once => t.min(1.0),
+            Wrap::Loop => t - t.floor(),
+            Wrap::PingPong => {
+                let t = t % 2.0;
+                if t > 1.0 { 2.0 - t } else { t }
+            },
+        };
+
+        let (start, end) = self.curve.span();
+        self.curve.sample(start + (end - start) * t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use anim::*;
+
+    #[test]
+    fn smooth_damp() {
+        let (mut value, mut velocity) = (0.0, 0.0);
+        let mut last = value;
+
+        for _ in 0..200 {
+            value = value.smooth_damp(10.0, &mut velocity, 0.5, None, 0.02);
+
+            assert!(value >= last && value <= 10.0);
+            last = value;
+        }
+
+        let error = (value - 10.0f32).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.001);
+
+        // Clamped approach
+        let target = alg::Vec3::new(100.0, 0.0, 0.0);
+        let (mut value, mut velocity) = (alg::Vec3::zero(), alg::Vec3::zero());
+
+        for _ in 0..50 {
+            let next = value.smooth_damp(
+                target,
+                &mut velocity,
+                0.5,
+                Some(4.0),
+                0.02,
+            );
+
+            let speed = next.dist(value) / 0.02;
+            eprintln!("Speed: {}", speed);
+            assert!(speed <= 4.0 + 0.0001);
+
+            assert!(next.x > value.x && next.x <= target.x);
+            value = next;
+        }
+    }
+
+    #[test]
+    fn ease_curve() {
+        let curve = Curve::new()
+            .key(1.0, 0.0, Mode::EaseInOut)
+            .key(3.0, 10.0, Mode::Linear);
+
+        assert!(curve.sample(0.0) == 0.0);
+        assert!(curve.sample(4.0) == 10.0);
+
+        let mut last = 0.0;
+
+        for i in 0..=20 {
+            let value = curve.sample(1.0 + i as f32 * 0.1);
+
+            assert!(value >= last);
+            assert!(value >= 0.0 && value <= 10.0);
+            last = value;
+        }
+
+        let error = (curve.sample(2.0) - 5.0).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn step_curve() {
+        let curve = Curve::new()
+            .key(0.0, 1.0, Mode::Step)
+            .key(1.0, 2.0, Mode::Step);
+
+        assert!(curve.sample(0.99) == 1.0);
+        assert!(curve.sample(1.0) == 2.0);
+    }
+
+    #[test]
+    fn ping_pong() {
+        let curve = Curve::new()
+            .key(0.0, 0.0, Mode::Linear)
+            .key(1.0, 1.0, Mode::Linear);
+
+        let mut playback = Playback::new(curve, 2.0, Wrap::PingPong);
+
+        playback.advance(3.0);
+        let error = (playback.value() - 0.5).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        playback.wrap = Wrap::Loop;
+        let error = (playback.value() - 0.5).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        playback.wrap = Wrap::Once;
+        assert!(playback.finished());
+        assert!(playback.value() == 1.0);
+    }
+}
@@ -0,0 +1,222 @@
+extern crate rodio;
+
+use std;
+use alg;
+use entity;
+use components;
+
+// Distance below which positional sounds play at full volume
+const REFERENCE_DISTANCE: f32 = 1.0;
+
+/// Decoded PCM clip, with interleaved 16-bit samples
+#[derive(Clone)]
+pub struct Clip {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<i16>,
+}
+
+impl Clip {
+    /// Load uncompressed (8-bit or 16-bit PCM) WAV file
+    pub fn load(path: &str) -> Result<Clip, String> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| format!("Could not read \"{}\": {}", path, e))?;
+
+        Clip::from_wav(&bytes)
+    }
+
+    /// Parse uncompressed (8-bit or 16-bit PCM) WAV data
+    pub fn from_wav(bytes: &[u8]) -> Result<Clip, String> {
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF"
+            || &bytes[8..12] != b"WAVE"
+        {
+            return Err("Invalid WAV data".into());
+        }
+
+        // Channels, sample rate, bits per sample
+        let mut format: Option<(u16, u32, u16)> = None;
+        let mut position = 12;
+
+        while position + 8 <= bytes.len() {
+            let id = &bytes[position..position + 4];
+            let size = read_u32(bytes, position + 4) as usize;
+            let start = position + 8;
+
+            if start + size > bytes.len() {
+                return Err("Unexpected end of WAV data".into());
+            }
+
+            let chunk = &bytes[start..start + size];
+
+            if id == b"fmt " {
+                if size < 16 {
+                    return Err("Invalid WAV format chunk".into());
+                }
+
+                if read_u16(chunk, 0) != 1 {
+                    return Err("Only PCM WAV data is supported".into());
+                }
+
+                format = Some((
+                    read_u16(chunk, 2),
+                    read_u32(chunk, 4),
+                    read_u16(chunk, 14),
+                ));
+            }
+
+            else if id == b"data" {
+                let (channels, sample_rate, bits) = format
+                    .ok_or("WAV data chunk precedes format chunk")?;
+
+                if channels == 0 || sample_rate == 0 {
+                    return Err("Invalid WAV format chunk".into());
+                }
+
+                let samples = match bits {
+                    8 => chunk.iter()
+                        .map(|&sample| (sample as i16 - 128) << 8)
+                        .collect(),
+                    16 => (0..size / 2)
+                        .map(|i| read_u16(chunk, i * 2) as i16)
+                        .collect(),
+                    _ => return Err(
+                        format!("Unsupported WAV bit depth {}", bits)
+                    ),
+                };
+
+                return Ok(Clip { sample_rate, channels, samples });
+            }
+
+            // Chunks are padded to an even size
+            position = start + size + (size & 1);
+        }
+
+        Err("WAV data is missing a data chunk".into())
+    }
+
+    /// Returns length in seconds
+    pub fn duration(&self) -> f32 {
+        let frames = self.samples.len() / self.channels as usize;
+        frames as f32 / self.sample_rate as f32
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    bytes[offset] as u16 | (bytes[offset + 1] as u16) << 8
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    read_u16(bytes, offset) as u32
+        | (read_u16(bytes, offset + 2) as u32) << 16
+}
+
+/// Returns volume for a sound `distance` away from the listener
+pub fn attenuation(distance: f32) -> f32 {
+    REFERENCE_DISTANCE / distance.max(REFERENCE_DISTANCE)
+}
+
+/// Plays clips on the default output device
+pub struct Manager {
+    device: rodio::Device,
+    listener: alg::Vec3,
+}
+
+impl Manager {
+    pub fn new() -> Result<Manager, String> {
+        let device = rodio::default_output_device()
+            .ok_or("No audio output device found")?;
+
+        Ok(
+            Manager {
+                device,
+                listener: alg::Vec3::zero(),
+            }
+        )
+    }
+
+    /// Position positional sounds relative to a camera
+    pub fn set_listener(
+        &mut self,
+        camera: entity::Handle,
+        transforms: &components::transform::Manager,
+    ) {
+        self.listener = transforms.get_position(camera);
+    }
+
+    /// Play clip once at full volume
+    pub fn play(&self, clip: &Clip) {
+        self.play_with_volume(clip, 1.0);
+    }
+
+    /// Play clip once at `position`,
+    /// attenuated by distance from the listener
+    pub fn play_at(&self, clip: &Clip, position: alg::Vec3) {
+        let volume = attenuation(position.dist(self.listener));
+        self.play_with_volume(clip, volume);
+    }
+
+    fn play_with_volume(&self, clip: &Clip, volume: f32) {
+        let sink = rodio::Sink::new(&self.device);
+
+        sink.set_volume(volume);
+        sink.append(
+            rodio::buffer::SamplesBuffer::new(
+                clip.channels,
+                clip.sample_rate,
+                clip.samples.clone(),
+            )
+        );
+
+        // Play to completion in the background
+        sink.detach();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use audio::*;
+
+    // 16-bit stereo WAV with `frames` frames of silence
+    fn wav(sample_rate: u32, frames: u32) -> Vec<u8> {
+        let size = frames * 4;
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + size).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        bytes.extend_from_slice(&2u16.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sample_rate * 4).to_le_bytes());
+        bytes.extend_from_slice(&4u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&size.to_le_bytes());
+        bytes.extend(std::iter::repeat(0).take(size as usize));
+
+        bytes
+    }
+
+    #[test]
+    fn from_wav() {
+        let clip = Clip::from_wav(&wav(22050, 11025)).unwrap();
+
+        assert!(clip.sample_rate == 22050);
+        assert!(clip.channels == 2);
+        assert!(clip.samples.len() == 22050);
+
+        let error = (clip.duration() - 0.5).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Truncated data
+        assert!(Clip::from_wav(&wav(22050, 4)[..40]).is_err());
+    }
+
+    #[test]
+    fn attenuation() {
+        assert!(super::attenuation(0.5) == 1.0);
+        assert!(super::attenuation(4.0) == 0.25);
+    }
+}
@@ -3,39 +3,82 @@ use entity;
 use components;
 use render;
 
+use anim::SmoothDamp;
+
 use components::transform;
+use components::scene;
 
 pub const DEFAULT_FOV: f32 = 60.0;
 pub const DEFAULT_NEAR: f32 = 0.01;
 pub const DEFAULT_FAR: f32 = 32.0;
 
+/// Screen axis along which the camera field of view is measured
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FovAxis { Vertical, Horizontal }
+
+impl FovAxis {
+    /// Convert field of view (in degrees) along this axis
+    /// to a vertical field of view, given the screen aspect ratio
+    pub fn to_vertical(self, fov: f32, aspect: f32) -> f32 {
+        match self {
+            FovAxis::Vertical => fov,
+            FovAxis::Horizontal => {
+                let half = (0.5 * fov).to_radians();
+                2.0 * (half.tan() / aspect).atan().to_degrees()
+            },
+        }
+    }
+}
+
+// Smoothly track another entity
+#[derive(Copy, Clone)]
+struct Follow {
+    target: entity::Handle,
+    smooth_time: f32,
+    offset: alg::Vec3,
+    velocity: alg::Vec3, // Smoothing state; not serialized
+}
+
 #[derive(Copy, Clone)]
 pub struct Camera {
     fov: f32,
+    fov_axis: FovAxis,
     near: f32,
     far: f32,
     overrule: Option<render::SharedUBO>,
+    follow: Option<Follow>,
+    clear_depth: bool, // Clear depth before rendering this camera
+    target: render::RenderTarget,
 }
 
 impl Default for Camera {
     fn default() -> Camera {
         Camera {
             fov: DEFAULT_FOV,
+            fov_axis: FovAxis::Vertical,
             near: DEFAULT_NEAR,
             far: DEFAULT_FAR,
             overrule: None,
+            follow: None,
+            clear_depth: true,
+            target: render::RenderTarget::Swapchain,
         }
     }
 }
 
 pub struct Manager {
+    axes: alg::Axes,
     active: usize,
+    render_list: Vec<usize>, // Overrides active camera if not empty
+    warned_empty: bool, // Reported rendering without cameras
     // There will likely be few cameras
     instances: Vec<(entity::Handle, Camera)>,
 }
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
+        debug_validate_unregistered!(self, entity);
+
         self.instances.push(
             (
                 entity,
@@ -58,13 +101,35 @@ impl components::Component for Manager {
 impl Manager {
     pub fn new(hint: usize) -> Manager {
         Manager {
+            axes: alg::Axes::default(),
             active: 0,
+            render_list: Vec::with_capacity(0),
+            warned_empty: false,
             instances: Vec::with_capacity(hint),
         }
     }
 
-    /// Set the main camera that will be rendered
-    pub fn set_active(&mut self, camera_index: usize) {
+    /// Set world coordinate convention used for projection
+    pub fn set_axes(&mut self, axes: alg::Axes) {
+        self.axes = axes;
+    }
+
+    pub fn axes(&self) -> alg::Axes {
+        self.axes
+    }
+
+    /// Set the main camera that will be rendered. \
+    /// Clears the render list.
+    pub fn set_active(&mut self, entity: entity::Handle) {
+        debug_validate_entity!(self, entity);
+        let index = self.index_of(entity).unwrap();
+        self.set_active_index(index);
+    }
+
+    /// Set the main camera that will be rendered,
+    /// given its index in registration order. \
+    /// Clears the render list.
+    pub fn set_active_index(&mut self, camera_index: usize) {
         #[cfg(debug_assertions)] {
             if camera_index >= self.instances.len() {
                 panic!(
@@ -75,6 +140,124 @@ impl Manager {
         }
 
         self.active = camera_index;
+        self.render_list.clear();
+    }
+
+    /// Set an ordered list of cameras to render in sequence,
+    /// e.g. a world camera followed by an overlay camera. \
+    /// The first camera in the list becomes the active camera.
+    pub fn set_render_list(&mut self, camera_indices: &[usize]) {
+        #[cfg(debug_assertions)] {
+            if camera_indices.is_empty() {
+                panic!("Attempted to set empty camera render list");
+            }
+
+            if camera_indices.len() > render::MAX_CAMERAS {
+                panic!(
+                    "Camera render list of length {} exceeds maximum of {}",
+                    camera_indices.len(),
+                    render::MAX_CAMERAS,
+                );
+            }
+
+            for camera_index in camera_indices {
+                if *camera_index >= self.instances.len() {
+                    panic!(
+                        "Attempted to add invalid index {} \
+                        to camera render list",
+                        camera_index,
+                    );
+                }
+            }
+        }
+
+        self.active = camera_indices[0];
+        self.render_list = camera_indices.to_vec();
+    }
+
+    /// Returns the camera rendered first, if any are registered
+    pub fn active(&self) -> Option<entity::Handle> {
+        self.render_list().first().map(|&(entity, _)| entity)
+    }
+
+    /// Remove camera component from entity. \
+    /// The active camera and render list keep referring to the same
+    /// cameras; if the active camera is removed, the first remaining
+    /// camera becomes active.
+    pub fn remove(&mut self, entity: entity::Handle) {
+        debug_validate_entity!(self, entity);
+        let index = self.index_of(entity).unwrap();
+        self.instances.remove(index);
+
+        self.render_list.retain(|i| *i != index);
+        for i in &mut self.render_list {
+            if *i > index { *i -= 1; }
+        }
+
+        if self.active == index {
+            self.active = self.render_list.first().cloned().unwrap_or(0);
+        } else if self.active > index {
+            self.active -= 1;
+        }
+    }
+
+    fn index_of(&self, entity: entity::Handle) -> Option<usize> {
+        self.instances.iter().position(|instance| instance.0 == entity)
+    }
+
+    /// Returns the ordered list of cameras to render,
+    /// paired with whether each clears depth before rendering
+    pub fn render_list(&self) -> Vec<(entity::Handle, bool)> {
+        if self.render_list.is_empty() {
+            return self.instances.get(self.active)
+                .map(|&(entity, camera)| (entity, camera.clear_depth))
+                .into_iter()
+                .collect();
+        }
+
+        self.render_list.iter()
+            .map(|index| self.instances[*index])
+            .map(|(entity, camera)| (entity, camera.clear_depth))
+            .collect()
+    }
+
+    /// Set whether the depth buffer is cleared before rendering this camera.
+    /// Disable for overlays that should draw over previous cameras.
+    pub fn set_clear_depth(&mut self, entity: entity::Handle, clear: bool) {
+        debug_validate_entity!(self, entity);
+        self.instances.iter_mut()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.clear_depth = clear;
+    }
+
+    /// Set the image this camera renders into (defaults to the swapchain). \
+    /// Offscreen targets determine the projection aspect ratio,
+    /// and are rendered before the swapchain so that they can be sampled
+    /// (see `render::Context::target_texture`).
+    pub fn set_target(
+        &mut self,
+        entity: entity::Handle,
+        target: render::RenderTarget,
+    ) {
+        #[cfg(debug_assertions)] {
+            if let render::RenderTarget::Offscreen { width, height } = target {
+                if width == 0 || height == 0 {
+                    panic!("Attempted to set empty offscreen render target");
+                }
+            }
+        }
+
+        debug_validate_entity!(self, entity);
+        self.instances.iter_mut()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.target = target;
+    }
+
+    pub fn get_target(&self, entity: entity::Handle) -> render::RenderTarget {
+        debug_validate_entity!(self, entity);
+        self.instances.iter()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.target
     }
 
     pub fn set_fov(&mut self, entity: entity::Handle, fov: f32) {
@@ -91,6 +274,21 @@ impl Manager {
             .1.fov
     }
 
+    /// Set the screen axis that the camera field of view is measured along
+    pub fn set_fov_axis(&mut self, entity: entity::Handle, axis: FovAxis) {
+        debug_validate_entity!(self, entity);
+        self.instances.iter_mut()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.fov_axis = axis;
+    }
+
+    pub fn get_fov_axis(&self, entity: entity::Handle) -> FovAxis {
+        debug_validate_entity!(self, entity);
+        self.instances.iter()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.fov_axis
+    }
+
     pub fn set_near(&mut self, entity: entity::Handle, near: f32) {
         debug_validate_entity!(self, entity);
         self.instances.iter_mut()
@@ -131,43 +329,620 @@ impl Manager {
             .1.overrule = Some(shared_ubo);
     }
 
-    /// Build a SharedUBO necessary for rendering from the active camera
+    /// Move camera toward the position of `target` plus `offset` every frame,
+    /// taking roughly `smooth_time` seconds to catch up.
+    pub fn set_follow(
+        &mut self,
+        entity: entity::Handle,
+        target: entity::Handle,
+        smooth_time: f32,
+        offset: alg::Vec3,
+    ) {
+        debug_assert!(smooth_time >= 0.0);
+        debug_validate_entity!(self, entity);
+        self.instances.iter_mut()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.follow = Some(Follow {
+                target,
+                smooth_time,
+                offset,
+                velocity: alg::Vec3::zero(),
+            });
+    }
+
+    /// Disable follow, reverting to manual control
+    pub fn clear_follow(&mut self, entity: entity::Handle) {
+        debug_validate_entity!(self, entity);
+        self.instances.iter_mut()
+            .find(|instance| instance.0 == entity).unwrap()
+            .1.follow = None;
+    }
+
+    /// Move camera along its forward axis
+    pub fn dolly(
+        &self,
+        entity: entity::Handle,
+        transforms: &mut transform::Manager,
+        distance: f32,
+    ) {
+        debug_validate_entity!(self, entity);
+
+        let (position, orientation, _) = transforms.get(entity);
+        let forward = orientation * alg::Vec3::fwd();
+
+        // World space, so that parented cameras move along their view
+        transforms.set_world_position(entity, position + forward * distance);
+    }
+
+    /// Move camera along its forward axis while adjusting the field of view
+    /// so that the plane through `target` keeps the same framing.
+    pub fn dolly_zoom(
+        &mut self,
+        entity: entity::Handle,
+        transforms: &mut transform::Manager,
+        target: alg::Vec3,
+        distance: f32,
+    ) {
+        debug_validate_entity!(self, entity);
+
+        let (position, orientation, _) = transforms.get(entity);
+        let forward = orientation * alg::Vec3::fwd();
+
+        let depth = (target - position).dot(forward);
+        let new_depth = depth - distance;
+
+        #[cfg(debug_assertions)] {
+            if depth <= 0.0 || new_depth <= 0.0 {
+                panic!("Attempted to dolly zoom past the target");
+            }
+        }
+
+        // Half-extent of the framed plane is depth * tan(fov / 2)
+        let fov = self.get_fov(entity);
+        let half = (0.5 * fov).to_radians().tan() * depth / new_depth;
+        self.set_fov(entity, 2.0 * half.atan().to_degrees());
+
+        self.dolly(entity, transforms, distance);
+    }
+
+    /// Update camera transforms that follow a target
+    pub(crate) fn update(
+        &mut self,
+        transforms: &mut transform::Manager,
+        delta: f32,
+    ) {
+        for &mut (entity, ref mut camera) in &mut self.instances {
+            let follow = match camera.follow {
+                Some(ref mut follow) => follow,
+                None => continue,
+            };
+
+            let position = transforms.get_position(entity);
+            let target = transforms.get_position(follow.target)
+                + follow.offset;
+
+            // Critically damped; independent of framerate
+            let position = position.smooth_damp(
+                target,
+                &mut follow.velocity,
+                follow.smooth_time,
+                None,
+                delta,
+            );
+
+            transforms.set_world_position(entity, position);
+        }
+    }
+
+    /// Write camera settings and render order for all instances. \
+    /// Overruled UBOs are not included.
+    pub(super) fn serialize(&self, writer: &mut scene::Writer) {
+        writer.usize(self.active);
+        writer.usize(self.render_list.len());
+
+        for index in &self.render_list {
+            writer.usize(*index);
+        }
+
+        writer.usize(self.instances.len());
+
+        for &(entity, camera) in &self.instances {
+            writer.entity(entity);
+            writer.f32(camera.fov);
+            writer.bool(camera.fov_axis == FovAxis::Horizontal);
+            writer.f32(camera.near);
+            writer.f32(camera.far);
+            writer.bool(camera.clear_depth);
+
+            match camera.target {
+                render::RenderTarget::Offscreen { width, height } => {
+                    writer.bool(true);
+                    writer.u32(width);
+                    writer.u32(height);
+                },
+                render::RenderTarget::Swapchain => writer.bool(false),
+            }
+
+            match camera.follow {
+                Some(follow) => {
+                    writer.bool(true);
+                    writer.entity(follow.target);
+                    writer.f32(follow.smooth_time);
+                    writer.vec3(follow.offset);
+                },
+                None => writer.bool(false),
+            }
+        }
+    }
+
+    /// Register and initialize instances written by `serialize()`. \
+    /// The loaded active camera and render list replace the current ones.
+    pub(super) fn deserialize(
+        &mut self,
+        reader: &mut scene::Reader,
+        handles: &scene::Handles,
+    ) -> Result<(), String> {
+        // Loaded cameras are appended after existing instances
+        let offset = self.instances.len();
+
+        let active = reader.usize()?;
+        let mut render_list = Vec::new();
+
+        for _ in 0..reader.usize()? {
+            render_list.push(reader.usize()?);
+        }
+
+        let count = reader.usize()?;
+
+        for _ in 0..count {
+            let entity = handles.read(reader)?;
+
+            let mut camera = Camera::default();
+            camera.fov = reader.f32()?;
+            camera.fov_axis = if reader.bool()? {
+                FovAxis::Horizontal
+            } else {
+                FovAxis::Vertical
+            };
+            camera.near = reader.f32()?;
+            camera.far = reader.f32()?;
+            camera.clear_depth = reader.bool()?;
+
+            if reader.bool()? {
+                camera.target = render::RenderTarget::Offscreen {
+                    width: reader.u32()?,
+                    height: reader.u32()?,
+                };
+            }
+
+            if reader.bool()? {
+                camera.follow = Some(Follow {
+                    target: handles.read(reader)?,
+                    smooth_time: reader.f32()?,
+                    offset: reader.vec3()?,
+                    velocity: alg::Vec3::zero(),
+                });
+            }
+
+            self.instances.push((entity, camera));
+        }
+
+        if count > 0 {
+            if active >= count || render_list.iter().any(|i| *i >= count) {
+                return Err("Invalid camera index in scene data".into());
+            }
+
+            self.active = offset + active;
+            self.render_list = render_list.iter()
+                .map(|index| offset + index)
+                .collect();
+        }
+
+        Ok(())
+    }
+
+    /// Build a SharedUBO necessary for rendering for every camera
+    /// in the render list (or the active camera), in order,
+    /// paired with whether depth should be cleared first
+    /// and the target to render into
     pub(crate) fn compute(
         &mut self,
         transforms: &transform::Manager,
         screen: ::ScreenData,
-    ) -> render::SharedUBO {
-        #[cfg(debug_assertions)] {
-            use components::Component;
-            if self.count() == 0 {
-                panic!("There are no cameras registered");
+    ) -> Vec<(render::SharedUBO, bool, render::RenderTarget)> {
+        // Render from the origin with default settings until a camera exists
+        if self.instances.is_empty() {
+            if !self.warned_empty {
+                eprintln!("Warning: No cameras registered; using default");
+                self.warned_empty = true;
             }
+
+            let camera = Camera::default();
+
+            return vec![(
+                self.project(
+                    camera,
+                    alg::Vec3::zero(),
+                    alg::Quat::id(),
+                    screen,
+                ),
+                camera.clear_depth,
+                camera.target,
+            )];
+        }
+
+        if self.render_list.is_empty() {
+            debug_assert!(self.active < self.instances.len());
+
+            return vec![(
+                self.build(self.active, transforms, screen),
+                self.instances[self.active].1.clear_depth,
+                self.instances[self.active].1.target,
+            )];
         }
 
-        debug_assert!(self.active < self.instances.len());
+        self.render_list.iter()
+            .map(|index| (
+                self.build(*index, transforms, screen),
+                self.instances[*index].1.clear_depth,
+                self.instances[*index].1.target,
+            )).collect()
+    }
+
+    fn build(
+        &self,
+        index: usize,
+        transforms: &transform::Manager,
+        screen: ::ScreenData,
+    ) -> render::SharedUBO {
+        debug_assert!(index < self.instances.len());
 
-        // Get active entity and camera
-        let (entity, camera) = self.instances[self.active];
+        // Get entity and camera
+        let (entity, camera) = self.instances[index];
 
         // Return overridden shared UBO if set
         if let Some(shared_ubo) = camera.overrule { return shared_ubo }
 
-        // Get transform data for active camera entity
+        // Get transform data for camera entity
         debug_validate_entity!(transforms, entity);
         let (position, orientation, _) = transforms.get(entity);
 
+        self.project(camera, position, orientation, screen)
+    }
+
+    fn project(
+        &self,
+        camera: Camera,
+        position: alg::Vec3,
+        orientation: alg::Quat,
+        screen: ::ScreenData,
+    ) -> render::SharedUBO {
         /* Build view and projection matrices */
 
         let view = orientation.conjugate().to_mat()
             * alg::Mat4::translation_vec(-position);
 
-        let projection = alg::Mat4::perspective(
-            camera.fov,
-            screen.width as f32 / screen.height as f32,
+        let (width, height) = camera.target.size(screen);
+        let aspect = width as f32 / height as f32;
+
+        let mut projection = alg::Mat4::perspective(
+            camera.fov_axis.to_vertical(camera.fov, aspect),
+            aspect,
             camera.near,
             camera.far,
         );
 
+        // Camera right vectors follow the left-handed convention;
+        // mirror them for right-handed worlds
+        if self.axes.handedness == alg::Handedness::Right {
+            projection = projection * alg::Mat4::scale(-1.0, 1.0, 1.0);
+        }
+
         render::SharedUBO::new(view, projection)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use entity;
+    use components::Component;
+    use components::transform;
+    use render;
+    use components::camera::*;
+
+    #[test]
+    fn no_cameras() {
+        let transforms = transform::Manager::new(1);
+        let mut cameras = Manager::new(1);
+
+        let screen = ::ScreenData { width: 1280, height: 720 };
+        let ubos = cameras.compute(&transforms, screen);
+
+        assert!(ubos.len() == 1);
+        assert!(ubos[0].2 == render::RenderTarget::Swapchain);
+
+        // Identity view with a default perspective
+        let (view, projection) = (ubos[0].0.view(), ubos[0].0.projection());
+        assert!(view.approx_eq(alg::Mat4::id(), 0.0001));
+
+        let point = projection * alg::Vec3::fwd() * 10.0;
+        eprintln!("Projected: {}", point);
+        assert!(point.x.abs() < 0.0001 && point.y.abs() < 0.0001);
+        assert!(point.z > 0.0 && point.z < 1.0);
+    }
+
+    #[test]
+    fn active_handle() {
+        let mut entities = entity::Manager::new(3);
+        let mut cameras = Manager::new(3);
+
+        let first = entities.add();
+        let middle = entities.add();
+        let last = entities.add();
+
+        for camera in &[first, middle, last] {
+            cameras.register(*camera);
+        }
+
+        cameras.set_active(middle);
+        assert!(cameras.active() == Some(middle));
+
+        // Removing another camera shifts indices
+        cameras.remove(first);
+        assert!(cameras.active() == Some(middle));
+        assert!(!cameras.registered(first));
+        assert!(cameras.count() == 2);
+
+        cameras.set_render_list(&[1, 0]);
+        cameras.remove(middle);
+        assert!(cameras.active() == Some(last));
+
+        cameras.remove(last);
+        assert!(cameras.active() == None);
+    }
+
+    #[test]
+    fn render_list() {
+        let mut entities = entity::Manager::new(2);
+        let mut cameras = Manager::new(2);
+
+        let world = entities.add();
+        let overlay = entities.add();
+        cameras.register(world);
+        cameras.register(overlay);
+
+        cameras.set_clear_depth(overlay, false);
+        cameras.set_render_list(&[0, 1]);
+
+        let list = cameras.render_list();
+        assert!(list.len() == 2);
+        assert!(list[0].0 == world && list[0].1);
+        assert!(list[1].0 == overlay && !list[1].1);
+
+        // Reverts to single camera
+        cameras.set_active(overlay);
+
+        let list = cameras.render_list();
+        assert!(list.len() == 1);
+        assert!(list[0].0 == overlay);
+    }
+
+    #[test]
+    fn follow() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = transform::Manager::new(2);
+        let mut cameras = Manager::new(1);
+
+        let camera = entities.add();
+        let target = entities.add();
+        transforms.register(camera);
+        transforms.register(target);
+        cameras.register(camera);
+
+        let offset = alg::Vec3::new(0.0, 1.0, -2.0);
+        let goal = alg::Vec3::new(4.0, 0.0, 0.0);
+        transforms.set_position(target, goal - offset);
+        cameras.set_follow(camera, target, 0.5, offset);
+
+        let mut last = transforms.get_position(camera).dist(goal);
+        for _ in 0..120 {
+            cameras.update(&mut transforms, 1.0 / 60.0);
+
+            let position = transforms.get_position(camera);
+            let distance = position.dist(goal);
+
+            // Approach monotonically without overshooting
+            assert!(distance < last);
+            assert!(position.x >= 0.0 && position.x <= goal.x);
+            last = distance;
+        }
+
+        eprintln!("Distance: {}", last);
+        assert!(last < 0.1);
+
+        // Manual control
+        cameras.clear_follow(camera);
+        transforms.set_position(camera, alg::Vec3::zero());
+        cameras.update(&mut transforms, 1.0 / 60.0);
+        assert!(transforms.get_position(camera) == alg::Vec3::zero());
+    }
+
+    #[test]
+    fn follow_parented() {
+        let mut entities = entity::Manager::new(3);
+        let mut transforms = transform::Manager::new(3);
+        let mut cameras = Manager::new(1);
+
+        let rig = entities.add();
+        let camera = entities.add();
+        let target = entities.add();
+        transforms.register(rig);
+        transforms.register(camera);
+        transforms.register(target);
+        cameras.register(camera);
+
+        // Camera mounted on a moved, rotated, and scaled rig
+        transforms.set(
+            rig,
+            alg::Vec3::new(-3.0, 2.0, 1.0),
+            alg::Quat::axis_angle(alg::Vec3::up(), 1.0),
+            alg::Vec3::one() * 2.0,
+        );
+
+        transforms.parent(camera, rig);
+
+        let goal = alg::Vec3::new(4.0, 0.0, 0.0);
+        transforms.set_position(target, goal);
+        cameras.set_follow(camera, target, 0.5, alg::Vec3::zero());
+
+        for _ in 0..240 {
+            cameras.update(&mut transforms, 1.0 / 60.0);
+        }
+
+        // Converges on the target in world space
+        let error = transforms.get_position(camera).dist(goal);
+        eprintln!("Error: {}", error);
+        assert!(error < 0.01);
+    }
+
+    #[test]
+    fn target() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = transform::Manager::new(2);
+        let mut cameras = Manager::new(2);
+
+        let main = entities.add();
+        let minimap = entities.add();
+
+        for &camera in &[main, minimap] {
+            transforms.register(camera);
+            cameras.register(camera);
+        }
+
+        let offscreen = render::RenderTarget::Offscreen {
+            width: 256,
+            height: 256,
+        };
+
+        cameras.set_target(minimap, offscreen);
+        cameras.set_render_list(&[1, 0]);
+
+        assert!(cameras.get_target(main) == render::RenderTarget::Swapchain);
+        assert!(cameras.get_target(minimap) == offscreen);
+
+        let screen = ::ScreenData { width: 1280, height: 720 };
+        let ubos = cameras.compute(&transforms, screen);
+
+        assert!(ubos[0].2 == offscreen);
+        assert!(ubos[1].2 == render::RenderTarget::Swapchain);
+
+        // Aspect ratio follows the target dimensions
+        let aspect = |ubo: render::SharedUBO| {
+            let projection = ubo.projection();
+            (projection.y1 / projection.x0).abs()
+        };
+
+        let error = (aspect(ubos[0].0) - 1.0).abs()
+            + (aspect(ubos[1].0) - 1280.0 / 720.0).abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn dolly_zoom() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut cameras = Manager::new(1);
+
+        let camera = entities.add();
+        transforms.register(camera);
+        cameras.register(camera);
+        transforms.set_position(camera, alg::Vec3::fwd() * -4.0);
+
+        let screen = ::ScreenData { width: 1280, height: 720 };
+        let target = alg::Vec3::fwd();
+        let edge = target + alg::Vec3::up() * 0.5;
+
+        // Projected height of the target in clip space
+        let size = |cameras: &mut Manager, transforms: &transform::Manager| {
+            let ubo = cameras.compute(transforms, screen)[0].0;
+            let view_projection = ubo.projection() * ubo.view();
+            let project = |point: alg::Vec3| {
+                let clip = view_projection * point;
+                let depth = (ubo.view() * point).z; // Perspective divide
+                clip.y / depth
+            };
+
+            (project(edge) - project(target)).abs()
+        };
+
+        let before = size(&mut cameras, &transforms);
+        cameras.dolly_zoom(camera, &mut transforms, target, 3.0);
+        let after = size(&mut cameras, &transforms);
+
+        let error = (before - after).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Closer camera, wider view
+        assert!(transforms.get_position(camera) == alg::Vec3::fwd() * -1.0);
+        assert!(cameras.get_fov(camera) > DEFAULT_FOV);
+
+        // Plain dolly keeps the field of view
+        let fov = cameras.get_fov(camera);
+        cameras.dolly(camera, &mut transforms, -1.0);
+        assert!(transforms.get_position(camera) == alg::Vec3::fwd() * -2.0);
+        assert!(cameras.get_fov(camera) == fov);
+    }
+
+    #[test]
+    fn dolly_parented() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = transform::Manager::new(2);
+        let mut cameras = Manager::new(1);
+
+        let rig = entities.add();
+        let camera = entities.add();
+        transforms.register(rig);
+        transforms.register(camera);
+        cameras.register(camera);
+        transforms.parent(camera, rig);
+
+        // Scaled and turned rig
+        transforms.set_position(rig, alg::Vec3::right() * 2.0);
+        transforms.set_scale(rig, alg::Vec3::one() * 2.0);
+        transforms.set_orientation(
+            rig,
+            alg::Quat::axis_angle(alg::Vec3::up(), 1.0),
+        );
+
+        let start = transforms.get_position(camera);
+        let forward = transforms.get_orientation(camera) * alg::Vec3::fwd();
+        cameras.dolly(camera, &mut transforms, 3.0);
+
+        // Moves the given distance in world space
+        let error = transforms.get_position(camera)
+            .dist(start + forward * 3.0);
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn horizontal_fov() {
+        let aspect = 16.0 / 9.0;
+        let horizontal = 90.0f32;
+        let vertical = FovAxis::Horizontal.to_vertical(horizontal, aspect);
+
+        let error = ((0.5 * vertical).to_radians().tan() * aspect
+            - (0.5 * horizontal).to_radians().tan()).abs()
+            + (FovAxis::Vertical.to_vertical(horizontal, aspect) - horizontal)
+                .abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+        assert!(vertical < horizontal);
+    }
+}
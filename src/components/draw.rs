@@ -8,13 +8,14 @@ use components;
 use components::transform;
 use components::softbody;
 use components::light;
+use components::camera;
 
 macro_rules! debug_validate_handle {
-    ($self: ident, $handle: expr, $entity: expr) => {
+    ($self: ident, $handles: expr, $entity: expr) => {
         #[cfg(debug_assertions)] {
-            if $handle.is_none() {
+            if $handles.is_empty() {
                 panic!(
-                    "Draw instance handle for entity {} is None. \
+                    "Draw instance handles for entity {} are empty. \
                     You probably attempted to use the Draw component \
                     before binding a model to it.",
                     $entity,
@@ -24,26 +25,39 @@ macro_rules! debug_validate_handle {
     }
 }
 
-macro_rules! get_handle {
+macro_rules! get_handles {
     ($self: ident, $entity: expr) => {{
         debug_validate_entity!($self, $entity);
-        let handle = $self.handles[&$entity];
-        debug_validate_handle!($self, handle, $entity);
-        handle.unwrap()
+        let handles = &$self.handles[&$entity];
+        debug_validate_handle!($self, handles, $entity);
+        handles
     }}
 }
 
+/// Orientation override that keeps an instance facing the camera
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Billboard {
+    Off,
+    Spherical,   // Forward axis points at the camera
+    Cylindrical, // Rotates about the world up axis only
+}
+
 pub struct Manager {
+    // One instance per bound model (sub-mesh)
     handles: fnv::FnvHashMap<
         entity::Handle,
-        Option<render::InstanceHandle>,
+        Vec<render::InstanceHandle>,
     >,
+    billboards: fnv::FnvHashMap<entity::Handle, Billboard>,
     pub instances: render::Instances,
+    lights_culled: usize, // Over all instances, during the last transfer
 }
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
-        self.handles.insert(entity, None);
+        debug_validate_unregistered!(self, entity);
+
+        self.handles.insert(entity, Vec::with_capacity(1));
     }
 
     fn registered(&self, entity: entity::Handle) -> bool {
@@ -61,10 +75,12 @@ impl Manager {
     pub fn new(hint: usize, instances: render::Instances) -> Manager {
         Manager {
             instances,
+            lights_culled: 0,
             handles: fnv::FnvHashMap::with_capacity_and_hasher(
                 hint,
                 Default::default(),
             ),
+            billboards: fnv::FnvHashMap::default(),
         }
     }
 
@@ -83,59 +99,138 @@ impl Manager {
         &mut self,
         entity: entity::Handle,
         model_index: usize,
+    ) {
+        self.bind_models(entity, &[model_index]);
+    }
+
+    /// Set several models (sub-meshes) that the draw component will render
+    /// for this entity under its transform, given their unique indices.
+    /// For now, this can only be done once.
+    pub fn bind_models(
+        &mut self,
+        entity: entity::Handle,
+        model_indices: &[usize],
     ) {
         debug_validate_entity!(self, entity);
-        debug_assert!(self.handles[&entity].is_none());
+        debug_assert!(self.handles[&entity].is_empty());
+        debug_assert!(!model_indices.is_empty());
+
+        let handles = model_indices.iter()
+            .map(|index| self.instances.add(
+                render::InstanceUBO::default(),
+                *index,
+            )).collect();
 
-        let handle = self.instances.add(
-            render::InstanceUBO::default(),
-            model_index,
-        );
+        *self.handles.get_mut(&entity).unwrap() = handles;
+    }
 
-        *self.handles.get_mut(&entity).unwrap() = Some(handle);
+    /// Returns instance handles of every model bound to this entity
+    pub fn get_handles(
+        &self,
+        entity: entity::Handle,
+    ) -> &[render::InstanceHandle] {
+        get_handles!(self, entity)
     }
 
     /// Stop entity from being rendered
     pub fn hide(&mut self, entity: entity::Handle) {
-        let handle = get_handle!(self, entity);
-        self.instances.update_meta(
-            handle,
-            render::InstanceMeta::new(true),
-        );
+        for handle in get_handles!(self, entity) {
+            self.instances.update_meta(
+                *handle,
+                render::InstanceMeta::new(true),
+            );
+        }
     }
 
     /// Resume rendering of entity (idempotent)
     pub fn unhide(&mut self, entity: entity::Handle) {
-        let handle = get_handle!(self, entity);
-        self.instances.update_meta(
-            handle,
-            render::InstanceMeta::new(false),
-        );
+        for handle in get_handles!(self, entity) {
+            self.instances.update_meta(
+                *handle,
+                render::InstanceMeta::new(false),
+            );
+        }
+    }
+
+    /// Override entity rotation so that it faces the active camera,
+    /// keeping its position and scale
+    pub fn set_billboard(&mut self, entity: entity::Handle, mode: Billboard) {
+        debug_validate_entity!(self, entity);
+
+        if mode == Billboard::Off {
+            self.billboards.remove(&entity);
+        } else {
+            self.billboards.insert(entity, mode);
+        }
     }
 
-    // Update
+    pub fn get_billboard(&self, entity: entity::Handle) -> Billboard {
+        debug_validate_entity!(self, entity);
+
+        self.billboards.get(&entity)
+            .cloned()
+            .unwrap_or(Billboard::Off)
+    }
+
+    /// Returns the number of lights rejected by culling,
+    /// summed over all instances, during the last update
+    pub fn lights_culled(&self) -> usize {
+        self.lights_culled
+    }
+
+    // Update, interpolating transforms by `alpha` between fixed steps
     pub(crate) fn transfer(
         &mut self,
         transforms: &transform::Manager,
         softbodies: &softbody::Manager,
         lights: &light::Manager,
+        cameras: &camera::Manager,
+        alpha: f32,
     ) {
-        for (entity, instance) in &self.handles {
-            debug_validate_handle!(self, instance, entity);
+        let active_lights = lights.active_count();
+        self.lights_culled = 0;
+
+        let viewer = cameras.active()
+            .map(|camera| transforms.get_position(camera));
+
+        let up = cameras.axes().up();
+
+        for (entity, handles) in &self.handles {
+            debug_validate_handle!(self, handles, entity);
 
             // Get transform component data
             debug_validate_entity!(transforms, *entity);
-            let transform = transforms.get(*entity);
+            let scale = transforms.get_scale(*entity);
+            let (position, orientation) = transforms.get_interpolated(
+                *entity,
+                alpha,
+            );
+
+            // Cull lights around the rendered position
+            let (center, radius) = transforms.world_bounds(*entity);
+            let center = center - transforms.get_position(*entity) + position;
+
+            let orientation = match (self.billboards.get(entity), viewer) {
+                (Some(mode), Some(viewer)) => {
+                    billboard(*mode, position, viewer, up)
+                        .unwrap_or(orientation)
+                },
+                _ => orientation,
+            };
 
             // Build uniform buffer object
             let ubo = {
                 let model = alg::Mat4::transform(
-                    transform.0,
-                    transform.1,
-                    transform.2,
+                    position,
+                    orientation,
+                    scale,
                 );
 
-                let instance_lights = lights.cull(transform.0);
+                let instance_lights = lights.cull_bounds(center, radius);
+
+                self.lights_culled += active_lights - instance_lights.iter()
+                    .filter(|light| light.radius != 0.0)
+                    .count();
 
                 render::InstanceUBO::new(
                     model,
@@ -145,8 +240,194 @@ impl Manager {
                 )
             };
 
-            // Update renderer
-            self.instances.update(instance.unwrap(), ubo);
+            // Update renderer (sub-meshes share the entity transform)
+            for handle in handles {
+                self.instances.update(*handle, ubo);
+            }
+        }
+    }
+}
+
+// Returns orientation facing the viewer,
+// or None if the viewer direction is degenerate
+fn billboard(
+    mode: Billboard,
+    position: alg::Vec3,
+    viewer: alg::Vec3,
+    up: alg::Vec3,
+) -> Option<alg::Quat> {
+    let mut fwd = viewer - position;
+
+    if mode == Billboard::Cylindrical {
+        fwd = fwd - up * fwd.dot(up);
+    }
+
+    if fwd.mag_squared() < 0.000001 {
+        return None;
+    }
+
+    // Fall back to another reference axis when looking straight along up
+    let fwd = fwd.norm();
+    let reference = if fwd.cross(up).mag_squared() < 0.000001 {
+        up.cross(alg::Vec3::right()).cross(up)
+    } else {
+        up
+    };
+
+    Some(alg::Quat::look_at(position, position + fwd, reference))
+}
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use render;
+    use entity;
+    use components::Component;
+    use components::transform;
+    use components::softbody;
+    use components::light;
+    use components::camera;
+    use components::draw::*;
+
+    #[test]
+    fn bind_models() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let softbodies = softbody::Manager::new(1, 1, 1);
+        let lights = light::Manager::new(1);
+        let cameras = camera::Manager::new(1);
+
+        let names = vec!["body".to_string(), "wheels".to_string()];
+        let instances = render::Instances::new(2, &names, None);
+        let mut draws = Manager::new(1, instances);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        transforms.set_position(entity, alg::Vec3::new(1.0, 2.0, 3.0));
+
+        draws.register(entity);
+        draws.bind_models(entity, &[0, 1]);
+        draws.transfer(&transforms, &softbodies, &lights, &cameras, 1.0);
+
+        let handles = draws.get_handles(entity);
+        assert!(handles.len() == 2);
+        assert!(draws.instances.count() == 2);
+
+        let expected = alg::Mat4::translation(1.0, 2.0, 3.0);
+
+        for handle in handles {
+            let model = draws.instances.get(*handle).0.model();
+            let error = (model * alg::Vec3::zero())
+                .dist(expected * alg::Vec3::zero());
+
+            eprintln!("Error: {}", error);
+            assert!(error < 0.0001);
         }
     }
+
+    #[test]
+    fn interpolate() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let softbodies = softbody::Manager::new(1, 1, 1);
+        let lights = light::Manager::new(1);
+        let cameras = camera::Manager::new(1);
+
+        let names = vec!["body".to_string()];
+        let instances = render::Instances::new(1, &names, None);
+        let mut draws = Manager::new(1, instances);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        transforms.set_position(entity, alg::Vec3::right() * 2.0);
+
+        draws.register(entity);
+        draws.bind_model_index(entity, 0);
+
+        // Fixed step moves the entity from its stored position
+        transforms.store_last();
+        transforms.set_position(entity, alg::Vec3::right() * 4.0);
+        draws.transfer(&transforms, &softbodies, &lights, &cameras, 0.25);
+
+        let handle = draws.get_handles(entity)[0];
+        let model = draws.instances.get(handle).0.model();
+        let error = (model * alg::Vec3::zero()).dist(alg::Vec3::right() * 2.5);
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn billboard() {
+        let mut entities = entity::Manager::new(3);
+        let mut transforms = transform::Manager::new(3);
+        let softbodies = softbody::Manager::new(1, 1, 1);
+        let lights = light::Manager::new(1);
+        let mut cameras = camera::Manager::new(1);
+
+        let names = vec!["quad".to_string()];
+        let instances = render::Instances::new(1, &names, None);
+        let mut draws = Manager::new(2, instances);
+
+        let camera = entities.add();
+        transforms.register(camera);
+        transforms.set_position(camera, alg::Vec3::new(3.0, 4.0, -2.0));
+        cameras.register(camera);
+
+        let spherical = entities.add();
+        let cylindrical = entities.add();
+
+        for entity in &[spherical, cylindrical] {
+            transforms.register(*entity);
+            transforms.set_position(*entity, alg::Vec3::new(1.0, 0.0, 2.0));
+            transforms.set_scale(*entity, alg::Vec3::one() * 2.0);
+            transforms.set_orientation(
+                *entity,
+                alg::Quat::axis_angle(alg::Vec3::new(1.0, 2.0, 3.0), 1.3),
+            );
+
+            draws.register(*entity);
+            draws.bind_model_index(*entity, 0);
+        }
+
+        draws.set_billboard(spherical, Billboard::Spherical);
+        draws.set_billboard(cylindrical, Billboard::Cylindrical);
+        draws.transfer(&transforms, &softbodies, &lights, &cameras, 1.0);
+
+        let center = alg::Vec3::new(1.0, 0.0, 2.0);
+        let to_camera = alg::Vec3::new(3.0, 4.0, -2.0) - center;
+
+        let model = |entity| {
+            let handle = draws.get_handles(entity)[0];
+            draws.instances.get(handle).0.model()
+        };
+
+        let model_fwd = |model: alg::Mat4| {
+            (model * alg::Vec3::fwd() - model * alg::Vec3::zero()).norm()
+        };
+
+        // Position and scale are preserved
+        let model = (model(spherical), model(cylindrical));
+        let error = (model.0 * alg::Vec3::zero()).dist(center);
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        let scale = (model.0 * alg::Vec3::fwd()).dist(center);
+        let error = (scale - 2.0).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        let error = model_fwd(model.0).dist(to_camera.norm());
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Yaw only
+        let flat = alg::Vec3::new(to_camera.x, 0.0, to_camera.z).norm();
+        let error = model_fwd(model.1).dist(flat);
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        draws.set_billboard(spherical, Billboard::Off);
+        assert!(draws.get_billboard(spherical) == Billboard::Off);
+    }
 }
@@ -69,6 +69,8 @@ pub struct Manager {
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
+        debug_validate_unregistered!(self, entity);
+
         self.instances.insert(
             entity,
             render::Text::empty_2d_instance(),
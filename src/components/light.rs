@@ -7,11 +7,38 @@ use entity;
 use components;
 
 use components::transform;
+use components::scene;
+
+use components::Component;
+
+// Near plane for point light shadow projections
+const SHADOW_NEAR: f32 = 0.01;
+
+/// Rectangular panel data for area lights
+#[derive(Clone, Copy, Debug)]
+struct Area {
+    width: f32,
+    height: f32,
+    direction: alg::Vec3, // Normalized
+    position: alg::Vec3, // Updated from transform component
+}
+
+/// Number of lights of each type, for diagnostics
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LightCounts {
+    pub directional: usize,
+    pub point: usize,
+    pub spot: usize, // Always zero until spot lights are supported
+    pub hemisphere: usize,
+    pub area: usize,
+}
 
 /// Builder pattern for lights
 pub struct LightBuilder<'a> {
     manager: &'a mut Manager,
     light: render::Light,
+    area: Option<Area>,
+    casts_shadows: bool,
 }
 
 impl<'a> LightBuilder<'a> {
@@ -24,12 +51,15 @@ impl<'a> LightBuilder<'a> {
                 color: graphics::Color::white(),
                 radius: 0.0,
             },
+            area: None,
+            casts_shadows: false,
         }
     }
 
     /// Create directional light with given vector \
-    /// Usage with `point_with_radius(...)` or
-    /// `hemisphere_with_lower_color(...)` results in undefined behavior
+    /// Usage with `point_with_radius(...)`,
+    /// `hemisphere_with_lower_color(...)`, or `area(...)`
+    /// results in undefined behavior
     pub fn directional(
         &mut self,
         direction: alg::Vec3,
@@ -41,8 +71,8 @@ impl<'a> LightBuilder<'a> {
 
     /// Create point light with given radius \
     /// Position is taken from the associated transform component \
-    /// Usage with `directional(...)` or `hemisphere_with_lower_color(...)`
-    /// results in undefined behavior
+    /// Usage with `directional(...)`, `hemisphere_with_lower_color(...)`,
+    /// or `area(...)` results in undefined behavior
     pub fn point_with_radius(
         &mut self,
         radius: f32,
@@ -53,8 +83,8 @@ impl<'a> LightBuilder<'a> {
 
     /// Create hemisphere light with given lower color \
     /// Use `color(...)` to set the upper color field
-    /// Usage with `directional(...)` or `point_with_radius(...)` results in
-    /// undefined behavior
+    /// Usage with `directional(...)`, `point_with_radius(...)`,
+    /// or `area(...)` results in undefined behavior
     pub fn hemisphere_with_lower_color(
         &mut self,
         lower_color: graphics::Color,
@@ -65,6 +95,36 @@ impl<'a> LightBuilder<'a> {
         self
     }
 
+    /// Create rectangular area light with given dimensions,
+    /// facing the given direction \
+    /// Position is taken from the associated transform component \
+    /// Affects instances in front of the panel within a distance
+    /// of its largest dimension \
+    /// Shaded as a soft directional light: brightness does not fall off
+    /// with distance or vary with position across the panel \
+    /// Usage with `directional(...)`, `point_with_radius(...)`,
+    /// or `hemisphere_with_lower_color(...)` results in undefined behavior
+    pub fn area(
+        &mut self,
+        width: f32,
+        height: f32,
+        direction: alg::Vec3,
+    ) -> &mut LightBuilder<'a> {
+        debug_assert!(width > 0.0 && height > 0.0);
+
+        self.light.vector = -direction.norm();
+        self.light.radius = -3.0; // Sentinel
+
+        self.area = Some(Area {
+            width,
+            height,
+            direction: direction.norm(),
+            position: alg::Vec3::zero(),
+        });
+
+        self
+    }
+
     pub fn color(&mut self, color: graphics::Color) -> &mut LightBuilder<'a> {
         self.light.color = color;
         self
@@ -76,6 +136,12 @@ impl<'a> LightBuilder<'a> {
         self
     }
 
+    /// Mark light as a shadow caster
+    pub fn casts_shadows(&mut self) -> &mut LightBuilder<'a> {
+        self.casts_shadows = true;
+        self
+    }
+
     /// Finalize
     pub fn for_entity(&mut self, entity: entity::Handle) {
         #[cfg(debug_assertions)] {
@@ -98,20 +164,79 @@ impl<'a> LightBuilder<'a> {
             }
         }
 
+        #[cfg(debug_assertions)] {
+            if self.light.radius == -3.0
+                && self.light.vector == alg::Vec3::zero()
+            {
+                panic!("Area light has no direction");
+            }
+        }
+
         self.manager.set(entity, self.light);
+
+        match self.area {
+            Some(area) => { self.manager.areas.insert(entity, area); },
+            None => { self.manager.areas.remove(&entity); },
+        }
+
+        self.manager.set_casts_shadows(entity, self.casts_shadows);
+    }
+}
+
+impl Area {
+    /// Check if a sphere is (partially) in front of the panel
+    /// and within reach
+    fn reaches(&self, center: alg::Vec3, radius: f32) -> bool {
+        let local = center - self.position;
+
+        if local.dot(self.direction) <= -radius {
+            return false;
+        }
+
+        // Build panel axes
+        let reference = if self.direction.dot(alg::Vec3::up()).abs() < 0.99 {
+            alg::Vec3::up()
+        } else {
+            alg::Vec3::fwd()
+        };
+
+        let right = reference.cross(self.direction).norm();
+        let up = self.direction.cross(right);
+
+        // Closest point on panel
+        let half_width = 0.5 * self.width;
+        let half_height = 0.5 * self.height;
+        let u = f32::min(f32::max(local.dot(right), -half_width), half_width);
+        let v = f32::min(f32::max(local.dot(up), -half_height), half_height);
+        let closest = right * u + up * v;
+
+        local.dist(closest) < f32::max(self.width, self.height) + radius
     }
 }
 
 pub struct Manager {
     instances: fnv::FnvHashMap<entity::Handle, render::Light>,
+    areas: fnv::FnvHashMap<entity::Handle, Area>,
+    order: Vec<entity::Handle>, // Registration order, for stable iteration
+
+    // Kept separate from the GPU light data to preserve its layout
+    disabled: fnv::FnvHashSet<entity::Handle>,
+    shadow_casters: fnv::FnvHashSet<entity::Handle>,
 }
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
-        self.instances.insert(
+        debug_validate_unregistered!(self, entity);
+
+        let previous = self.instances.insert(
             entity,
             render::Light::default(),
         );
+
+        // Re-registering resets the light but keeps its place in the order
+        if previous.is_none() {
+            self.order.push(entity);
+        }
     }
 
     fn registered(&self, entity: entity::Handle) -> bool {
@@ -132,6 +257,10 @@ impl Manager {
                 hint,
                 Default::default(),
             ),
+            areas: fnv::FnvHashMap::default(),
+            order: Vec::with_capacity(hint),
+            disabled: fnv::FnvHashSet::default(),
+            shadow_casters: fnv::FnvHashSet::default(),
         }
     }
 
@@ -156,6 +285,11 @@ impl Manager {
         instance.color = color;
     }
 
+    pub fn get_color(&self, entity: entity::Handle) -> graphics::Color {
+        debug_validate_entity!(self, entity);
+        self.instances[&entity].color
+    }
+
     /// Sets direction vector of directional light.
     /// Normalizes input.
     pub fn set_direction(
@@ -179,20 +313,247 @@ impl Manager {
         instance.vector = vector;
     }
 
-    /// Update point light positions from transform component
+    /// Enable or disable light without losing its configuration. \
+    /// Disabled lights are skipped entirely when culling.
+    pub fn set_enabled(&mut self, entity: entity::Handle, enabled: bool) {
+        debug_validate_entity!(self, entity);
+
+        if enabled {
+            self.disabled.remove(&entity);
+        } else {
+            self.disabled.insert(entity);
+        }
+    }
+
+    pub fn is_enabled(&self, entity: entity::Handle) -> bool {
+        debug_validate_entity!(self, entity);
+        !self.disabled.contains(&entity)
+    }
+
+    /// Returns the number of lights considered when culling
+    /// (enabled and not dummy lights)
+    pub(super) fn active_count(&self) -> usize {
+        self.iter()
+            .filter(|&(entity, light)| {
+                !self.disabled.contains(&entity) && light.radius != 0.0
+            }).count()
+    }
+
+    pub fn set_casts_shadows(
+        &mut self,
+        entity: entity::Handle,
+        casts_shadows: bool,
+    ) {
+        debug_validate_entity!(self, entity);
+
+        if casts_shadows {
+            self.shadow_casters.insert(entity);
+        } else {
+            self.shadow_casters.remove(&entity);
+        }
+    }
+
+    pub fn casts_shadows(&self, entity: entity::Handle) -> bool {
+        debug_validate_entity!(self, entity);
+        self.shadow_casters.contains(&entity)
+    }
+
+    /// Returns light-space view-projection matrix for rendering a depth map,
+    /// or `None` if the light does not cast shadows. \
+    /// Directional lights use an orthographic projection fit to
+    /// `scene_bounds` (min, max); point lights use a perspective projection
+    /// from the light position toward the center of the bounds. \
+    /// Other light types are not supported.
+    pub fn shadow_matrix(
+        &self,
+        entity: entity::Handle,
+        scene_bounds: (alg::Vec3, alg::Vec3),
+    ) -> Option<alg::Mat4> {
+        debug_validate_entity!(self, entity);
+
+        if !self.shadow_casters.contains(&entity) {
+            return None;
+        }
+
+        let light = self.instances[&entity];
+        let (min, max) = scene_bounds;
+        let center = (min + max) * 0.5;
+
+        // Bounding sphere of scene
+        let radius = min.dist(max) * 0.5;
+
+        let view = |position: alg::Vec3| {
+            let fwd = center - position;
+
+            // Avoid degenerate up vector
+            let up = if fwd.norm().dot(alg::Vec3::up()).abs() < 0.99 {
+                alg::Vec3::up()
+            } else {
+                alg::Vec3::fwd()
+            };
+
+            alg::Mat4::look_at_view(position, center, up)
+        };
+
+        // Directional
+        if light.radius == -1.0 {
+            // Vector points toward the light
+            let position = center + light.vector.norm() * radius;
+
+            let projection = alg::Mat4::orthographic(
+                -radius, radius,
+                -radius, radius,
+                0.0, 2.0 * radius,
+            );
+
+            Some(projection * view(position))
+        }
+
+        // Point
+        else if light.radius > 0.0 {
+            let projection = alg::Mat4::perspective(
+                90.0,
+                1.0,
+                SHADOW_NEAR,
+                light.radius,
+            );
+
+            Some(projection * view(light.vector))
+        }
+
+        else { None }
+    }
+
+    /// Tally lights by type. \
+    /// Lights with a radius of zero are not counted.
+    pub fn counts(&self) -> LightCounts {
+        let mut counts = LightCounts::default();
+
+        for light in self.instances.values() {
+            if light.radius == -1.0 { counts.directional += 1; }
+            else if light.radius == -2.0 { counts.hemisphere += 1; }
+            else if light.radius == -3.0 { counts.area += 1; }
+            else if light.radius > 0.0 { counts.point += 1; }
+        }
+
+        counts
+    }
+
+    /// Returns width and height of area light,
+    /// or `None` if the light is not an area light
+    pub fn get_area(&self, entity: entity::Handle) -> Option<(f32, f32)> {
+        debug_validate_entity!(self, entity);
+        self.areas.get(&entity).map(|area| (area.width, area.height))
+    }
+
+    /// Write light data and flags for all instances,
+    /// in registration order so that culling priority survives loading
+    pub(super) fn serialize(&self, writer: &mut scene::Writer) {
+        writer.usize(self.order.len());
+
+        for (entity, &light) in self.iter() {
+            writer.entity(entity);
+            writer.vec3(light.vector);
+            writer.f32(light.radius);
+            writer.color(light.color);
+            writer.f32(light.intensity);
+            writer.bool(!self.disabled.contains(&entity));
+            writer.bool(self.shadow_casters.contains(&entity));
+
+            match self.areas.get(&entity) {
+                Some(area) => {
+                    writer.bool(true);
+                    writer.f32(area.width);
+                    writer.f32(area.height);
+                    writer.vec3(area.direction);
+                },
+                None => writer.bool(false),
+            }
+        }
+    }
+
+    /// Register and initialize instances written by `serialize()`
+    pub(super) fn deserialize(
+        &mut self,
+        reader: &mut scene::Reader,
+        handles: &scene::Handles,
+    ) -> Result<(), String> {
+        for _ in 0..reader.usize()? {
+            let entity = handles.read(reader)?;
+
+            let light = render::Light {
+                vector: reader.vec3()?,
+                radius: reader.f32()?,
+                color: reader.color()?,
+                intensity: reader.f32()?,
+            };
+
+            let enabled = reader.bool()?;
+            let casts_shadows = reader.bool()?;
+
+            self.register(entity);
+            self.set(entity, light);
+            self.set_enabled(entity, enabled);
+            self.set_casts_shadows(entity, casts_shadows);
+
+            if reader.bool()? {
+                let area = Area {
+                    width: reader.f32()?,
+                    height: reader.f32()?,
+                    direction: reader.vec3()?,
+                    position: alg::Vec3::zero(), // Set on update
+                };
+
+                self.areas.insert(entity, area);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Update point and area light positions from transform component
     pub(crate) fn update(&mut self, transforms: &transform::Manager) {
-        for (entity, light) in &mut self.instances {
+        for entity in &self.order {
+            let light = self.instances.get_mut(entity).unwrap();
+
             if light.radius > 0.0 {
                 debug_validate_entity!(transforms, *entity);
                 light.vector = transforms.get_position(*entity);
             }
         }
+
+        for (entity, area) in &mut self.areas {
+            debug_validate_entity!(transforms, *entity);
+            area.position = transforms.get_position(*entity);
+        }
+    }
+
+    // Lights in registration order
+    fn iter<'a>(
+        &'a self,
+    ) -> impl Iterator<Item = (entity::Handle, &'a render::Light)> + 'a {
+        self.order.iter()
+            .map(move |entity| (*entity, &self.instances[entity]))
     }
 
-    /// Given a position, return the set of lights affecting it
-    pub(super) fn cull(
+    /// Given a position, return the set of lights affecting it. \
+    /// Equivalent to `cull_bounds(position, 0.0)`.
+    pub fn cull(
         &self,
         position: alg::Vec3,
+    ) -> [render::Light; render::MAX_INSTANCE_LIGHTS] {
+        self.cull_bounds(position, 0.0)
+    }
+
+    /// Given a bounding sphere, return the set of lights affecting it,
+    /// in registration order. \
+    /// When more lights reach the sphere than fit,
+    /// the earliest registered are kept. \
+    /// A radius of zero culls against a single point.
+    pub fn cull_bounds(
+        &self,
+        center: alg::Vec3,
+        radius: f32,
     ) -> [render::Light; render::MAX_INSTANCE_LIGHTS] {
         let mut instance_lights = [
             render::Light::default();
@@ -201,9 +562,28 @@ impl Manager {
 
         let mut i = 0;
 
-        for light in self.instances.values() {
+        for (entity, light) in self.iter() {
+            // Disabled light
+            if self.disabled.contains(&entity) {
+                continue;
+            }
+
+            // Area light--check distance to panel
+            if light.radius == -3.0 {
+                // Skip sentinel without panel data (e.g. from a raw light)
+                let reaches = self.areas.get(&entity)
+                    .map_or(false, |area| area.reaches(center, radius));
+
+                if !reaches {
+                    continue;
+                }
+
+                instance_lights[i] = *light; // Set light
+                i += 1;
+            }
+
             // Directional (or hemisphere)
-            if light.radius < 0.0 {
+            else if light.radius < 0.0 {
                 instance_lights[i] = *light; // Set light
                 i += 1;
             }
@@ -213,8 +593,8 @@ impl Manager {
                 continue;
             }
 
-            // Point light--check radius for containment
-            else if light.radius > position.dist(light.vector) {
+            // Point light--check radius for intersection
+            else if light.radius + radius > center.dist(light.vector) {
                 instance_lights[i] = *light; // Set light
                 i += 1;
             }
@@ -228,3 +608,222 @@ impl Manager {
         instance_lights
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use graphics;
+    use entity;
+    use components::Component;
+    use components::light::*;
+
+    #[test]
+    fn area() {
+        let mut entities = entity::Manager::new(1);
+        let mut lights = Manager::new(1);
+
+        let entity = entities.add();
+        lights.register(entity);
+
+        lights.build()
+            .area(2.0, 0.5, -alg::Vec3::up())
+            .for_entity(entity);
+
+        let light = lights.instances[&entity];
+        assert!(light.radius == -3.0);
+        assert!(light.vector.dist(alg::Vec3::up()) < 0.0001);
+        assert!(lights.get_area(entity) == Some((2.0, 0.5)));
+
+        // Below the panel
+        let below = lights.cull_bounds(alg::Vec3::new(0.5, -1.0, 0.0), 0.0);
+        assert!(below[0].radius == -3.0);
+
+        // Above the panel
+        let above = lights.cull_bounds(alg::Vec3::new(0.5, 1.0, 0.0), 0.0);
+        assert!(above[0].radius == 0.0);
+
+        // Out of reach
+        let far = lights.cull_bounds(alg::Vec3::new(0.0, -3.0, 0.0), 0.0);
+        assert!(far[0].radius == 0.0);
+
+        // Area sentinel without a panel is ignored
+        lights.areas.clear();
+        let missing = lights.cull_bounds(alg::Vec3::new(0.5, -1.0, 0.0), 0.0);
+        assert!(missing[0].radius == 0.0);
+    }
+
+    #[test]
+    fn directional_shadow() {
+        let mut entities = entity::Manager::new(1);
+        let mut lights = Manager::new(1);
+
+        let entity = entities.add();
+        lights.register(entity);
+
+        lights.build()
+            .directional(alg::Vec3::new(1.0, -2.0, 0.5))
+            .casts_shadows()
+            .for_entity(entity);
+
+        let min = alg::Vec3::new(-4.0, 0.0, -1.0);
+        let max = alg::Vec3::new(2.0, 3.0, 5.0);
+        let matrix = lights.shadow_matrix(entity, (min, max)).unwrap();
+
+        for i in 0..8 {
+            let corner = alg::Vec3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            );
+
+            let ndc = matrix * corner;
+            assert!(ndc.x >= -1.0 && ndc.x <= 1.0);
+            assert!(ndc.y >= -1.0 && ndc.y <= 1.0);
+            assert!(ndc.z >= 0.0 && ndc.z <= 1.0);
+        }
+
+        lights.set_casts_shadows(entity, false);
+        assert!(lights.shadow_matrix(entity, (min, max)).is_none());
+    }
+
+    #[test]
+    fn counts() {
+        let mut entities = entity::Manager::new(6);
+        let mut lights = Manager::new(6);
+
+        let handles: Vec<entity::Handle> = (0..6)
+            .map(|_| entities.add())
+            .collect();
+
+        for handle in &handles {
+            lights.register(*handle);
+        }
+
+        lights.build()
+            .directional(-alg::Vec3::up())
+            .for_entity(handles[0]);
+
+        lights.build()
+            .point_with_radius(2.0)
+            .for_entity(handles[1]);
+
+        lights.build()
+            .point_with_radius(8.0)
+            .for_entity(handles[2]);
+
+        lights.build()
+            .hemisphere_with_lower_color(graphics::Color::gray())
+            .for_entity(handles[3]);
+
+        lights.build()
+            .area(1.0, 1.0, alg::Vec3::fwd())
+            .for_entity(handles[4]);
+
+        // handles[5] remains a dummy light
+
+        assert!(lights.counts() == LightCounts {
+            directional: 1,
+            point: 2,
+            spot: 0,
+            hemisphere: 1,
+            area: 1,
+        });
+    }
+
+    #[test]
+    fn cull_bounds() {
+        let mut entities = entity::Manager::new(1);
+        let mut lights = Manager::new(1);
+
+        let entity = entities.add();
+        lights.register(entity);
+
+        lights.build()
+            .point_with_radius(2.0)
+            .for_entity(entity);
+
+        // Light misses the center but clips the bounding sphere
+        let center = alg::Vec3::new(3.0, 0.0, 0.0);
+        assert!(lights.cull_bounds(center, 0.0)[0].radius == 0.0);
+        assert!(lights.cull(center)[0].radius == 0.0);
+        assert!(lights.cull_bounds(center, 1.5)[0].radius == 2.0);
+    }
+
+    #[test]
+    fn cull_capacity() {
+        let count = render::MAX_INSTANCE_LIGHTS + 2;
+        let mut entities = entity::Manager::new(count);
+        let mut lights = Manager::new(count);
+
+        for _ in 0..count {
+            let entity = entities.add();
+            lights.register(entity);
+            lights.build()
+                .directional(alg::Vec3::up())
+                .for_entity(entity);
+        }
+
+        let culled = lights.cull_bounds(alg::Vec3::zero(), 1.0);
+
+        // Excess lights are dropped
+        assert!(culled.len() == render::MAX_INSTANCE_LIGHTS);
+        assert!(culled.iter().all(|light| light.radius == -1.0));
+    }
+
+    #[test]
+    fn disabled() {
+        let mut entities = entity::Manager::new(1);
+        let mut lights = Manager::new(1);
+
+        let entity = entities.add();
+        lights.register(entity);
+
+        lights.build()
+            .point_with_radius(4.0)
+            .for_entity(entity);
+
+        assert!(lights.cull_bounds(alg::Vec3::one(), 0.0)[0].radius == 4.0);
+
+        assert!(lights.active_count() == 1);
+
+        lights.set_enabled(entity, false);
+        assert!(!lights.is_enabled(entity));
+        assert!(lights.cull_bounds(alg::Vec3::one(), 0.0)[0].radius == 0.0);
+        assert!(lights.active_count() == 0);
+
+        // Configuration persists
+        lights.set_enabled(entity, true);
+        assert!(lights.cull_bounds(alg::Vec3::one(), 0.0)[0].radius == 4.0);
+    }
+
+    #[test]
+    fn cull_order() {
+        let count = render::MAX_INSTANCE_LIGHTS + 2;
+        let mut entities = entity::Manager::new(count);
+        let mut lights = Manager::new(count);
+
+        let handles: Vec<entity::Handle> = (0..count)
+            .map(|_| entities.add())
+            .collect();
+
+        // Register out of entity order
+        for (i, entity) in handles.iter().rev().enumerate() {
+            lights.register(*entity);
+            lights.build()
+                .directional(alg::Vec3::up())
+                .intensity(i as f32 + 1.0)
+                .for_entity(*entity);
+        }
+
+        for _ in 0..4 {
+            let culled = lights.cull_bounds(alg::Vec3::zero(), 1.0);
+
+            // Earliest registered lights are kept, in order
+            assert!(culled.len() == render::MAX_INSTANCE_LIGHTS);
+
+            for (i, light) in culled.iter().enumerate() {
+                assert!(light.intensity == i as f32 + 1.0);
+            }
+        }
+    }
+}
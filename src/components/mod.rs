@@ -17,6 +17,22 @@ macro_rules! debug_validate_entity {
     }
 }
 
+macro_rules! debug_validate_unregistered {
+    ($component: path, $entity: expr) => {
+        #[cfg(debug_assertions)] {
+            use components::Component;
+
+            if $component.registered($entity) {
+                panic!(
+                    "{} component already registered for entity {}",
+                    $component.debug_name(),
+                    $entity,
+                );
+            }
+        }
+    }
+}
+
 pub mod transform;
 pub mod camera;
 pub mod light;
@@ -25,10 +41,13 @@ pub mod softbody;
 pub mod bitmap;
 pub mod text;
 pub mod label;
+mod scene;
 
 use entity;
 
 pub trait Component {
+    /// Add component to entity. \
+    /// Each entity may only be registered once.
     fn register(&mut self, entity: entity::Handle);
     fn registered(&self, entity: entity::Handle) -> bool;
     fn count(&self) -> usize;
@@ -46,3 +65,60 @@ pub struct Container {
     pub texts:      text::Manager,
     pub labels:     label::Manager,
 }
+
+impl Container {
+    /// Returns every component manager, paired with its name
+    pub fn all(&self) -> [(&'static str, &dyn Component); 7] {
+        [
+            ("Transform", &self.transforms),
+            ("Camera",    &self.cameras),
+            ("Light",     &self.lights),
+            ("Draw",      &self.draws),
+            ("Softbody",  &self.softbodies),
+            ("Text",      &self.texts),
+            ("Label",     &self.labels),
+        ]
+    }
+
+    /// Returns the names of all components registered for `entity`
+    pub fn components_of(&self, entity: entity::Handle) -> Vec<&'static str> {
+        self.all().iter()
+            .filter(|&&(_, component)| component.registered(entity))
+            .map(|&(name, _)| name)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use render;
+    use entity;
+    use components::*;
+
+    #[test]
+    fn components_of() {
+        let mut entities = entity::Manager::new(2);
+        let mut components = Container {
+            transforms: transform::Manager::new(1),
+            cameras:    camera::Manager::new(1),
+            lights:     light::Manager::new(1),
+            draws:      draw::Manager::new(
+                1,
+                render::Instances::new(0, &Vec::new(), None),
+            ),
+            softbodies: softbody::Manager::new(1, 1, 1),
+            texts:      text::Manager::new(1),
+            labels:     label::Manager::new(1),
+        };
+
+        let entity = entities.add();
+        let other = entities.add();
+
+        components.transforms.register(entity);
+        components.lights.register(entity);
+        components.transforms.register(other);
+
+        assert!(components.components_of(entity) == vec!["Transform", "Light"]);
+        assert!(components.components_of(other) == vec!["Transform"]);
+    }
+}
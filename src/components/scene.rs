@@ -0,0 +1,366 @@
+use alg;
+use entity;
+use components;
+use graphics;
+
+// Leading bytes of every serialized scene
+const MAGIC: &[u8; 4] = b"NMGS";
+
+// Increment when the layout changes after a release
+const VERSION: u32 = 1;
+
+/// Little-endian binary writer for scene data
+pub(super) struct Writer {
+    bytes: Vec<u8>,
+    entities: Vec<u32>, // Indices of all written entities
+}
+
+impl Writer {
+    fn new() -> Writer {
+        Writer {
+            bytes: Vec::new(),
+            entities: Vec::new(),
+        }
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.bytes.push(value as u8);
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn usize(&mut self, value: usize) {
+        debug_assert!(value <= u32::max_value() as usize);
+        self.u32(value as u32);
+    }
+
+    pub fn f32(&mut self, value: f32) {
+        self.u32(value.to_bits());
+    }
+
+    pub fn vec3(&mut self, value: alg::Vec3) {
+        self.f32(value.x);
+        self.f32(value.y);
+        self.f32(value.z);
+    }
+
+    pub fn quat(&mut self, value: alg::Quat) {
+        self.f32(value.x);
+        self.f32(value.y);
+        self.f32(value.z);
+        self.f32(value.w);
+    }
+
+    pub fn color(&mut self, value: graphics::Color) {
+        self.f32(value.r);
+        self.f32(value.g);
+        self.f32(value.b);
+    }
+
+    pub fn usizes(&mut self, values: &[usize]) {
+        self.usize(values.len());
+        values.iter().for_each(|value| self.usize(*value));
+    }
+
+    pub fn vec3s(&mut self, values: &[alg::Vec3]) {
+        self.usize(values.len());
+        values.iter().for_each(|value| self.vec3(*value));
+    }
+
+    pub fn entity(&mut self, entity: entity::Handle) {
+        let index = entity.get_index();
+
+        self.entities.push(index);
+        self.u32(index);
+    }
+}
+
+/// Little-endian binary reader for scene data
+pub(super) struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, position: 0 }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        if self.position + count > self.bytes.len() {
+            return Err("Unexpected end of scene data".into());
+        }
+
+        let result = &self.bytes[self.position..self.position + count];
+        self.position += count;
+
+        Ok(result)
+    }
+
+    pub fn bool(&mut self) -> Result<bool, String> {
+        Ok(self.u8()? != 0)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(
+              (bytes[0] as u32)
+            | (bytes[1] as u32) << 8
+            | (bytes[2] as u32) << 16
+            | (bytes[3] as u32) << 24
+        )
+    }
+
+    pub fn usize(&mut self) -> Result<usize, String> {
+        Ok(self.u32()? as usize)
+    }
+
+    pub fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    pub fn vec3(&mut self) -> Result<alg::Vec3, String> {
+        Ok(alg::Vec3::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    pub fn quat(&mut self) -> Result<alg::Quat, String> {
+        Ok(
+            alg::Quat::new(self.f32()?, self.f32()?, self.f32()?, self.f32()?)
+        )
+    }
+
+    pub fn color(&mut self) -> Result<graphics::Color, String> {
+        Ok(graphics::Color::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    pub fn usizes(&mut self) -> Result<Vec<usize>, String> {
+        (0..self.usize()?).map(|_| self.usize()).collect()
+    }
+
+    pub fn vec3s(&mut self) -> Result<Vec<alg::Vec3>, String> {
+        (0..self.usize()?).map(|_| self.vec3()).collect()
+    }
+}
+
+/// Mapping from serialized entity indices to reconstructed handles
+pub(super) struct Handles {
+    map: Vec<(u32, entity::Handle)>,
+}
+
+impl Handles {
+    /// Read serialized entity and return its reconstructed handle
+    pub fn read(&self, reader: &mut Reader) -> Result<entity::Handle, String> {
+        let index = reader.u32()?;
+
+        self.map.iter()
+            .find(|&&(old, _)| old == index)
+            .map(|&(_, handle)| handle)
+            .ok_or(format!("Unknown entity {} in scene data", index))
+    }
+}
+
+impl components::Container {
+    /// Serialize transforms (with hierarchy), lights, cameras,
+    /// and softbody instances into a versioned binary layout. \
+    /// Draw and text components are not included.
+    pub fn serialize_scene(&self) -> Vec<u8> {
+        let mut body = Writer::new();
+
+        self.transforms.serialize(&mut body);
+        self.lights.serialize(&mut body);
+        self.cameras.serialize(&mut body);
+        self.softbodies.serialize(&mut body);
+
+        // Table of every entity referenced by the component data
+        let mut entities = body.entities;
+        entities.sort_unstable();
+        entities.dedup();
+
+        let mut writer = Writer::new();
+        writer.bytes.extend_from_slice(MAGIC);
+        writer.u32(VERSION);
+
+        writer.usize(entities.len());
+        for index in entities {
+            writer.u32(index);
+        }
+
+        writer.bytes.extend(body.bytes);
+        writer.bytes
+    }
+
+    /// Reconstruct a scene written by `serialize_scene()`,
+    /// creating new entities for it. \
+    /// Returns the new entity handles in serialized order. \
+    /// Invalid data leaves the scene and `entities` unchanged.
+    pub fn deserialize_scene(
+        &mut self,
+        entities: &mut entity::Manager,
+        bytes: &[u8],
+    ) -> Result<Vec<entity::Handle>, String> {
+        // Load into empty components first, so that an error partway
+        // through cannot leave entities or instances behind
+        load(
+            bytes,
+            &mut entity::Manager::new(1),
+            &mut components::transform::Manager::new(1),
+            &mut components::light::Manager::new(1),
+            &mut components::camera::Manager::new(1),
+            &mut components::softbody::Manager::new(1, 1, 1),
+        )?;
+
+        load(
+            bytes,
+            entities,
+            &mut self.transforms,
+            &mut self.lights,
+            &mut self.cameras,
+            &mut self.softbodies,
+        )
+    }
+}
+
+/// Create entities and components from scene data, stopping at the first error
+fn load(
+    bytes: &[u8],
+    entities: &mut entity::Manager,
+    transforms: &mut components::transform::Manager,
+    lights: &mut components::light::Manager,
+    cameras: &mut components::camera::Manager,
+    softbodies: &mut components::softbody::Manager,
+) -> Result<Vec<entity::Handle>, String> {
+    let mut reader = Reader::new(bytes);
+
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err("Invalid scene data".into());
+    }
+
+    let version = reader.u32()?;
+    if version != VERSION {
+        return Err(format!("Unsupported scene version {}", version));
+    }
+
+    let mut handles = Handles { map: Vec::new() };
+
+    for _ in 0..reader.usize()? {
+        let index = reader.u32()?;
+        handles.map.push((index, entities.add()));
+    }
+
+    transforms.deserialize(&mut reader, &handles)?;
+    lights.deserialize(&mut reader, &handles)?;
+    cameras.deserialize(&mut reader, &handles)?;
+    softbodies.deserialize(&mut reader, &handles)?;
+
+    Ok(handles.map.iter().map(|&(_, handle)| handle).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use render;
+    use entity;
+    use graphics;
+    use components::*;
+
+    fn container() -> Container {
+        Container {
+            transforms: transform::Manager::new(1),
+            cameras:    camera::Manager::new(1),
+            lights:     light::Manager::new(1),
+            draws:      draw::Manager::new(
+                1,
+                render::Instances::new(0, &Vec::new(), None),
+            ),
+            softbodies: softbody::Manager::new(1, 1, 1),
+            texts:      text::Manager::new(1),
+            labels:     label::Manager::new(1),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut entities = entity::Manager::new(2);
+        let mut components = container();
+
+        let parent = entities.add();
+        let child = entities.add();
+
+        components.transforms.register(parent);
+        components.transforms.register(child);
+        components.transforms.set_position(parent, alg::Vec3::up());
+        components.transforms.set_position(child, alg::Vec3::right());
+        components.transforms.parent(child, parent);
+
+        components.lights.register(child);
+        components.lights.build()
+            .point_with_radius(3.0)
+            .color(graphics::Color::orange())
+            .for_entity(child);
+
+        let bytes = components.serialize_scene();
+
+        let mut loaded = container();
+        let handles = loaded.deserialize_scene(&mut entities, &bytes)
+            .unwrap();
+
+        assert!(handles.len() == 2);
+        let (parent, child) = (handles[0], handles[1]);
+
+        assert!(loaded.transforms.parent_of(child) == Some(parent));
+        assert!(loaded.transforms.children(parent) == vec![child]);
+        assert!(loaded.lights.get_color(child) == graphics::Color::orange());
+
+        let error = loaded.transforms.get_position(child)
+            .dist(alg::Vec3::new(1.0, 1.0, 0.0));
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Corrupt data
+        assert!(loaded.deserialize_scene(&mut entities, &bytes[..9]).is_err());
+    }
+
+    #[test]
+    fn corrupt_parent() {
+        let mut entities = entity::Manager::new(2);
+        let mut components = container();
+
+        let parent = entities.add();
+        let child = entities.add();
+
+        components.transforms.register(parent);
+        components.transforms.register(child);
+        components.transforms.parent(child, parent);
+
+        let mut bytes = components.serialize_scene();
+
+        // Header, entity table, transform count, then two records
+        let record = 4 + 12 + 16 + 12 + 4 + 1;
+        let offset = 4 + 4 + 4 + 2 * 4 + 4 + record + record;
+        let index = parent.get_index().to_le_bytes();
+        assert!(bytes[offset..offset + 4] == index);
+
+        // Parent the child to itself
+        let index = child.get_index().to_le_bytes();
+        bytes[offset..offset + 4].copy_from_slice(&index);
+
+        let mut loaded = container();
+        let result = loaded.deserialize_scene(&mut entities, &bytes);
+        assert!(result.is_err());
+
+        // Nothing created before the error is kept
+        assert!(entities.count() == 2);
+        assert!(loaded.transforms.count() == 0);
+    }
+}
@@ -11,6 +11,9 @@ use debug;
 
 use ::FIXED_DT; // Import from lib
 use components::transform;
+use components::scene;
+
+use components::Component;
 
 /// Default instance mass
 pub const INST_DEFAULT_MASS: f32 = 1.0;
@@ -24,9 +27,21 @@ pub const MNGR_DEFAULT_BOUNCE: f32 = 2.0;
 /// Default system (softbody manager) friction
 pub const MNGR_DEFAULT_FRICTION: f32 = 0.02;
 
+/// Default system (softbody manager) gravity, along world down
+pub const MNGR_DEFAULT_GRAVITY: f32 = 9.8;
+
+/// Default particle radius for collision between instances
+pub const MNGR_DEFAULT_PARTICLE_RADIUS: f32 = 0.1;
+
+/// Default rod strain, relative to rest length, drawn fully red
+pub const MNGR_DEFAULT_STRAIN_WINDOW: f32 = 0.1;
+
 // Constraint solver iterations
 const ITERATIONS: usize = 10;
 
+// Upper bound for rod over-relaxation; higher values diverge
+const MAX_OVER_RELAXATION: f32 = 1.9;
+
 // Range 0 - 1; 1.0 = cannot be deformed
 // A value of zero nullifies all rods in the instance
 const ROD_DEFORM: f32 = 1.000;
@@ -130,6 +145,103 @@ impl Rod {
             length,
         }
     }
+
+    /// Move endpoints toward the rest length by `strength`,
+    /// weighting the correction by inverse mass
+    fn solve(
+        &self,
+        particles: &mut [Particle],
+        inv_masses: &[f32],
+        strength: f32,
+    ) {
+        let left = particles[self.left].position;
+        let right = particles[self.right].position;
+
+        // Uniform masses split the correction evenly
+        let inv_left = inv_masses[self.left];
+        let inv_right = inv_masses[self.right];
+        let inv_sum = inv_left + inv_right;
+
+        if inv_sum == 0.0 {
+            return;
+        }
+
+        let difference = right - left;
+        let distance = difference.mag();
+
+        // Coincident particles have no correction direction
+        if distance < std::f32::EPSILON {
+            return;
+        }
+
+        let offset = difference * strength
+            * (self.length / distance - 1.) * 2.0 / inv_sum;
+
+        particles[self.left].position = left - offset * inv_left;
+        particles[self.right].position = right + offset * inv_right;
+    }
+}
+
+/// Snapshot of an instance rod, for inspection
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RodInfo {
+    pub left: usize, // Particle indices
+    pub right: usize,
+    pub rest_length: f32,
+    pub current_length: f32,
+}
+
+// Kinematic pin following another entity's transform
+#[derive(Clone, Copy)]
+struct Attachment {
+    particle: usize,
+    target: entity::Handle,
+    offset: alg::Vec3, // Local to the target transform
+}
+
+// Collision plane bounded by a rectangle
+#[derive(Clone, Copy)]
+struct Quad {
+    plane: alg::Plane,
+    center: alg::Vec3,
+    right: alg::Vec3, // In-plane axes
+    fwd: alg::Vec3,
+    half_extents: alg::Vec2,
+}
+
+impl Quad {
+    fn new(
+        center: alg::Vec3,
+        normal: alg::Vec3,
+        half_extents: alg::Vec2,
+        orientation: alg::Quat,
+    ) -> Quad {
+        let normal = normal.norm();
+
+        // Flatten oriented axes onto the plane
+        let flatten = |axis: alg::Vec3| {
+            (axis - normal * axis.dot(normal)).norm()
+        };
+
+        let right = flatten(orientation * alg::Vec3::right());
+        let fwd = normal.cross(right);
+
+        Quad {
+            plane: alg::Plane::from_point_normal(center, normal),
+            center,
+            right,
+            fwd,
+            half_extents,
+        }
+    }
+
+    // Check if point projects onto the quad
+    fn contains(&self, point: alg::Vec3) -> bool {
+        let offset = point - self.center;
+
+        offset.dot(self.right).abs() <= self.half_extents.x
+            && offset.dot(self.fwd).abs() <= self.half_extents.y
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -440,7 +552,15 @@ pub struct Instance {
     match_shape: bool, // Actively match shape at runtime
 
     force: alg::Vec3,
-    accel_dt: alg::Vec3, // Cached value, dependent on force
+    gravity: Option<alg::Vec3>, // Overrides manager gravity
+    accel_dt: alg::Vec3, // Cached value, dependent on force and gravity
+    pressure: f32, // Internal gas pressure; zero disables
+    drag: f32, // Air resistance per second; zero disables
+    bends: Vec<Rod>, // Distance constraints resisting folding
+    bend_stiffness: f32, // Range 0 - 1, relative to rigidity
+    attachments: Vec<Attachment>, // Particles driven by other transforms
+    active: bool, // Skipped by the simulation when false
+    warned: bool, // Reported divergence
 
     /* Updated per-frame */
 
@@ -451,6 +571,7 @@ pub struct Instance {
 
     mass: f32,
     inv_pt_mass: f32, // Cached inverse mass per particle
+    inv_masses: Vec<f32>, // Per-particle inverse mass; zero = pinned
     end_offset: f32, // Distance from center to simple endpoint
     start_indices: Vec<usize>, // Optional joint start highlight
     end_indices: Vec<usize>, // Optional joint end highlight
@@ -538,13 +659,22 @@ impl Instance {
             match_shape,
 
             force: alg::Vec3::zero(),
+            gravity: None,
             accel_dt: initial_accel * FIXED_DT * FIXED_DT,
+            pressure: 0.0,
+            drag: 0.0,
+            bends: Vec::new(),
+            bend_stiffness: 0.0,
+            attachments: Vec::new(),
+            active: true,
+            warned: false,
 
             frame_position: alg::Vec3::zero(),
             frame_orientation_conjugate: alg::Quat::id(),
 
             mass,
             inv_pt_mass: 1.0 / (mass / points_len as f32),
+            inv_masses: vec![1.0 / (mass / points_len as f32); points_len],
             model: Model {
                 positions: model,
                 com,
@@ -670,19 +800,31 @@ impl Instance {
         end_indices.sort_unstable();
         end_indices.dedup();
 
+        let inv_pt_mass = 1.0 / (mass / vertices_len as f32);
+        let inv_masses = vec![inv_pt_mass; particles.len()];
+
         Instance {
             particles,
             rods: Vec::with_capacity(0),
             match_shape: true,
 
             force: alg::Vec3::zero(),
+            gravity: None,
             accel_dt: initial_accel * FIXED_DT * FIXED_DT,
+            pressure: 0.0,
+            drag: 0.0,
+            bends: Vec::new(),
+            bend_stiffness: 0.0,
+            attachments: Vec::new(),
+            active: true,
+            warned: false,
 
             frame_position: alg::Vec3::zero(),
             frame_orientation_conjugate: alg::Quat::id(),
 
             mass,
-            inv_pt_mass: 1.0 / (mass / vertices_len as f32),
+            inv_pt_mass,
+            inv_masses,
             end_offset,
             start_indices,
             end_indices,
@@ -732,17 +874,350 @@ impl Instance {
     // Must be called when gravity or force changes
     #[inline]
     fn update_cache(&mut self, gravity: alg::Vec3) {
+        let gravity = self.gravity.unwrap_or(gravity);
+
         self.accel_dt = (self.force * self.inv_pt_mass + gravity)
             * FIXED_DT * FIXED_DT;
     }
 
+    /// Write full simulation state of instance
+    fn serialize(&self, writer: &mut scene::Writer) {
+        writer.usize(self.particles.len());
+
+        for particle in &self.particles {
+            writer.vec3(particle.position);
+            writer.vec3(particle.last);
+            writer.vec3(particle.displacement);
+        }
+
+        for rods in &[&self.rods, &self.bends] {
+            writer.usize(rods.len());
+
+            for rod in rods.iter() {
+                writer.usize(rod.left);
+                writer.usize(rod.right);
+                writer.f32(rod.length);
+            }
+        }
+
+        writer.bool(self.match_shape);
+        writer.vec3(self.force);
+
+        match self.gravity {
+            Some(gravity) => {
+                writer.bool(true);
+                writer.vec3(gravity);
+            },
+            None => writer.bool(false),
+        }
+
+        writer.f32(self.pressure);
+        writer.f32(self.drag);
+        writer.f32(self.bend_stiffness);
+        writer.bool(self.active);
+        writer.f32(self.mass);
+        writer.f32(self.inv_pt_mass);
+
+        for inv_mass in &self.inv_masses {
+            writer.f32(*inv_mass);
+        }
+
+        writer.f32(self.end_offset);
+        writer.usizes(&self.start_indices);
+        writer.usizes(&self.end_indices);
+        writer.f32(self.rigidity);
+
+        writer.vec3s(&self.model.positions);
+
+        match self.model.positions_override {
+            Some(ref positions) => {
+                writer.bool(true);
+                writer.vec3s(positions);
+            },
+            None => writer.bool(false),
+        }
+
+        writer.vec3(self.model.com);
+        writer.usizes(&self.model.indices);
+        writer.vec3s(&self.model.normals);
+        writer.usizes(&self.model.duplicates);
+
+        writer.usize(self.attachments.len());
+
+        for attachment in &self.attachments {
+            writer.usize(attachment.particle);
+            writer.entity(attachment.target);
+            writer.vec3(attachment.offset);
+        }
+    }
+
+    /// Read instance written by `serialize()`
+    fn deserialize(
+        reader: &mut scene::Reader,
+        handles: &scene::Handles,
+        gravity: alg::Vec3,
+    ) -> Result<Instance, String> {
+        let mut particles = Vec::new();
+
+        for _ in 0..reader.usize()? {
+            particles.push(Particle {
+                position: reader.vec3()?,
+                last: reader.vec3()?,
+                displacement: reader.vec3()?,
+            });
+        }
+
+        let particle_count = particles.len();
+
+        let mut read_rods = || -> Result<Vec<Rod>, String> {
+            (0..reader.usize()?).map(|_| Ok(
+                Rod {
+                    left: reader.usize()?,
+                    right: reader.usize()?,
+                    length: reader.f32()?,
+                }
+            )).collect()
+        };
+
+        let rods = read_rods()?;
+        let bends = read_rods()?;
+
+        let mut instance = Instance {
+            particles,
+            rods,
+            match_shape: reader.bool()?,
+
+            force: reader.vec3()?,
+            gravity: if reader.bool()? { Some(reader.vec3()?) } else { None },
+            accel_dt: alg::Vec3::zero(), // Updated below
+            pressure: reader.f32()?,
+            drag: reader.f32()?,
+            bends,
+            bend_stiffness: reader.f32()?,
+            attachments: Vec::new(), // Read below
+            active: reader.bool()?,
+            warned: false,
+
+            frame_position: alg::Vec3::zero(),
+            frame_orientation_conjugate: alg::Quat::id(),
+
+            mass: reader.f32()?,
+            inv_pt_mass: reader.f32()?,
+            inv_masses: (0..particle_count)
+                .map(|_| reader.f32())
+                .collect::<Result<_, _>>()?,
+            end_offset: reader.f32()?,
+            start_indices: reader.usizes()?,
+            end_indices: reader.usizes()?,
+            rigidity: reader.f32()?,
+
+            model: Model {
+                positions: reader.vec3s()?,
+                positions_override: if reader.bool()? {
+                    Some(reader.vec3s()?)
+                } else {
+                    None
+                },
+                com: reader.vec3()?,
+                indices: reader.usizes()?,
+                normals: reader.vec3s()?,
+                duplicates: reader.usizes()?,
+            },
+        };
+
+        for _ in 0..reader.usize()? {
+            instance.attachments.push(Attachment {
+                particle: reader.usize()?,
+                target: handles.read(reader)?,
+                offset: reader.vec3()?,
+            });
+        }
+
+        /* Validate indices */
+
+        let len = instance.particles.len();
+
+        let valid = instance.rods.iter().chain(&instance.bends)
+                .all(|rod| rod.left < len && rod.right < len)
+            && instance.model.indices.iter().all(|i| *i < len)
+            && instance.model.duplicates.iter().all(|i| *i < len)
+            && instance.start_indices.iter().all(|i| *i < len)
+            && instance.end_indices.iter().all(|i| *i < len)
+            && instance.attachments.iter().all(|a| a.particle < len)
+            && instance.model.positions.len() == len;
+
+        if !valid {
+            return Err("Invalid softbody instance in scene data".into());
+        }
+
+        instance.update_cache(gravity);
+        Ok(instance)
+    }
+
     /* General instance methods */
 
+    /// Returns center of mass of instance particles.
     pub fn center(&self) -> alg::Vec3 {
+        Instance::centroid(
+            self.particles.iter().map(|particle| particle.position),
+            &self.inv_masses,
+        )
+    }
+
+    /// Mass-weighted average of `points`, one per particle. \
+    /// Pinned (infinite mass) particles are weighted by the average mass
+    /// of the free particles, so that pins don't drag the center.
+    fn centroid<I>(points: I, inv_masses: &[f32]) -> alg::Vec3
+    where
+        I: Iterator<Item = alg::Vec3>,
+    {
+        let uniform = inv_masses.iter().all(|&inv| inv == inv_masses[0]);
+
+        let (free_mass, free_count) = inv_masses.iter()
+            .filter(|&&inv| inv != 0.0)
+            .fold((0.0, 0), |(mass, count), inv| (mass + 1.0 / inv, count + 1));
+
+        let pinned_weight = if free_count > 0 {
+            free_mass / free_count as f32
+        } else { 1.0 };
+
+        let (sum, total) = points.zip(inv_masses).fold(
+            (alg::Vec3::zero(), 0.0),
+            |(sum, total), (point, &inv_mass)| {
+                let weight = if uniform {
+                    1.0
+                } else if inv_mass == 0.0 {
+                    pinned_weight
+                } else {
+                    1.0 / inv_mass
+                };
+
+                (sum + point * weight, total + weight)
+            },
+        );
+
+        sum / total
+    }
+
+    /// Returns volume enclosed by the instance surface triangles. \
+    /// Only meaningful for closed meshes.
+    pub fn volume(&self) -> f32 {
+        self.signed_volume().abs()
+    }
+
+    // Positive when triangle normals point outward
+    fn signed_volume(&self) -> f32 {
+        self.model.indices.chunks(3).fold(0.0, |sum, triangle| {
+            let a = self.particles[triangle[0]].position;
+            let b = self.particles[triangle[1]].position;
+            let c = self.particles[triangle[2]].position;
+
+            sum + a.dot(b.cross(c))
+        }) / 6.0
+    }
+
+    /// Per-particle acceleration from internal pressure,
+    /// proportional to face area and inverse enclosed volume
+    fn pressure_accel(&self) -> Vec<alg::Vec3> {
+        let mut accel = vec![alg::Vec3::zero(); self.particles.len()];
+        let volume = self.signed_volume();
+
+        // Open or collapsed surface
+        if volume.abs() < std::f32::EPSILON {
+            return accel;
+        }
+
+        // Divide by volume and orient outward;
+        // per-face force is shared among three vertices
+        let scale = self.pressure / volume / 6.0;
+
+        for triangle in self.model.indices.chunks(3) {
+            let a = self.particles[triangle[0]].position;
+            let b = self.particles[triangle[1]].position;
+            let c = self.particles[triangle[2]].position;
+
+            // Magnitude is twice the face area
+            let force = (b - a).cross(c - a) * scale;
+
+            for &i in triangle {
+                accel[i] = accel[i] + force * self.inv_masses[i];
+            }
+        }
+
+        accel
+    }
+
+    /// Returns axis-aligned bounding box of instance particles
+    /// as a (min, max) pair.
+    pub fn bounds(&self) -> (alg::Vec3, alg::Vec3) {
         self.particles.iter().fold(
-            alg::Vec3::zero(),
-            |sum, particle| sum + particle.position
-        ) / self.particles.len() as f32
+            (
+                alg::Vec3::one() * std::f32::MAX,
+                alg::Vec3::one() * std::f32::MIN,
+            ),
+            |(min, max), particle| {
+                let p = particle.position;
+                (
+                    alg::Vec3::new(
+                        f32::min(min.x, p.x),
+                        f32::min(min.y, p.y),
+                        f32::min(min.z, p.z),
+                    ),
+                    alg::Vec3::new(
+                        f32::max(max.x, p.x),
+                        f32::max(max.y, p.y),
+                        f32::max(max.z, p.z),
+                    ),
+                )
+            },
+        )
+    }
+
+    /// Cast ray from `ray.start` through `ray.end` against the instance
+    /// surface triangles. \
+    /// Returns the nearest hit point and its distance from `ray.start`.
+    pub fn raycast(&self, ray: alg::Line) -> Option<(alg::Vec3, f32)> {
+        // Broad phase: slab test against the bounding box
+        let (min, max) = self.bounds();
+        let direction = ray.end - ray.start;
+        let (mut near, mut far) = (0f32, std::f32::MAX);
+
+        for &(start, direction, min, max) in &[
+            (ray.start.x, direction.x, min.x, max.x),
+            (ray.start.y, direction.y, min.y, max.y),
+            (ray.start.z, direction.z, min.z, max.z),
+        ] {
+            // Parallel to the slab
+            if direction.abs() < std::f32::EPSILON {
+                if start < min || start > max {
+                    return None;
+                }
+
+                continue;
+            }
+
+            let (a, b) = ((min - start) / direction, (max - start) / direction);
+            near = near.max(a.min(b));
+            far = far.min(a.max(b));
+
+            if near > far {
+                return None;
+            }
+        }
+
+        self.model.indices.chunks(3)
+            .filter_map(|triangle| ray.intersect_triangle(
+                self.particles[triangle[0]].position,
+                self.particles[triangle[1]].position,
+                self.particles[triangle[2]].position,
+            ))
+            .map(|(point, _)| (point, point.dist(ray.start)))
+            .fold(None, |nearest: Option<(alg::Vec3, f32)>, hit| {
+                match nearest {
+                    Some(nearest) if nearest.1 <= hit.1 => Some(nearest),
+                    _ => Some(hit),
+                }
+            })
     }
 
     /// Returns velocity of instance in meters per second.
@@ -753,6 +1228,63 @@ impl Instance {
         ) / self.particles.len() as f32
     }
 
+    /// Returns true if all particle positions are finite
+    pub fn is_finite(&self) -> bool {
+        self.particles.iter().all(|particle| particle.position.is_finite())
+    }
+
+    /// Move particles into the rest pose at the position and orientation
+    /// of the last completed step, nullifying velocity.
+    pub fn reset_pose(&mut self) {
+        let orientation = self.frame_orientation_conjugate.conjugate();
+
+        for (particle, model_position) in self.particles.iter_mut()
+            .zip(&self.model.positions)
+        {
+            particle.position = orientation
+                * (*model_position - self.model.com)
+                + self.frame_position;
+
+            particle.last = particle.position;
+            particle.displacement = alg::Vec3::zero();
+        }
+    }
+
+    /// Reset to rest pose if any particle position is non-finite,
+    /// instead of propagating NaNs. Warns on the first reset only.
+    fn recover(&mut self, index: usize) {
+        if self.is_finite() {
+            return;
+        }
+
+        if !self.warned {
+            eprintln!(
+                "Warning: softbody instance {} diverged; \
+                resetting to rest pose",
+                index,
+            );
+
+            self.warned = true;
+        }
+
+        self.reset_pose();
+    }
+
+    /// Returns kinetic energy of instance in joules,
+    /// given the duration of the last step in seconds. \
+    /// Pinned particles do not contribute.
+    pub fn kinetic_energy(&self, dt: f32) -> f32 {
+        let inv_dt_squared = 1.0 / (dt * dt);
+
+        0.5 * self.particles.iter()
+            .zip(&self.inv_masses)
+            .filter(|&(_, inv_mass)| *inv_mass > 0.0)
+            .map(|(particle, inv_mass)| {
+                (particle.position - particle.last).mag_squared()
+                    * inv_dt_squared / inv_mass
+            }).sum::<f32>()
+    }
+
     /// Returns axis and angular velocity of instance in radians per second. \
     /// `center` and `velocity` are parameters for optional caching.
     pub fn ang_velocity(
@@ -775,6 +1307,15 @@ impl Instance {
         alg::Quat::axis_angle(omega.norm(), omega.mag())
     }
 
+    // Offset of particle from the model reference, in model space,
+    // as of the last simulated frame
+    fn position_offset(&self, index: usize) -> alg::Vec3 {
+        self.frame_orientation_conjugate * (
+            self.particles[index].position - self.frame_position
+        ) - self.model.positions_override.as_ref()
+            .unwrap_or(&self.model.positions)[index]
+    }
+
     /// Returns instance orientation using least squares fit. \
     /// `center` is a parameter for optional caching.
     pub fn matched_orientation(&self, center: alg::Vec3) -> alg::Mat3 {
@@ -798,16 +1339,19 @@ impl Instance {
 
         // Compute rotation component using polar decomposition
         let s = (transform.transpose() * transform).sqrt();
-        transform * s.inverse()
+        let rotation = transform * s.inverse();
+
+        // Degenerate (e.g. collapsed) shapes have no defined rotation
+        if rotation.is_finite() { rotation } else { alg::Mat3::id() }
     }
 
     // Call with point == center for a general rotate method
     #[inline]
     pub fn rotate_around(&mut self, rotation: alg::Quat, point: alg::Vec3) {
         // Rotate
-        for i in 0..8 {
-            self.particles[i].position = rotation
-                * (self.particles[i].position - point) // Center rotation axis
+        for particle in self.particles.iter_mut() {
+            particle.position = rotation
+                * (particle.position - point) // Center rotation axis
                 + point; // Move back to world space
         }
     }
@@ -871,6 +1415,7 @@ pub struct InstanceBuilder<'a> {
     particles: Option<&'a [alg::Vec3]>,
     indices: Option<&'a [usize]>,
     bindings: Option<&'a [(usize, usize)]>,
+    bends: Option<(&'a [(usize, usize)], f32)>, // With stiffness
     initial_pos: alg::Vec3,
     match_shape: bool,
     end_offset: f32,
@@ -890,6 +1435,7 @@ impl<'a> InstanceBuilder<'a> {
             particles: None,
             indices: None,
             bindings: None,
+            bends: None,
             initial_pos: alg::Vec3::zero(),
             match_shape: false,
             end_offset: 0.0, // Default to no simple endpoint
@@ -953,6 +1499,19 @@ impl<'a> InstanceBuilder<'a> {
         self
     }
 
+    /// Particle pairs (e.g. two apart across a fold) that resist bending,
+    /// solved alongside rods with `stiffness` in the range [0, 1]
+    /// relative to rigidity
+    pub fn bends(
+        &mut self,
+        bends: &'a [(usize, usize)],
+        stiffness: f32,
+    ) -> &mut InstanceBuilder<'a> {
+        debug_assert!(stiffness >= 0.0 && stiffness <= 1.0);
+        self.bends = Some((bends, stiffness));
+        self
+    }
+
     pub fn initial_pos(
         &mut self,
         position: alg::Vec3,
@@ -1012,7 +1571,7 @@ impl<'a> InstanceBuilder<'a> {
 
         /* Box limb instance */
 
-        let instance = if let Some(scale) = self.scale {
+        let mut instance = if let Some(scale) = self.scale {
             let scale = scale * 0.5;
 
             debug_assert!(self.model.is_none());
@@ -1122,36 +1681,74 @@ impl<'a> InstanceBuilder<'a> {
             )
         };
 
+        if let Some((bends, stiffness)) = self.bends {
+            let particles = &instance.particles;
+
+            instance.bends = bends.iter()
+                .map(|&(left, right)| Rod::new(left, right, particles))
+                .collect();
+
+            instance.bend_stiffness = stiffness;
+        }
+
         // Register with manager
         self.manager.add_instance(instance, entity);
     }
 }
 
+/// Side of a cloth grid, as seen from above (top toward +Z)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ClothEdge { Top, Bottom, Left, Right }
+
+/// Particle layout of a cloth instance created by `init_cloth()`
+#[derive(Clone, PartialEq, Debug)]
+pub struct Cloth {
+    pub resolution: usize, // Particles per side
+    pub indices: Vec<usize>, // Surface triangles (CW from above)
+    pub rods: Vec<(usize, usize)>, // Structural and shear rods
+    pub bends: Vec<(usize, usize)>, // Particles two apart, if bending
+    pub pinned: Vec<usize>,
+}
+
+impl Cloth {
+    /// Returns the particle index at `column` (from the left)
+    /// and `row` (from the top)
+    pub fn particle(&self, column: usize, row: usize) -> usize {
+        debug_assert!(column < self.resolution && row < self.resolution);
+        row * self.resolution + column
+    }
+}
+
 // Data layout assumes many physics objects (but may still be sparse)
 pub struct Manager {
     handles: Vec<Option<entity::Handle>>,
     instances: Vec<Option<Instance>>,
     joints: fnv::FnvHashMap<usize, Vec<Joint>>,
     planes: Vec<alg::Plane>,
+    quads: Vec<Quad>,
     gravity: alg::Vec3,
     bounce: f32,
     friction: f32,
+    collide_instances: bool,
+    particle_radius: f32, // Collision radius between instances
+    stack_order: bool, // Resolve instance collisions bottom-up
+    time_scale: f32,
+    velocity_scale: f32, // Time scale of the implied particle velocities
+    check_finite: bool, // Reset diverged instances every step
+    strain_window: f32, // Debug rod coloring
+    over_relaxation: f32, // Rod correction scale
     count: usize,
 }
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
+        debug_validate_unregistered!(self, entity);
         let i = entity.get_index() as usize;
 
-        // Resize array to fit new entity
-        loop {
-            if i >= self.instances.len() {
-                self.handles.push(None);
-                self.instances.push(None);
-                continue;
-            }
-
-            break;
+        // Resize arrays to fit new entity
+        if i >= self.instances.len() {
+            self.handles.resize_with(i + 1, || None);
+            self.instances.resize_with(i + 1, || None);
         }
 
         self.handles[i] = Some(entity);
@@ -1188,9 +1785,18 @@ impl Manager {
             instances: Vec::with_capacity(instance_hint),
             joints: joint_map,
             planes: Vec::with_capacity(plane_hint),
-            gravity: alg::Vec3::new(0., -9.8, 0.), // Default gravity
+            quads: Vec::new(),
+            gravity: alg::Vec3::up() * -MNGR_DEFAULT_GRAVITY,
             bounce: MNGR_DEFAULT_BOUNCE,
             friction: MNGR_DEFAULT_FRICTION,
+            collide_instances: false,
+            particle_radius: MNGR_DEFAULT_PARTICLE_RADIUS,
+            stack_order: false,
+            time_scale: 1.0,
+            velocity_scale: 1.0,
+            check_finite: true,
+            strain_window: MNGR_DEFAULT_STRAIN_WINDOW,
+            over_relaxation: 1.0,
             count: 0,
         }
     }
@@ -1201,6 +1807,175 @@ impl Manager {
         InstanceBuilder::new(self)
     }
 
+    /// Initialize the softbody instance for this entity from a mesh,
+    /// with particles at unique vertex positions
+    /// and rods along each (deduplicated) triangle edge
+    pub fn init_instance_from_model(
+        &mut self,
+        entity: entity::Handle,
+        mass: f32,
+        model: &render::ModelData,
+    ) {
+        let mut instance = Instance::new_from_model(
+            model,
+            mass,
+            INST_DEFAULT_RIGID * 0.5, // Scale rigidity properly
+            alg::Vec3::zero(),
+            self.gravity, // Initialize with gravity
+            0.0, // No simple endpoint
+            &[],
+            &[],
+        );
+
+        let mut edges: Vec<(usize, usize)> = instance.model.indices
+            .chunks(3)
+            .flat_map(|triangle| {
+                let (i, j, k) = (triangle[0], triangle[1], triangle[2]);
+                vec![(i, j), (j, k), (k, i)]
+            })
+            .filter(|&(left, right)| left != right)
+            .map(|(left, right)| (left.min(right), left.max(right)))
+            .collect();
+
+        edges.sort_unstable();
+        edges.dedup();
+
+        instance.rods = edges.iter()
+            .map(|&(left, right)| Rod::new(left, right, &instance.particles))
+            .collect();
+
+        self.add_instance(instance, entity);
+    }
+
+    /// Initialize the softbody instance for this entity as a
+    /// horizontal `width` by `height` sheet in the local XZ plane,
+    /// with `resolution` particles per side. \
+    /// Neighboring particles are joined by structural rods,
+    /// and each grid cell is braced by two shear rods. \
+    /// Particles along `pinned_edge` are held in place. \
+    /// A nonzero `bend_stiffness` (up to 1) adds bending constraints
+    /// between particles two apart along each row and column.
+    pub fn init_cloth(
+        &mut self,
+        entity: entity::Handle,
+        mass: f32,
+        width: f32,
+        height: f32,
+        resolution: usize,
+        pinned_edge: Option<ClothEdge>,
+        bend_stiffness: f32,
+    ) -> Cloth {
+        debug_assert!(resolution >= 2);
+        debug_assert!(bend_stiffness >= 0.0 && bend_stiffness <= 1.0);
+
+        let last = (resolution - 1) as f32;
+        let mut cloth = Cloth {
+            resolution,
+            indices: Vec::with_capacity((resolution - 1).pow(2) * 6),
+            rods: Vec::with_capacity((resolution - 1) * resolution * 4),
+            bends: Vec::new(),
+            pinned: Vec::with_capacity(resolution),
+        };
+
+        let mut points = Vec::with_capacity(resolution * resolution);
+
+        for row in 0..resolution {
+            for column in 0..resolution {
+                points.push(
+                    alg::Vec3::new(
+                        width * (column as f32 / last - 0.5),
+                        0.0,
+                        height * (0.5 - row as f32 / last),
+                    )
+                );
+            }
+        }
+
+        for row in 0..resolution {
+            for column in 0..resolution {
+                let i = cloth.particle(column, row);
+
+                // Structural
+                if column + 1 < resolution {
+                    cloth.rods.push((i, i + 1));
+                }
+
+                if row + 1 < resolution {
+                    cloth.rods.push((i, i + resolution));
+                }
+
+                // Bending
+                if bend_stiffness > 0.0 {
+                    if column + 2 < resolution {
+                        cloth.bends.push((i, i + 2));
+                    }
+
+                    if row + 2 < resolution {
+                        cloth.bends.push((i, i + resolution * 2));
+                    }
+                }
+
+                if column + 1 == resolution || row + 1 == resolution {
+                    continue;
+                }
+
+                let (right, below) = (i + 1, i + resolution);
+                let diagonal = below + 1;
+
+                // Shear
+                cloth.rods.push((i, diagonal));
+                cloth.rods.push((right, below));
+
+                cloth.indices.extend_from_slice(
+                    &[i, right, diagonal, diagonal, below, i]
+                );
+            }
+        }
+
+        if let Some(edge) = pinned_edge {
+            cloth.pinned = (0..resolution).map(|j| match edge {
+                ClothEdge::Top => cloth.particle(j, 0),
+                ClothEdge::Bottom => cloth.particle(j, resolution - 1),
+                ClothEdge::Left => cloth.particle(0, j),
+                ClothEdge::Right => cloth.particle(resolution - 1, j),
+            }).collect();
+        }
+
+        let mut instance = Instance::new(
+            &points,
+            &cloth.indices,
+            None, // No model override
+            &cloth.rods,
+            false, // Cloth does not hold its shape
+            mass,
+            INST_DEFAULT_RIGID * 0.5, // Scale rigidity properly
+            alg::Vec3::zero(),
+            self.gravity, // Initialize with gravity
+            0.0, // No simple endpoint
+            &[],
+            &[],
+        );
+
+        for &i in &cloth.pinned {
+            instance.inv_masses[i] = 0.0;
+        }
+
+        instance.bends = cloth.bends.iter()
+            .map(|&(left, right)| Rod::new(left, right, &instance.particles))
+            .collect();
+
+        instance.bend_stiffness = bend_stiffness;
+
+        // Keep rest center consistent with the weighted center
+        instance.model.com = Instance::centroid(
+            instance.model.positions.iter().cloned(),
+            &instance.inv_masses,
+        );
+
+        self.add_instance(instance, entity);
+        cloth
+    }
+
     fn add_instance(&mut self, instance: Instance, entity: entity::Handle) {
         debug_validate_entity!(self, entity);
         let i = entity.get_index() as usize;
@@ -1310,20 +2085,283 @@ impl Manager {
         sum.0 / sum.1
     }
 
+    /// Cast ray from `ray.start` through `ray.end` against every instance
+    /// surface (see `Instance::raycast()`). \
+    /// Returns the nearest hit entity, point, and distance.
+    pub fn raycast(
+        &self,
+        ray: alg::Line,
+    ) -> Option<(entity::Handle, alg::Vec3, f32)> {
+        self.handles.iter().zip(&self.instances)
+            .filter_map(|(handle, instance)| match (handle, instance) {
+                (Some(handle), Some(instance)) => instance.raycast(ray)
+                    .map(|(point, dist)| (*handle, point, dist)),
+                _ => None,
+            })
+            .fold(None, |nearest, hit| {
+                match nearest {
+                    Some((_, _, dist)) if dist <= hit.2 => nearest,
+                    _ => Some(hit),
+                }
+            })
+    }
+
     pub fn set_force(&mut self, entity: entity::Handle, force: alg::Vec3) {
         let instance = get_mut_instance!(self, entity);
         instance.force = force;
         instance.update_cache(self.gravity);
     }
 
-    pub fn get_particle(
-        &self,
+    /// Scale how strongly instance bending constraints resist folding,
+    /// in the range [0, 1] relative to rigidity. Zero disables.
+    pub fn set_bend_stiffness(
+        &mut self,
         entity: entity::Handle,
-        index: usize,
-    ) -> alg::Vec3 {
-        let instance = get_instance!(self, entity);
-        debug_assert!(index < instance.particles.len());
-        instance.particles[index].position
+        stiffness: f32,
+    ) {
+        debug_assert!(stiffness >= 0.0 && stiffness <= 1.0);
+        get_mut_instance!(self, entity).bend_stiffness = stiffness;
+    }
+
+    /// Inflate closed mesh instance with internal gas pressure,
+    /// pushing surface faces outward. Zero disables.
+    pub fn set_pressure(&mut self, entity: entity::Handle, pressure: f32) {
+        debug_assert!(pressure >= 0.0);
+        get_mut_instance!(self, entity).pressure = pressure;
+    }
+
+    /// Slow instance particles in proportion to their velocity,
+    /// giving falling bodies a terminal velocity of gravity / drag. \
+    /// Separate from rod rigidity; zero disables.
+    pub fn set_drag(&mut self, entity: entity::Handle, coefficient: f32) {
+        debug_assert!(coefficient >= 0.0);
+        get_mut_instance!(self, entity).drag = coefficient;
+    }
+
+    /// Freeze (or resume) simulation of an instance,
+    /// leaving its state intact. \
+    /// Joints involving an inactive instance are not solved.
+    pub fn set_active(&mut self, entity: entity::Handle, active: bool) {
+        get_mut_instance!(self, entity).active = active;
+    }
+
+    pub fn is_active(&self, entity: entity::Handle) -> bool {
+        get_instance!(self, entity).active
+    }
+
+    /// Returns kinetic energy of instance in joules. \
+    /// Spikes usually indicate an unstable simulation.
+    pub fn kinetic_energy(&self, entity: entity::Handle) -> f32 {
+        if self.time_scale == 0.0 {
+            return 0.0; // Frozen
+        }
+
+        let instance = get_instance!(self, entity);
+        instance.kinetic_energy(FIXED_DT * self.time_scale)
+    }
+
+    /// Returns summed kinetic energy of all instances in joules
+    pub fn total_kinetic_energy(&self) -> f32 {
+        if self.time_scale == 0.0 {
+            return 0.0; // Frozen
+        }
+
+        self.instances.iter()
+            .filter_map(|instance| instance.as_ref())
+            .map(|instance| instance.kinetic_energy(FIXED_DT * self.time_scale))
+            .sum()
+    }
+
+    /// Override manager gravity for a single instance
+    pub fn set_instance_gravity(
+        &mut self,
+        entity: entity::Handle,
+        gravity: alg::Vec3,
+    ) {
+        let instance = get_mut_instance!(self, entity);
+        instance.gravity = Some(gravity);
+        instance.update_cache(self.gravity);
+    }
+
+    /// Revert instance to manager gravity
+    pub fn clear_instance_gravity(&mut self, entity: entity::Handle) {
+        let instance = get_mut_instance!(self, entity);
+        instance.gravity = None;
+        instance.update_cache(self.gravity);
+    }
+
+    /// Returns the endpoints and rest and current lengths
+    /// of every instance rod
+    pub fn rods(&self, entity: entity::Handle) -> Vec<RodInfo> {
+        let instance = get_instance!(self, entity);
+
+        instance.rods.iter().map(|rod| RodInfo {
+            left: rod.left,
+            right: rod.right,
+            rest_length: rod.length,
+            current_length: instance.particles[rod.left].position
+                .dist(instance.particles[rod.right].position),
+        }).collect()
+    }
+
+    pub fn get_particle(
+        &self,
+        entity: entity::Handle,
+        index: usize,
+    ) -> alg::Vec3 {
+        let instance = get_instance!(self, entity);
+        debug_assert!(index < instance.particles.len());
+        instance.particles[index].position
+    }
+
+    /// Set mass of a single instance particle. \
+    /// Heavier particles move less when resolving rods;
+    /// an infinite mass pins the particle in place. \
+    /// Does not change the instance total mass.
+    pub fn set_particle_mass(
+        &mut self,
+        entity: entity::Handle,
+        index: usize,
+        mass: f32,
+    ) {
+        debug_assert!(mass > 0.0);
+
+        let instance = get_mut_instance!(self, entity);
+        debug_assert!(index < instance.particles.len());
+        instance.inv_masses[index] = 1.0 / mass;
+
+        // Keep rest center consistent with the weighted center
+        instance.model.com = Instance::centroid(
+            instance.model.positions.iter().cloned(),
+            &instance.inv_masses,
+        );
+    }
+
+    /// Drive a particle to `local_offset` in the space of the `target`
+    /// transform every step, like a pin that follows the target. \
+    /// The particle becomes kinematic (infinite mass).
+    pub fn attach_to_transform(
+        &mut self,
+        entity: entity::Handle,
+        particle_index: usize,
+        target: entity::Handle,
+        local_offset: alg::Vec3,
+    ) {
+        let instance = get_mut_instance!(self, entity);
+        debug_assert!(particle_index < instance.particles.len());
+
+        instance.attachments.retain(|a| a.particle != particle_index);
+        instance.attachments.push(Attachment {
+            particle: particle_index,
+            target,
+            offset: local_offset,
+        });
+
+        instance.inv_masses[particle_index] = 0.0;
+
+        // Keep rest center consistent with the weighted center
+        instance.model.com = Instance::centroid(
+            instance.model.positions.iter().cloned(),
+            &instance.inv_masses,
+        );
+    }
+
+    /// Returns the rest (model) position of each instance particle
+    pub fn get_rest_pose(&self, entity: entity::Handle) -> &[alg::Vec3] {
+        let instance = get_instance!(self, entity);
+        &instance.model.positions
+    }
+
+    /// Replace the rest (model) position of each instance particle,
+    /// recomputing rod and bend rest lengths, so that the instance relaxes
+    /// toward the new shape
+    pub fn set_rest_pose(
+        &mut self,
+        entity: entity::Handle,
+        positions: &[alg::Vec3],
+    ) {
+        let instance = get_mut_instance!(self, entity);
+
+        #[cfg(debug_assertions)] {
+            if positions.len() != instance.particles.len() {
+                panic!(
+                    "Rest pose with {} positions given for softbody instance \
+                    of entity {} with {} particles",
+                    positions.len(), entity, instance.particles.len(),
+                );
+            }
+        }
+
+        instance.model.positions = positions.to_vec();
+        instance.model.com = Instance::centroid(
+            positions.iter().cloned(),
+            &instance.inv_masses,
+        );
+
+        // Rebuild base comparison normals from the new shape
+        let particles: Vec<Particle> = positions.iter()
+            .map(|position| Particle::new(*position))
+            .collect();
+
+        instance.model.normals = Instance::compute_normals(
+            &particles,
+            &instance.model.indices,
+            instance.model.duplicates.len(),
+        );
+
+        for rod in instance.rods.iter_mut().chain(&mut instance.bends) {
+            rod.length = positions[rod.left].dist(positions[rod.right]);
+        }
+    }
+
+    /// Add rod between two particles of an instance at runtime. \
+    /// Rest length is computed from the current particle positions.
+    pub fn add_rod(
+        &mut self,
+        entity: entity::Handle,
+        left: usize,
+        right: usize,
+    ) {
+        let instance = get_mut_instance!(self, entity);
+
+        #[cfg(debug_assertions)] {
+            let count = instance.particles.len();
+
+            if left >= count || right >= count {
+                panic!(
+                    "Rod ({}, {}) out of range for softbody instance \
+                    of entity {} with {} particles",
+                    left, right, entity, count,
+                );
+            }
+
+            if left == right {
+                panic!("Attempted to bind particle {} to itself", left);
+            }
+        }
+
+        let rod = Rod::new(left, right, &instance.particles);
+        instance.rods.push(rod);
+    }
+
+    /// Remove all rods between two particles of an instance,
+    /// in either order. \
+    /// Returns false if no such rod exists.
+    pub fn remove_rod(
+        &mut self,
+        entity: entity::Handle,
+        left: usize,
+        right: usize,
+    ) -> bool {
+        let instance = get_mut_instance!(self, entity);
+        let count = instance.rods.len();
+
+        instance.rods.retain(|rod| {
+            !(rod.left == left && rod.right == right)
+                && !(rod.left == right && rod.right == left)
+        });
+
+        instance.rods.len() < count
     }
 
     /// Get instance particle offsets from the model.
@@ -1363,11 +2401,7 @@ impl Manager {
             {
                 // Get offset from center; compare current transform against
                 // model reference
-                let offset = instance.frame_orientation_conjugate * (
-                    instance.particles[j].position - instance.frame_position
-                ) - instance.model.positions_override.as_ref()
-                    .unwrap_or(&instance.model.positions)[j];
-
+                let offset = instance.position_offset(j);
                 offsets[i] = render::PaddedVec3::new(offset);
             }
         }
@@ -1375,6 +2409,17 @@ impl Manager {
         offsets
     }
 
+    /// Returns the largest particle offset from the model reference
+    /// sent to the renderer, for detecting deformation beyond
+    /// the range the mesh was authored for
+    pub fn max_offset_magnitude(&self, entity: entity::Handle) -> f32 {
+        let instance = get_instance!(self, entity);
+
+        (0..instance.particles.len())
+            .map(|i| instance.position_offset(i).mag())
+            .fold(0.0, f32::max)
+    }
+
     /// Get instance particle offsets from the normals model.
     pub(super) fn get_normal_offsets(
         &self,
@@ -1518,6 +2563,75 @@ impl Manager {
         planes.iter().for_each(|plane| self.add_plane(*plane));
     }
 
+    pub fn planes(&self) -> &[alg::Plane] {
+        &self.planes
+    }
+
+    /// Remove plane at `index` (in insertion order) and return it. \
+    /// Later planes shift down by one.
+    pub fn remove_plane(&mut self, index: usize) -> Option<alg::Plane> {
+        if index < self.planes.len() {
+            Some(self.planes.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn clear_planes(&mut self) {
+        self.planes.clear();
+    }
+
+    /// Add collision plane bounded to a rectangle around `center`. \
+    /// `half_extents` are along the quad's right and forward axes,
+    /// given by `orientation`.
+    pub fn add_quad(
+        &mut self,
+        center: alg::Vec3,
+        normal: alg::Vec3,
+        half_extents: alg::Vec2,
+        orientation: alg::Quat,
+    ) {
+        debug_assert!(half_extents.x >= 0.0 && half_extents.y >= 0.0);
+
+        #[cfg(debug_assertions)] {
+            let right = orientation * alg::Vec3::right();
+
+            if right.cross(normal).mag_squared() < 0.0001 {
+                panic!("Quad orientation right axis is parallel to normal");
+            }
+        }
+
+        self.quads.push(Quad::new(center, normal, half_extents, orientation));
+    }
+
+    pub fn quad_count(&self) -> usize {
+        self.quads.len()
+    }
+
+    /// Remove quad at `index` (in insertion order). \
+    /// Returns the removed quad's center, normal, and half extents.
+    pub fn remove_quad(
+        &mut self,
+        index: usize,
+    ) -> Option<(alg::Vec3, alg::Vec3, alg::Vec2)> {
+        if index < self.quads.len() {
+            let quad = self.quads.remove(index);
+            Some((quad.center, quad.plane.normal, quad.half_extents))
+        } else {
+            None
+        }
+    }
+
+    pub fn clear_quads(&mut self) {
+        self.quads.clear();
+    }
+
+    /// Remove all planes and quads
+    pub fn clear_colliders(&mut self) {
+        self.clear_planes();
+        self.clear_quads();
+    }
+
     /// Set gravity for all instances. \
     /// Heavier call than `set_gravity_raw(...)`, \
     /// but will force-update all instances.
@@ -1531,6 +2645,11 @@ impl Manager {
         }
     }
 
+    /// Returns gravity shared by instances without an override
+    pub fn gravity(&self) -> alg::Vec3 {
+        self.gravity
+    }
+
     /// Set gravity for all instances. \
     /// May not immediately affect all instances.
     pub fn set_gravity_raw(&mut self, gravity: alg::Vec3) {
@@ -1549,11 +2668,213 @@ impl Manager {
         self.friction = friction;
     }
 
+    /// Enable or disable the per-step check that resets instances
+    /// with non-finite particle positions to their rest pose. \
+    /// Enabled by default.
+    pub fn set_finite_check(&mut self, enabled: bool) {
+        self.check_finite = enabled;
+    }
+
+    /// Set rod strain (relative to rest length) at which
+    /// debug rods are drawn fully red. \
+    /// Lower values highlight subtler stress.
+    pub fn set_strain_window(&mut self, window: f32) {
+        debug_assert!(window > 0.0);
+        self.strain_window = window;
+    }
+
+    /// Scale rod corrections for faster convergence
+    /// at the same iteration count. \
+    /// Range 1 - 1.9; 1.0 = no over-relaxation (default).
+    /// Values are clamped to the upper bound.
+    pub fn set_over_relaxation(&mut self, factor: f32) {
+        debug_assert!(factor > 0.0);
+        self.over_relaxation = factor.min(MAX_OVER_RELAXATION);
+    }
+
+    /// Enable or disable collision between instances. \
+    /// Particles of different instances closer than twice the particle
+    /// radius are pushed apart. \
+    /// Disabled by default; cost scales with the square of the instance
+    /// count.
+    pub fn set_instance_collision(&mut self, enabled: bool) {
+        self.collide_instances = enabled;
+    }
+
+    /// Set particle radius used for collision between instances. \
+    /// Sparse meshes need a larger radius to avoid passing through
+    /// each other.
+    pub fn set_particle_radius(&mut self, radius: f32) {
+        debug_assert!(radius > 0.0);
+        self.particle_radius = radius;
+    }
+
+    /// Resolve instance collisions in order of lowest point along gravity
+    /// (bottom-up): each instance is separated from the corrected positions
+    /// of those below it, which act as fixed support. \
+    /// Steadies stacks at the cost of a sort per iteration; lower instances
+    /// are no longer pushed by those resting on them.
+    /// Disabled by default (index order, separation split evenly).
+    pub fn set_stack_order(&mut self, enabled: bool) {
+        self.stack_order = enabled;
+    }
+
+    /// Write manager settings, planes, and all instances. \
+    /// Joints are not included.
+    pub(super) fn serialize(&self, writer: &mut scene::Writer) {
+        writer.vec3(self.gravity);
+        writer.f32(self.bounce);
+        writer.f32(self.friction);
+        writer.bool(self.collide_instances);
+        writer.f32(self.particle_radius);
+        writer.bool(self.stack_order);
+        writer.f32(self.time_scale);
+        writer.f32(self.velocity_scale);
+        writer.f32(self.over_relaxation);
+        writer.usize(self.planes.len());
+
+        for plane in &self.planes {
+            writer.vec3(plane.normal);
+            writer.f32(plane.offset);
+        }
+
+        writer.usize(self.quads.len());
+
+        for quad in &self.quads {
+            writer.vec3(quad.center);
+            writer.vec3(quad.plane.normal);
+            writer.vec3(quad.right);
+            writer.f32(quad.half_extents.x);
+            writer.f32(quad.half_extents.y);
+        }
+
+        writer.usize(self.count);
+
+        for (handle, instance) in self.handles.iter().zip(&self.instances) {
+            let entity = match *handle {
+                Some(entity) => entity,
+                None => continue,
+            };
+
+            writer.entity(entity);
+
+            match *instance {
+                Some(ref instance) => {
+                    writer.bool(true);
+                    instance.serialize(writer);
+                },
+                None => writer.bool(false),
+            }
+        }
+    }
+
+    /// Register and initialize instances written by `serialize()`. \
+    /// The loaded settings and planes replace the current ones.
+    pub(super) fn deserialize(
+        &mut self,
+        reader: &mut scene::Reader,
+        handles: &scene::Handles,
+    ) -> Result<(), String> {
+        self.gravity = reader.vec3()?;
+        self.bounce = reader.f32()?;
+        self.friction = reader.f32()?;
+        self.collide_instances = reader.bool()?;
+        self.particle_radius = reader.f32()?;
+        self.stack_order = reader.bool()?;
+        self.time_scale = reader.f32()?;
+        self.velocity_scale = reader.f32()?;
+        self.over_relaxation = reader.f32()?;
+
+        self.planes.clear();
+
+        for _ in 0..reader.usize()? {
+            let plane = alg::Plane::new_raw(reader.vec3()?, reader.f32()?);
+            self.planes.push(plane);
+        }
+
+        self.quads.clear();
+
+        for _ in 0..reader.usize()? {
+            let center = reader.vec3()?;
+            let normal = reader.vec3()?;
+            let right = reader.vec3()?;
+
+            let half_extents = alg::Vec2::new(reader.f32()?, reader.f32()?);
+
+            self.quads.push(Quad {
+                plane: alg::Plane::from_point_normal(center, normal),
+                center,
+                right,
+                fwd: normal.cross(right),
+                half_extents,
+            });
+        }
+
+        for _ in 0..reader.usize()? {
+            let entity = handles.read(reader)?;
+            self.register(entity);
+
+            if reader.bool()? {
+                let instance = Instance::deserialize(
+                    reader,
+                    handles,
+                    self.gravity,
+                )?;
+                self.instances[entity.get_index() as usize] = Some(instance);
+            }
+        }
+
+        // Refresh existing instances with the loaded gravity
+        self.set_gravity(self.gravity);
+
+        Ok(())
+    }
+
+    /// Scale simulation time without changing the tick rate. \
+    /// A value of zero pauses the simulation; 0.5 is half speed.
+    pub fn set_time_scale(&mut self, scale: f32) {
+        debug_assert!(scale >= 0.0);
+        self.time_scale = scale;
+
+        // Paused instances keep their velocities until resumed
+        if scale == 0.0 {
+            return;
+        }
+
+        /* Rescale the velocities implied by Verlet integration,
+         * so that changing the time scale does not add energy
+         */
+
+        let ratio = scale / self.velocity_scale;
+        self.velocity_scale = scale;
+
+        for instance in self.instances.iter_mut()
+            .filter_map(|instance| instance.as_mut())
+        {
+            for particle in &mut instance.particles {
+                particle.last = particle.position
+                    - (particle.position - particle.last) * ratio;
+            }
+        }
+    }
+
+    pub fn get_time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
     pub(crate) fn simulate<T>(
         &mut self,
         game: &mut T,
         transforms: &mut transform::Manager
     ) where T: Iterate {
+        // Frozen in time
+        if self.time_scale == 0.0 {
+            return;
+        }
+
+        // Scale cached (unscaled) acceleration by the squared time scale
+        let accel_scale = self.time_scale * self.time_scale;
+
         // Update instance particles
         for i in 0..self.instances.len() {
             let mut instance = match self.instances[i] {
@@ -1561,19 +2882,67 @@ impl Manager {
                 None => continue,
             };
 
+            if !instance.active {
+                continue;
+            }
+
+            // Move attached particles with their targets
+            for attachment in &instance.attachments {
+                let target = transforms.get_mat(attachment.target)
+                    * attachment.offset;
+
+                instance.particles[attachment.particle].position = target;
+            }
+
+            let pressure_dt = if instance.pressure > 0.0 {
+                Some(instance.pressure_accel())
+            } else { None };
+
+            // Fraction of the implied velocity removed this step
+            let drag = (instance.drag * FIXED_DT * self.time_scale).min(1.0);
+
             // Position Verlet
-            for particle in &mut instance.particles {
+            for (j, (particle, inv_mass)) in instance.particles.iter_mut()
+                .zip(&instance.inv_masses)
+                .enumerate()
+            {
+                // Pinned particles do not integrate
+                if *inv_mass == 0.0 {
+                    particle.displacement = alg::Vec3::zero();
+                    particle.last = particle.position;
+                    continue;
+                }
+
+                let mut accel_dt = instance.accel_dt;
+
+                if let Some(ref pressure) = pressure_dt {
+                    accel_dt = accel_dt + pressure[j] * FIXED_DT * FIXED_DT;
+                }
+
                 let next_position = particle.position * 2.
                     - particle.last
-                    + instance.accel_dt;
+                    - (particle.position - particle.last) * drag
+                    + accel_dt * accel_scale;
 
                 particle.displacement = (next_position - particle.last) / 2.0;
                 particle.last = particle.position;
                 particle.position = next_position;
             }
 
+            // Catch external corruption before it reaches the solver
+            if self.check_finite {
+                instance.recover(i);
+            }
+
             // Plane friction
-            for plane in &self.planes {
+            let colliders = self.planes.iter()
+                .map(|plane| (plane, None))
+                .chain(
+                    self.quads.iter()
+                        .map(|quad| (&quad.plane, Some(quad)))
+                );
+
+            for (plane, quad) in colliders {
                 for particle in &mut instance.particles {
                     let distance = plane.dist(particle.position);
 
@@ -1581,6 +2950,10 @@ impl Manager {
                         continue;
                     }
 
+                    if let Some(quad) = quad {
+                        if !quad.contains(particle.position) { continue; }
+                    }
+
                     let direction = particle.displacement.norm();
                     let tangent = direction
                         .cross(plane.normal)
@@ -1598,14 +2971,30 @@ impl Manager {
 
         // Solve constraints
         for _ in 0..ITERATIONS {
+            // Instance collision
+            if self.collide_instances {
+                self.solve_instance_collisions();
+            }
+
             for i in 0..self.instances.len() {
                 let mut instance = match self.instances[i] {
                     Some(ref mut instance) => instance,
                     None => continue,
                 };
 
+                if !instance.active {
+                    continue;
+                }
+
                 // Plane collision
-                for plane in &self.planes {
+                let colliders = self.planes.iter()
+                    .map(|plane| (plane, None))
+                    .chain(
+                        self.quads.iter()
+                            .map(|quad| (&quad.plane, Some(quad)))
+                    );
+
+                for (plane, quad) in colliders {
                     for particle in &mut instance.particles {
                         let distance = plane.dist(particle.position);
 
@@ -1613,6 +3002,10 @@ impl Manager {
                             continue;
                         }
 
+                        if let Some(quad) = quad {
+                            if !quad.contains(particle.position) { continue; }
+                        }
+
                         particle.position = particle.position
                             - plane.normal * self.bounce * distance;
                     }
@@ -1620,17 +3013,23 @@ impl Manager {
 
                 // Rods
                 for rod in &instance.rods {
-                    let left = instance.particles[rod.left].position;
-                    let right = instance.particles[rod.right].position;
-
-                    let difference = right - left;
-                    let distance = difference.mag();
-
-                    let offset = difference * instance.rigidity
-                        * (rod.length / distance - 1.);
+                    rod.solve(
+                        &mut instance.particles,
+                        &instance.inv_masses,
+                        instance.rigidity * self.over_relaxation,
+                    );
+                }
 
-                    instance.particles[rod.left].position = left - offset;
-                    instance.particles[rod.right].position = right + offset;
+                // Bending constraints
+                if instance.bend_stiffness > 0.0 {
+                    for bend in &instance.bends {
+                        bend.solve(
+                            &mut instance.particles,
+                            &instance.inv_masses,
+                            instance.rigidity * instance.bend_stiffness
+                                * self.over_relaxation,
+                        );
+                    }
                 }
 
                 // Shape matching
@@ -1638,9 +3037,16 @@ impl Manager {
                     let center = instance.center();
                     let orientation = instance.matched_orientation(center);
 
-                    for (particle, model_position) in instance.particles
-                        .iter_mut().zip(&instance.model.positions)
+                    for ((particle, model_position), &inv_mass) in instance
+                        .particles.iter_mut()
+                        .zip(&instance.model.positions)
+                        .zip(&instance.inv_masses)
                     {
+                        // Pinned particles stay put
+                        if inv_mass == 0.0 {
+                            continue;
+                        }
+
                         let target = orientation
                             * (*model_position - instance.model.com)
                             + center;
@@ -1668,7 +3074,7 @@ impl Manager {
         // Solve abstracted constraints
         for _ in 0..ITERATIONS {
             // External constraints
-            game.iterate(FIXED_DT, ITERATIONS, self);
+            game.iterate(FIXED_DT * self.time_scale, ITERATIONS, self);
 
             // Joint constraints
             self.solve_joints();
@@ -1681,6 +3087,14 @@ impl Manager {
                 None => continue,
             };
 
+            if !instance.active {
+                continue;
+            }
+
+            if self.check_finite {
+                instance.recover(i);
+            }
+
             // Compute average position and best fit orientation
             let center = instance.center();
             let orientation = instance.matched_orientation(center).to_quat();
@@ -1689,15 +3103,153 @@ impl Manager {
             instance.frame_position = center;
             instance.frame_orientation_conjugate = orientation.conjugate();
 
-            // Update transform
+            // Update transform in a single chain update--particles are
+            // simulated in world space, so the transform stays unscaled
             debug_validate_entity!(transforms, self.handles[i].unwrap());
             transforms.set_raw(i, center, orientation, alg::Vec3::one());
         }
     }
 
     #[inline]
-    fn solve_joints(&mut self) {
-        for (parent_index, joints) in &self.joints {
+    fn solve_instance_collisions(&mut self) {
+        let diameter = 2.0 * self.particle_radius;
+
+        // Broad phase: cache bounds for this iteration
+        let mut bounds: Vec<Option<(alg::Vec3, alg::Vec3)>> = self.instances
+            .iter()
+            .map(|instance| instance.as_ref().map(|inst| inst.bounds()))
+            .collect();
+
+        let mut order: Vec<usize> = (0..self.instances.len())
+            .filter(|i| bounds[*i].is_some())
+            .collect();
+
+        if self.stack_order {
+            let up = if self.gravity.mag_squared() > 0.0 {
+                -self.gravity.norm()
+            } else {
+                alg::Vec3::up()
+            };
+
+            // Lowest corner of the bounds, against gravity
+            let height = |i: usize| {
+                let (min, max) = bounds[i].unwrap();
+
+                (min.x * up.x).min(max.x * up.x)
+                    + (min.y * up.y).min(max.y * up.y)
+                    + (min.z * up.z).min(max.z * up.z)
+            };
+
+            order.sort_by(|a, b| {
+                height(*a).partial_cmp(&height(*b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+
+        // Stacked instances take the full correction
+        let share = if self.stack_order { 1.0 } else { 0.5 };
+
+        // Stacked instances rest on those below
+        let mut resolved = vec![false; self.instances.len()];
+
+        // Particle positions of the instance being collided against
+        let mut others: Vec<alg::Vec3> = Vec::new();
+
+        for i in order {
+            let (min, max) = match bounds[i] {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+
+            resolved[i] = true;
+
+            // Inactive instances still act as obstacles
+            if !self.instances[i].as_ref().unwrap().active {
+                continue;
+            }
+
+            for j in 0..self.instances.len() {
+                if i == j || (self.stack_order && !resolved[j]) {
+                    continue;
+                }
+
+                let (other_min, other_max) = match bounds[j] {
+                    Some(bounds) => bounds,
+                    None => continue,
+                };
+
+                // Bounds are padded by a particle diameter
+                if max.x + diameter < other_min.x
+                    || min.x > other_max.x + diameter
+                    || max.y + diameter < other_min.y
+                    || min.y > other_max.y + diameter
+                    || max.z + diameter < other_min.z
+                    || min.z > other_max.z + diameter
+                {
+                    continue;
+                }
+
+                // Separation direction for coincident particles,
+                // pointing away from the other instance
+                let offset = (min + max) - (other_min + other_max);
+                let fallback = if offset.mag_squared() > 0.0 {
+                    offset.norm()
+                } else {
+                    alg::Vec3::up()
+                };
+
+                others.clear();
+                others.extend(
+                    self.instances[j].as_ref().unwrap().particles.iter()
+                        .map(|particle| particle.position)
+                );
+
+                let instance = self.instances[i].as_mut().unwrap();
+
+                // Narrow phase: push apart particle pairs closer than a
+                // diameter. Each instance is responsible for half of the
+                // separation; when stacking, the lower instance is fixed
+                // and the upper instance moves the whole way.
+                for (particle, inv_mass) in instance.particles.iter_mut()
+                    .zip(&instance.inv_masses)
+                {
+                    // Pinned particles are held in place
+                    if *inv_mass == 0.0 { continue; }
+
+                    for other in &others {
+                        let offset = particle.position - *other;
+                        let distance_squared = offset.mag_squared();
+
+                        if distance_squared >= diameter * diameter {
+                            continue;
+                        }
+
+                        let distance = distance_squared.sqrt();
+                        let normal = if distance > std::f32::EPSILON {
+                            offset / distance
+                        } else {
+                            fallback
+                        };
+
+                        let depth = diameter - distance;
+
+                        particle.position = particle.position
+                            + normal * self.bounce * share * depth;
+                    }
+                }
+            }
+
+            // Instances above see the corrected bounds
+            if self.stack_order {
+                let instance = self.instances[i].as_ref().unwrap();
+                bounds[i] = Some(instance.bounds());
+            }
+        }
+    }
+
+    #[inline]
+    fn solve_joints(&mut self) {
+        for (parent_index, joints) in &self.joints {
             /* Unsafely acquire mutable references to vector elements.
              * Unfortunately, the only safe Rust alternative (split_at_mut())
              * is slower.
@@ -1725,6 +3277,11 @@ impl Manager {
 
             debug_assert!(children.len() == joints.len());
 
+            // Joints are frozen along with any of their instances
+            if !parent.active || children.iter().any(|child| !child.active) {
+                continue;
+            }
+
             /* Constrain orientations to joint connection */
 
             for i in 0..children.len() {
@@ -2027,6 +3584,7 @@ impl Manager {
         alg::Quat::simple(alg::Vec3::fwd(), midpoint)
     }
 
+    /// Draw all instances and joints
     #[allow(unused_variables)]
     pub fn draw_all(&self, debug: &mut debug::Handler) {
         #[cfg(debug_assertions)] {
@@ -2049,11 +3607,14 @@ impl Manager {
         }
     }
 
+    /// Draw rods and endpoints of every live instance
     #[allow(unused_variables)]
     pub fn draw_all_instances(&self, debug: &mut debug::Handler) {
         #[cfg(debug_assertions)] {
             for i in 0..self.instances.len() {
-                self.draw_instance(i, false, true, debug);
+                if self.instances[i].is_some() {
+                    self.draw_instance(i, false, true, debug);
+                }
             }
         }
     }
@@ -2116,14 +3677,14 @@ impl Manager {
                     let right = instance.particles[rod.right].position;
 
                     let lerp = (rod.length - left.dist(right)).abs()
-                        / (0.1 * rod.length);
+                        / (self.strain_window * rod.length);
 
                     debug.add_line(
                         alg::Line::new(left, right),
                         graphics::Color::lerp(
                             graphics::Color::green(),
                             graphics::Color::red(),
-                            lerp,
+                            lerp.min(1.0),
                         ),
                     );
                 }
@@ -2325,3 +3886,1447 @@ impl Manager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use entity;
+    use render;
+    #[cfg(debug_assertions)]
+    use debug;
+    #[cfg(debug_assertions)]
+    use graphics;
+    use components::Component;
+    use components::transform;
+    use components::softbody::*;
+    use FIXED_DT;
+
+    struct Game;
+    impl Iterate for Game { }
+
+    fn cube_model() -> render::ModelData {
+        let (vertices, indices) = render::test_cube();
+
+        render::ModelData::new_with_normals(
+            "cube",
+            vertices,
+            indices,
+            render::NormalMode::Flat,
+        )
+    }
+
+    #[test]
+    fn init_from_model() {
+        let model = cube_model();
+
+        let mut entities = entity::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        let entity = entities.add();
+        softbodies.register(entity);
+        softbodies.init_instance_from_model(entity, 1.0, &model);
+
+        // Eight corners; twelve cube edges plus one diagonal per face
+        let instance = softbodies.get_instance(entity);
+        assert!(instance.particles.len() == 8);
+        assert!(instance.rods.len() == 12 + 6);
+
+        let diagonal = 8f32.sqrt();
+
+        for rod in &instance.rods {
+            let error = (rod.length - 2.0).abs()
+                .min((rod.length - diagonal).abs());
+
+            assert!(error < 0.0001);
+        }
+    }
+
+    #[test]
+    fn bending() {
+        // Returns fold angle (radians) of cloth hung over its middle row
+        let drape = |bend_stiffness: f32| -> f32 {
+            let mut entities = entity::Manager::new(1);
+            let mut transforms = transform::Manager::new(1);
+            let mut softbodies = Manager::new(1, 0, 0);
+
+            let entity = entities.add();
+            transforms.register(entity);
+            softbodies.register(entity);
+
+            let cloth = softbodies.init_cloth(
+                entity,
+                1.0,
+                2.0,
+                2.0,
+                9,
+                None,
+                bend_stiffness,
+            );
+
+            for column in 0..9 {
+                softbodies.set_particle_mass(
+                    entity,
+                    cloth.particle(column, 4),
+                    std::f32::INFINITY,
+                );
+            }
+
+            for _ in 0..300 {
+                softbodies.simulate(&mut Game, &mut transforms);
+            }
+
+            let particle = |row| softbodies.get_particle(
+                entity,
+                cloth.particle(4, row),
+            );
+
+            let incoming = (particle(4) - particle(3)).norm();
+            let outgoing = (particle(5) - particle(4)).norm();
+            let angle = incoming.dot(outgoing).max(-1.0).min(1.0).acos();
+
+            eprintln!("Stiffness {}: fold {}", bend_stiffness, angle);
+            angle
+        };
+
+        let sharp = drape(0.0);
+        let smooth = drape(1.0);
+
+        assert!(smooth < sharp * 0.75);
+    }
+
+    #[test]
+    fn rods() {
+        let mut entities = entity::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        let entity = entities.add();
+        softbodies.register(entity);
+        softbodies.build_instance()
+            .particles(&[alg::Vec3::zero(), alg::Vec3::new(3.0, 4.0, 0.0)])
+            .indices(&[])
+            .bindings(&[(0, 1)])
+            .for_entity(entity);
+
+        let rods = softbodies.rods(entity);
+        assert!(rods.len() == 1);
+        assert!(rods[0].left == 0 && rods[0].right == 1);
+
+        let error = (rods[0].rest_length - 5.0).abs()
+            + (rods[0].current_length - 5.0).abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Stretch
+        softbodies.get_instance(entity).particles[1].position
+            = alg::Vec3::new(6.0, 8.0, 0.0);
+
+        let rod = softbodies.rods(entity)[0];
+        let error = (rod.rest_length - 5.0).abs()
+            + (rod.current_length - 10.0).abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn cloth() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+
+        let cloth = softbodies.init_cloth(
+            entity,
+            1.0,
+            2.0,
+            2.0,
+            4,
+            Some(ClothEdge::Top),
+            0.0, // No bending
+        );
+
+        // 24 structural rods and two shear rods for each of 9 cells
+        assert!(cloth.rods.len() == 24 + 18);
+        assert!(cloth.indices.len() == 9 * 6);
+        assert!(cloth.pinned == vec![0, 1, 2, 3]);
+        assert!(cloth.bends.is_empty());
+
+        let top = softbodies.get_particle(entity, cloth.particle(3, 0));
+        let bottom = softbodies.get_particle(entity, cloth.particle(1, 3));
+
+        for _ in 0..100 {
+            softbodies.simulate(&mut Game, &mut transforms);
+        }
+
+        let error = softbodies.get_particle(entity, cloth.particle(3, 0))
+            .dist(top);
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        let sag = bottom.y
+            - softbodies.get_particle(entity, cloth.particle(1, 3)).y;
+
+        eprintln!("Sag: {}", sag);
+        assert!(sag > 0.01);
+    }
+
+    #[test]
+    fn raycast() {
+        let mut entities = entity::Manager::new(2);
+        let mut softbodies = Manager::new(2, 0, 0);
+
+        let near = entities.add();
+        let far = entities.add();
+
+        for &(entity, z) in &[(near, 2.0), (far, 5.0)] {
+            softbodies.register(entity);
+            softbodies.build_instance()
+                .make_box_limb(alg::Vec3::one())
+                .initial_pos(alg::Vec3::fwd() * z)
+                .for_entity(entity);
+        }
+
+        // Front face of the nearer unit cube
+        let ray = alg::Line::new(
+            alg::Vec3::new(0.1, 0.2, 0.0),
+            alg::Vec3::new(0.1, 0.2, 1.0),
+        );
+
+        let (entity, point, dist) = softbodies.raycast(ray).unwrap();
+        assert!(entity == near);
+
+        let error = (dist - 1.5).abs()
+            + point.dist(alg::Vec3::new(0.1, 0.2, 1.5));
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Rejected by the bounding boxes
+        let miss = alg::Line::new(
+            alg::Vec3::right() * 2.0,
+            alg::Vec3::new(2.0, 0.0, 1.0),
+        );
+
+        assert!(softbodies.raycast(miss).is_none());
+
+        // Pointing away
+        let away = alg::Line::new(alg::Vec3::zero(), -alg::Vec3::fwd());
+        assert!(softbodies.raycast(away).is_none());
+    }
+
+    #[test]
+    fn drag() {
+        // Returns fall speed (meters per simulated second)
+        // after each simulated second
+        let fall = |drag: f32, time_scale: f32| -> Vec<f32> {
+            let mut entities = entity::Manager::new(1);
+            let mut transforms = transform::Manager::new(1);
+            let mut softbodies = Manager::new(1, 0, 0);
+
+            let entity = entities.add();
+            transforms.register(entity);
+            softbodies.register(entity);
+            softbodies.build_instance()
+                .make_box_limb(alg::Vec3::one())
+                .for_entity(entity);
+
+            softbodies.set_drag(entity, drag);
+            softbodies.set_time_scale(time_scale);
+
+            let dt = FIXED_DT * time_scale;
+
+            (0..4).map(|_| {
+                for _ in 0..(1.0 / dt) as usize {
+                    softbodies.simulate(&mut Game, &mut transforms);
+                }
+
+                let speed = softbodies.get_instance(entity).velocity().mag()
+                    / dt;
+
+                eprintln!("Drag {}: speed {}", drag, speed);
+                speed
+            }).collect()
+        };
+
+        // Without drag, the body keeps accelerating
+        let free = fall(0.0, 1.0);
+        assert!(free[3] > free[2] * 1.2);
+
+        // With drag, it settles at gravity / drag,
+        // regardless of the time scale
+        let terminal = MNGR_DEFAULT_GRAVITY / 4.0;
+
+        for &time_scale in &[1.0, 0.5] {
+            let damped = fall(4.0, time_scale);
+
+            for speed in &damped[1..] {
+                let error = (speed - terminal).abs() / terminal;
+                eprintln!("Error: {}", error);
+                assert!(error < 0.05);
+            }
+        }
+    }
+
+    #[test]
+    fn pressure() {
+        let model = cube_model();
+
+        let inflate = |pressure: f32| -> (f32, f32) {
+            let mut entities = entity::Manager::new(1);
+            let mut transforms = transform::Manager::new(1);
+            let mut softbodies = Manager::new(1, 0, 0);
+            softbodies.set_gravity(alg::Vec3::zero());
+
+            let entity = entities.add();
+            transforms.register(entity);
+            softbodies.register(entity);
+            softbodies.build_instance()
+                .from_model(&model)
+                .rigidity(0.05)
+                .for_entity(entity);
+
+            softbodies.set_pressure(entity, pressure);
+
+            let mut last = 0.0;
+
+            for _ in 0..500 {
+                last = softbodies.get_instance(entity).volume();
+                softbodies.simulate(&mut Game, &mut transforms);
+            }
+
+            let volume = softbodies.get_instance(entity).volume();
+            eprintln!("Pressure {}: volume {}", pressure, volume);
+
+            (volume, (volume - last).abs())
+        };
+
+        let (rest, _) = inflate(0.0);
+        let (low, low_change) = inflate(10.0);
+        let (high, high_change) = inflate(40.0);
+
+        assert!((rest - 8.0).abs() < 0.0001);
+        assert!(low > rest && high > low);
+
+        // Settled
+        assert!(low_change < 0.0001 && high_change < 0.0001);
+    }
+
+    #[test]
+    fn quad_edge() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        // Slide along x
+        softbodies.set_gravity(alg::Vec3::new(2.0, -9.8, 0.0));
+
+        softbodies.add_quad(
+            alg::Vec3::zero(),
+            alg::Vec3::up(),
+            alg::Vec2::new(2.0, 2.0),
+            alg::Quat::id(),
+        );
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .initial_pos(alg::Vec3::up() * 0.5)
+            .for_entity(entity);
+
+        let mut fell = false;
+
+        for _ in 0..1000 {
+            softbodies.simulate(&mut Game, &mut transforms);
+
+            let (min, max) = softbodies.get_instance(entity).bounds();
+
+            // Supported while entirely over the quad
+            if max.x < 2.0 {
+                assert!(min.y > -0.1);
+            }
+
+            // Entirely below the surface, past the edge
+            if max.y < -1.0 {
+                eprintln!("Fell at x = {}", min.x);
+                assert!(min.x > 2.0);
+
+                fell = true;
+                break;
+            }
+        }
+
+        assert!(fell);
+    }
+
+    #[test]
+    fn remove_plane() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 2, 0);
+
+        let upper = alg::Plane::new(alg::Vec3::up(), 0.0);
+        let lower = alg::Plane::new(alg::Vec3::up(), 3.0); // y = -3
+
+        softbodies.add_planes(&[upper, lower]);
+
+        assert!(softbodies.remove_plane(0) == Some(upper));
+        assert!(softbodies.remove_plane(1) == None);
+        assert!(softbodies.planes() == &[lower]);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .initial_pos(alg::Vec3::up())
+            .for_entity(entity);
+
+        for _ in 0..300 {
+            softbodies.simulate(&mut Game, &mut transforms);
+        }
+
+        // Falls through the removed plane, rests on the remaining one
+        let (min, _) = softbodies.get_instance(entity).bounds();
+        let error = (min.y + 3.0).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.1);
+
+        softbodies.clear_colliders();
+        assert!(softbodies.planes().is_empty());
+
+        for _ in 0..100 {
+            softbodies.simulate(&mut Game, &mut transforms);
+        }
+
+        let (_, max) = softbodies.get_instance(entity).bounds();
+        assert!(max.y < -3.0);
+    }
+
+    #[test]
+    fn transform_write_back() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        transforms.set_scale(entity, alg::Vec3::one() * 2.0);
+
+        softbodies.register(entity);
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .initial_pos(alg::Vec3::up())
+            .for_entity(entity);
+
+        softbodies.simulate(&mut Game, &mut transforms);
+
+        let center = softbodies.get_instance(entity).center();
+        assert!(transforms.get_position(entity) == center);
+        assert!(transforms.get_scale(entity) == alg::Vec3::one());
+    }
+
+    #[test]
+    fn attach_to_transform() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = transform::Manager::new(2);
+        let mut softbodies = Manager::new(1, 0, 0);
+        softbodies.set_gravity(alg::Vec3::zero());
+
+        let pole = entities.add();
+        transforms.register(pole);
+
+        let flag = entities.add();
+        transforms.register(flag);
+        softbodies.register(flag);
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .for_entity(flag);
+
+        // Corner particle follows the pole
+        let corner = softbodies.get_particle(flag, 0);
+        softbodies.attach_to_transform(flag, 0, pole, corner);
+
+        let far = softbodies.get_particle(flag, 6);
+
+        for i in 1..=10 {
+            let position = alg::Vec3::right() * 0.1 * i as f32;
+            transforms.set_position(pole, position);
+            softbodies.simulate(&mut Game, &mut transforms);
+
+            let error = softbodies.get_particle(flag, 0)
+                .dist(position + corner);
+
+            eprintln!("Error: {}", error);
+            assert!(error < 0.0001);
+        }
+
+        // Remainder of the body lags behind
+        let moved = softbodies.get_particle(flag, 6).x - far.x;
+        eprintln!("Far corner moved: {}", moved);
+        assert!(moved > 0.0 && moved < 1.0);
+    }
+
+    #[test]
+    fn change_gravity() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .for_entity(entity);
+
+        softbodies.set_force(entity, alg::Vec3::right());
+
+        // After initialization
+        let gravity = alg::Vec3::up() * -20.0;
+        softbodies.set_gravity(gravity);
+        assert!(softbodies.gravity() == gravity);
+
+        let start = softbodies.get_instance(entity).center();
+        softbodies.simulate(&mut Game, &mut transforms);
+        let offset = softbodies.get_instance(entity).center() - start;
+
+        // Eight particles share the unit mass
+        let expected = (alg::Vec3::right() * 8.0 + gravity)
+            * FIXED_DT * FIXED_DT;
+
+        let error = offset.dist(expected);
+        eprintln!("Error: {}", error);
+        assert!(error < 0.00001);
+    }
+
+    #[test]
+    fn set_active() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .for_entity(entity);
+
+        for _ in 0..10 {
+            softbodies.simulate(&mut Game, &mut transforms);
+        }
+
+        let center = softbodies.get_instance(entity).center();
+        let velocity = softbodies.get_instance(entity).velocity();
+
+        softbodies.set_active(entity, false);
+        assert!(!softbodies.is_active(entity));
+
+        for _ in 0..10 {
+            softbodies.simulate(&mut Game, &mut transforms);
+        }
+
+        assert!(softbodies.get_instance(entity).center() == center);
+        assert!(transforms.get_position(entity) == center);
+
+        // Resume with the same velocity
+        softbodies.set_active(entity, true);
+        softbodies.simulate(&mut Game, &mut transforms);
+
+        let resumed = softbodies.get_instance(entity).velocity();
+        assert!(resumed.y < velocity.y);
+        assert!(softbodies.get_instance(entity).center().y < center.y);
+    }
+
+    #[test]
+    fn registered() {
+        let mut entities = entity::Manager::new(2);
+        let mut softbodies = Manager::new(2, 0, 0);
+
+        let first = entities.add();
+        let second = entities.add();
+
+        assert!(!softbodies.registered(first));
+        assert!(!softbodies.registered(second));
+
+        softbodies.register(second);
+
+        // Index within bounds but not registered
+        assert!(!softbodies.registered(first));
+        assert!(softbodies.registered(second));
+
+        softbodies.register(first);
+        assert!(softbodies.registered(first));
+    }
+
+    #[test]
+    fn register_sparse() {
+        let mut entities = entity::Manager::new(4096);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        let entity = (0..4096).map(|_| entities.add()).last().unwrap();
+        let i = entity.get_index() as usize;
+
+        // Single resize straight to the new index
+        softbodies.register(entity);
+        assert!(softbodies.instances.len() == i + 1);
+        assert!(softbodies.handles.len() == i + 1);
+
+        assert!(softbodies.registered(entity));
+        assert!(softbodies.count() == 1);
+    }
+
+    #[test]
+    fn stack_order() {
+        let jitter = |stack_order: bool| {
+            let mut entities = entity::Manager::new(3);
+            let mut transforms = transform::Manager::new(3);
+            let mut softbodies = Manager::new(3, 0, 1);
+
+            softbodies.set_instance_collision(true);
+            softbodies.set_particle_radius(0.5);
+            softbodies.set_stack_order(stack_order);
+            softbodies.add_plane(alg::Plane::new(alg::Vec3::up(), 0.0));
+
+            // Registered top to bottom, slightly staggered
+            let bodies: Vec<entity::Handle> = (0..3).map(|i| {
+                let i = i as f32;
+
+                let entity = entities.add();
+                transforms.register(entity);
+                softbodies.register(entity);
+
+                softbodies.build_instance()
+                    .make_box_limb(alg::Vec3::one())
+                    .initial_pos(alg::Vec3::new(i, -i * 20.2 + 46.0, i) * 0.1)
+                    .for_entity(entity);
+
+                entity
+            }).collect();
+
+            let mut game = Game { };
+            for _ in 0..100 {
+                softbodies.simulate(&mut game, &mut transforms);
+            }
+
+            // Accumulated movement of the settled stack
+            let mut total = 0.0;
+
+            for _ in 0..100 {
+                let last: Vec<alg::Vec3> = bodies.iter()
+                    .map(|body| softbodies.get_instance(*body).center())
+                    .collect();
+
+                softbodies.simulate(&mut game, &mut transforms);
+
+                total += bodies.iter().zip(&last)
+                    .map(|(body, last)| {
+                        softbodies.get_instance(*body).center().dist(*last)
+                    }).sum::<f32>();
+            }
+
+            total
+        };
+
+        let index = jitter(false);
+        let stacked = jitter(true);
+
+        eprintln!("Jitter: {} -> {}", index, stacked);
+        assert!(stacked < index);
+    }
+
+    #[test]
+    fn stack_order_gravity() {
+        let mut entities = entity::Manager::new(2);
+        let mut softbodies = Manager::new(2, 0, 0);
+
+        softbodies.set_instance_collision(true);
+        softbodies.set_particle_radius(0.25);
+        softbodies.set_stack_order(true);
+
+        // Sideways gravity; the lower instance is registered last
+        softbodies.set_gravity(alg::Vec3::right() * -MNGR_DEFAULT_GRAVITY);
+
+        let bodies: Vec<entity::Handle> = [1.3, 0.0].iter().map(|x| {
+            let entity = entities.add();
+            softbodies.register(entity);
+
+            softbodies.build_instance()
+                .make_box_limb(alg::Vec3::one())
+                .initial_pos(alg::Vec3::right() * *x)
+                .for_entity(entity);
+
+            entity
+        }).collect();
+
+        let (upper, lower) = (bodies[0], bodies[1]);
+        let upper_start = softbodies.get_instance(upper).center();
+        let lower_start = softbodies.get_instance(lower).center();
+
+        softbodies.solve_instance_collisions();
+
+        // Only the instance above (against gravity) moves
+        let error = softbodies.get_instance(lower).center().dist(lower_start);
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        let moved = softbodies.get_instance(upper).center() - upper_start;
+        assert!(moved.x > 0.0);
+    }
+
+    #[test]
+    fn instance_collision() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = transform::Manager::new(2);
+        let mut softbodies = Manager::new(2, 0, 0);
+
+        softbodies.set_gravity(alg::Vec3::zero());
+        softbodies.set_instance_collision(true);
+        softbodies.set_particle_radius(0.5);
+
+        let a = entities.add();
+        let b = entities.add();
+
+        for (entity, position) in [
+            (a, alg::Vec3::zero()),
+            (b, alg::Vec3::new(0.7, 0.3, 0.2)),
+        ].iter() {
+            transforms.register(*entity);
+            softbodies.register(*entity);
+            softbodies.build_instance()
+                .make_box_limb(alg::Vec3::one())
+                .initial_pos(*position)
+                .for_entity(*entity);
+        }
+
+        // Closest pair of particles from different instances
+        let closest = |softbodies: &mut Manager| {
+            let others: Vec<alg::Vec3> = softbodies.get_instance(b)
+                .particles.iter()
+                .map(|particle| particle.position)
+                .collect();
+
+            softbodies.get_instance(a).particles.iter()
+                .flat_map(|particle| others.iter().map(
+                    move |other| particle.position.dist(*other)
+                )).fold(std::f32::MAX, f32::min)
+        };
+
+        let before = softbodies.get_instance(a).center()
+            .dist(softbodies.get_instance(b).center());
+
+        let closest_before = closest(&mut softbodies);
+
+        let mut game = Game;
+        for _ in 0..8 {
+            softbodies.simulate(&mut game, &mut transforms);
+        }
+
+        let after = softbodies.get_instance(a).center()
+            .dist(softbodies.get_instance(b).center());
+
+        let closest_after = closest(&mut softbodies);
+
+        eprintln!("Distance: {} -> {}", before, after);
+        eprintln!("Closest: {} -> {}", closest_before, closest_after);
+        assert!(after > before);
+
+        // Particles are kept a diameter apart
+        assert!(closest_before < 1.0);
+        assert!(closest_after > 0.99);
+    }
+
+    // Holds the first two particles of every instance in place
+    struct Pinned {
+        positions: [alg::Vec3; 2],
+    }
+
+    impl Iterate for Pinned {
+        fn iterate(&mut self, _: f32, _: usize, manager: &mut Manager) {
+            for instance in manager.instances.iter_mut()
+                .filter_map(|instance| instance.as_mut())
+            {
+                for (particle, position) in instance.particles.iter_mut()
+                    .zip(&self.positions)
+                {
+                    particle.position = *position;
+                    particle.last = *position;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn max_offset_magnitude() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+        softbodies.set_gravity(alg::Vec3::zero());
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .for_entity(entity);
+
+        let mut game = Game { };
+        softbodies.simulate(&mut game, &mut transforms);
+
+        let rest = softbodies.max_offset_magnitude(entity);
+        eprintln!("Magnitude: {}", rest);
+        assert!(rest < 0.0001);
+
+        let start = softbodies.get_particle(entity, 0);
+        let mut last = rest;
+
+        for push in &[0.1, 0.2, 0.4] {
+            let instance = softbodies.instances[0].as_mut().unwrap();
+            let position = start + alg::Vec3::up() * *push;
+
+            instance.particles[0].position = position;
+            instance.particles[0].last = position;
+
+            let magnitude = softbodies.max_offset_magnitude(entity);
+            eprintln!("Magnitude: {}", magnitude);
+
+            assert!(magnitude > last);
+            last = magnitude;
+        }
+    }
+
+    #[test]
+    fn rest_pose() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+        softbodies.set_gravity(alg::Vec3::zero());
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .for_entity(entity);
+
+        // Stretch along the up axis
+        let pose: Vec<alg::Vec3> = softbodies.get_rest_pose(entity).iter()
+            .map(|p| alg::Vec3::new(p.x, p.y * 2.0, p.z))
+            .collect();
+
+        softbodies.set_rest_pose(entity, &pose);
+        assert!(softbodies.get_rest_pose(entity) == &pose[..]);
+
+        let mut game = Game { };
+        for _ in 0..100 {
+            softbodies.simulate(&mut game, &mut transforms);
+        }
+
+        let (min, max) = softbodies.get_instance(entity).bounds();
+        let size = max - min;
+
+        eprintln!("Size: {}", size);
+        assert!((size.y - 2.0).abs() < 0.01);
+        assert!((size.x - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn rest_pose_bends() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+        softbodies.set_gravity(alg::Vec3::zero());
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+
+        // Straight chain, resisting folding
+        let points: Vec<alg::Vec3> = (0..5)
+            .map(|i| alg::Vec3::right() * i as f32)
+            .collect();
+
+        let bindings: Vec<(usize, usize)> = (0..4)
+            .map(|i| (i, i + 1))
+            .collect();
+
+        let bends: Vec<(usize, usize)> = (0..3)
+            .map(|i| (i, i + 2))
+            .collect();
+
+        softbodies.build_instance()
+            .particles(&points)
+            .indices(&[])
+            .bindings(&bindings)
+            .bends(&bends, 1.0)
+            .for_entity(entity);
+
+        // Fold the chain at its middle particle
+        let pose = [
+            alg::Vec3::zero(),
+            alg::Vec3::right(),
+            alg::Vec3::right() * 2.0,
+            alg::Vec3::right() * 2.0 + alg::Vec3::up(),
+            alg::Vec3::right() * 2.0 + alg::Vec3::up() * 2.0,
+        ];
+
+        {
+            let instance = softbodies.instances[0].as_mut().unwrap();
+
+            for (particle, position) in instance.particles.iter_mut()
+                .zip(&pose)
+            {
+                particle.position = *position;
+                particle.last = *position;
+            }
+        }
+
+        softbodies.set_rest_pose(entity, &pose);
+
+        let mut game = Game { };
+        for _ in 0..100 {
+            softbodies.simulate(&mut game, &mut transforms);
+        }
+
+        // Bends hold the folded shape instead of straightening it
+        let error = pose.iter().enumerate()
+            .map(|(i, position)| {
+                softbodies.get_particle(entity, i).dist(*position)
+            }).fold(0.0, f32::max);
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.01);
+    }
+
+    #[test]
+    fn over_relaxation() {
+        // Hanging chain, pinned at the top, with a heavy end
+        let points: Vec<alg::Vec3> = (0..8)
+            .map(|i| alg::Vec3::up() * -(i as f32))
+            .collect();
+
+        let bindings: Vec<(usize, usize)> = (0..7)
+            .map(|i| (i, i + 1))
+            .collect();
+
+        let stretch = |factor: f32| {
+            let mut entities = entity::Manager::new(1);
+            let mut transforms = transform::Manager::new(1);
+            let mut softbodies = Manager::new(1, 0, 0);
+            softbodies.set_over_relaxation(factor);
+
+            let entity = entities.add();
+            transforms.register(entity);
+            softbodies.register(entity);
+
+            softbodies.build_instance()
+                .particles(&points)
+                .indices(&[])
+                .bindings(&bindings)
+                .for_entity(entity);
+
+            softbodies.set_particle_mass(entity, 0, std::f32::INFINITY);
+            softbodies.set_particle_mass(entity, 7, 20.0);
+
+            let mut game = Game { };
+            for _ in 0..20 {
+                softbodies.simulate(&mut game, &mut transforms);
+            }
+
+            // Total rest length error over the chain
+            let bottom = softbodies.get_particle(entity, 7);
+            bottom.dist(alg::Vec3::zero()) - 7.0
+        };
+
+        let plain = stretch(1.0);
+        let relaxed = stretch(1.5);
+
+        eprintln!("Stretch: {} -> {}", plain, relaxed);
+        assert!(relaxed < plain);
+
+        // Excessive values are clamped
+        let mut softbodies = Manager::new(1, 0, 0);
+        softbodies.set_over_relaxation(5.0);
+        assert!(softbodies.over_relaxation == MAX_OVER_RELAXATION);
+    }
+
+    #[test]
+    fn add_remove_rod() {
+        // Vertical post with a horizontal arm hinged at its base
+        let points = [
+            alg::Vec3::up(),
+            alg::Vec3::zero(),
+            alg::Vec3::right(),
+        ];
+
+        let sag = |brace: bool| {
+            let mut entities = entity::Manager::new(1);
+            let mut transforms = transform::Manager::new(1);
+            let mut softbodies = Manager::new(1, 0, 0);
+
+            let entity = entities.add();
+            transforms.register(entity);
+            softbodies.register(entity);
+
+            softbodies.build_instance()
+                .particles(&points)
+                .indices(&[])
+                .bindings(&[(0, 1), (1, 2)])
+                .for_entity(entity);
+
+            // Brace the arm against the top of the post
+            if brace {
+                softbodies.add_rod(entity, 2, 0);
+            }
+
+            let mut game = Pinned { positions: [points[0], points[1]] };
+            for _ in 0..50 {
+                softbodies.simulate(&mut game, &mut transforms);
+            }
+
+            -softbodies.get_particle(entity, 2).y
+        };
+
+        let floppy = sag(false);
+        let braced = sag(true);
+
+        eprintln!("Sag: {} -> {}", floppy, braced);
+        assert!(braced < floppy * 0.1);
+
+        // Removal
+        let mut entities = entity::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        let entity = entities.add();
+        softbodies.register(entity);
+        softbodies.build_instance()
+            .particles(&points)
+            .indices(&[])
+            .bindings(&[(0, 1), (1, 2)])
+            .for_entity(entity);
+
+        assert!(softbodies.remove_rod(entity, 2, 1));
+        assert!(!softbodies.remove_rod(entity, 2, 1));
+        assert!(softbodies.get_instance(entity).rods.len() == 1);
+    }
+
+    #[test]
+    fn particle_mass() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        softbodies.set_gravity(alg::Vec3::zero());
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+
+        softbodies.build_instance()
+            .particles(&[alg::Vec3::zero(), alg::Vec3::right()])
+            .indices(&[])
+            .bindings(&[(0, 1)])
+            .for_entity(entity);
+
+        softbodies.set_particle_mass(entity, 0, 100.0);
+        softbodies.set_particle_mass(entity, 1, 1.0);
+
+        // Stretch the rod
+        {
+            let particle = &mut softbodies.get_instance(entity).particles[1];
+            particle.position = alg::Vec3::right() * 2.0;
+            particle.last = particle.position;
+        }
+
+        softbodies.simulate(&mut Game, &mut transforms);
+
+        let heavy = softbodies.get_particle(entity, 0).mag();
+        let light = softbodies.get_particle(entity, 1)
+            .dist(alg::Vec3::right() * 2.0);
+
+        eprintln!("Heavy: {} Light: {}", heavy, light);
+        assert!(heavy < light * 0.05);
+
+        // Pinned particle does not move at all
+        softbodies.set_particle_mass(entity, 0, std::f32::INFINITY);
+        softbodies.get_instance(entity).particles[1].position
+            = alg::Vec3::right() * 3.0;
+
+        let before = softbodies.get_particle(entity, 0);
+        softbodies.simulate(&mut Game, &mut transforms);
+
+        assert!(softbodies.get_particle(entity, 0) == before);
+    }
+
+    #[test]
+    fn weighted_center() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        softbodies.set_gravity(alg::Vec3::zero());
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .for_entity(entity);
+
+        let error = softbodies.get_instance(entity).center().mag();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Make the right side of the box three times heavier
+        for i in 0..8 {
+            let right = softbodies.get_particle(entity, i).x > 0.0;
+            let mass = if right { 3.0 } else { 1.0 };
+            softbodies.set_particle_mass(entity, i, mass);
+        }
+
+        let center = softbodies.get_instance(entity).center();
+        let error = center.dist(alg::Vec3::right() * 0.25);
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Resting body keeps its shape and writes back the weighted center
+        softbodies.simulate(&mut Game, &mut transforms);
+
+        let error = transforms.get_position(entity).dist(center)
+            + softbodies.get_instance(entity).velocity().mag();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // A pinned corner weighs as much as a free particle,
+        // instead of pulling the center onto the pin
+        for i in 0..8 {
+            softbodies.set_particle_mass(entity, i, 1.0);
+        }
+
+        let corner = softbodies.get_particle(entity, 0);
+        softbodies.set_particle_mass(entity, 0, std::f32::INFINITY);
+
+        let center = softbodies.get_instance(entity).center();
+        let error = center.mag();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+        assert!(center.dist(corner) > 0.5);
+    }
+
+    #[test]
+    fn recover() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .for_entity(entity);
+
+        let mut game = Game { };
+
+        for _ in 0..2 {
+            let nan = alg::Vec3::one() * std::f32::NAN;
+            softbodies.get_instance(entity).particles[0].position = nan;
+            softbodies.simulate(&mut game, &mut transforms);
+
+            // Reset each time, warning only once
+            let instance = softbodies.get_instance(entity);
+            assert!(instance.is_finite());
+            assert!(instance.warned);
+        }
+    }
+
+    #[test]
+    fn recovered_orientation() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        softbodies.set_gravity(alg::Vec3::zero());
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::new(1.0, 0.5, 2.0))
+            .initial_pos(alg::Vec3::up())
+            .for_entity(entity);
+
+        let rotation = alg::Quat::axis_angle(
+            alg::Vec3::new(1.0, 2.0, -0.5),
+            70f32.to_radians(),
+        );
+
+        // Rigidly rotate particles about the body center
+        {
+            let instance = softbodies.get_instance(entity);
+            let center = instance.center();
+
+            instance.rotate_around(rotation, center);
+            instance.lock();
+        }
+
+        softbodies.simulate(&mut Game, &mut transforms);
+
+        let orientation = transforms.get_orientation(entity);
+
+        let error = (orientation * alg::Vec3::fwd())
+            .dist(rotation * alg::Vec3::fwd())
+            + (orientation * alg::Vec3::up())
+            .dist(rotation * alg::Vec3::up())
+            + transforms.get_position(entity).dist(alg::Vec3::up());
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn strain_color() {
+        let mut entities = entity::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+        let mut debug = debug::Handler::new();
+
+        let entity = entities.add();
+        softbodies.register(entity);
+        softbodies.build_instance()
+            .particles(&[alg::Vec3::zero(), alg::Vec3::right()])
+            .indices(&[])
+            .bindings(&[(0, 1)])
+            .for_entity(entity);
+
+        softbodies.set_strain_window(0.05);
+
+        let rod_color = |softbodies: &Manager, debug: &mut debug::Handler| {
+            debug.clear_lines();
+            softbodies.draw_entity(entity, false, false, debug);
+            debug.lines[0].start.color
+        };
+
+        // At rest
+        let color = rod_color(&softbodies, &mut debug);
+        assert!(color == graphics::Color::green());
+
+        // Halfway through the strain window
+        softbodies.get_instance(entity).particles[1].position
+            = alg::Vec3::right() * 1.025;
+
+        let color = rod_color(&softbodies, &mut debug);
+        let error = (color.r - 0.5).abs() + (color.g - 0.5).abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Far past the strain window; does not overshoot red
+        softbodies.get_instance(entity).particles[1].position
+            = alg::Vec3::right() * 3.0;
+
+        let color = rod_color(&softbodies, &mut debug);
+        assert!(color == graphics::Color::red());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn draw_all_instances() {
+        let mut entities = entity::Manager::new(3);
+        let mut softbodies = Manager::new(3, 0, 0);
+        let mut debug = debug::Handler::new();
+
+        let first = entities.add();
+        let empty = entities.add(); // Leaves a vacant slot
+        let second = entities.add();
+
+        for &(entity, bindings) in &[(first, 1), (second, 2)] {
+            softbodies.register(entity);
+            softbodies.build_instance()
+                .particles(&[alg::Vec3::zero(), alg::Vec3::right()])
+                .indices(&[])
+                .bindings(&[(0, 1), (1, 0)][..bindings])
+                .for_entity(entity);
+        }
+
+        assert!(!softbodies.registered(empty));
+
+        softbodies.draw_all_instances(&mut debug);
+        assert!(debug.lines.len() == 3);
+    }
+
+    #[test]
+    fn instance_gravity() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = transform::Manager::new(2);
+        let mut softbodies = Manager::new(2, 0, 0);
+
+        let balloon = entities.add();
+        let rock = entities.add();
+
+        for &entity in &[balloon, rock] {
+            transforms.register(entity);
+            softbodies.register(entity);
+            softbodies.build_instance()
+                .make_box_limb(alg::Vec3::one())
+                .for_entity(entity);
+        }
+
+        softbodies.set_instance_gravity(balloon, alg::Vec3::up() * 2.0);
+
+        // Global gravity changes do not affect the override
+        softbodies.set_gravity(alg::Vec3::up() * -5.0);
+
+        for _ in 0..10 {
+            softbodies.simulate(&mut Game, &mut transforms);
+        }
+
+        assert!(transforms.get_position(balloon).y > 0.01);
+        assert!(transforms.get_position(rock).y < -0.01);
+
+        // Reverted instance falls again
+        softbodies.clear_instance_gravity(balloon);
+        softbodies.get_instance(balloon).lock();
+
+        let before = transforms.get_position(balloon).y;
+        softbodies.simulate(&mut Game, &mut transforms);
+        assert!(transforms.get_position(balloon).y < before);
+    }
+
+    #[test]
+    fn kinetic_energy() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = transform::Manager::new(2);
+        let mut softbodies = Manager::new(2, 0, 0);
+
+        softbodies.set_gravity(alg::Vec3::zero());
+
+        let resting = entities.add();
+        let moving = entities.add();
+
+        for &entity in &[resting, moving] {
+            transforms.register(entity);
+            softbodies.register(entity);
+            softbodies.build_instance()
+                .make_box_limb(alg::Vec3::one())
+                .mass(2.0)
+                .for_entity(entity);
+        }
+
+        // Impulse: one meter per second along X
+        for particle in &mut softbodies.get_instance(moving).particles {
+            particle.last = particle.position - alg::Vec3::right() * FIXED_DT;
+        }
+
+        softbodies.simulate(&mut Game, &mut transforms);
+
+        let rest = softbodies.kinetic_energy(resting);
+        let error = (softbodies.kinetic_energy(moving) - 1.0).abs();
+
+        eprintln!("Rest: {} Error: {}", rest, error);
+        assert!(rest < 0.0001);
+        assert!(error < 0.001);
+
+        let error = (softbodies.total_kinetic_energy() - 1.0 - rest).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn coincident_particles() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+
+        softbodies.build_instance()
+            .particles(&[alg::Vec3::zero(), alg::Vec3::right()])
+            .indices(&[])
+            .bindings(&[(0, 1)])
+            .for_entity(entity);
+
+        softbodies.simulate(&mut Game, &mut transforms);
+
+        // Collapse the rod
+        {
+            let instance = softbodies.get_instance(entity);
+            instance.particles[1].position = instance.particles[0].position;
+            instance.particles[1].last = instance.particles[0].last;
+        }
+
+        for _ in 0..4 {
+            softbodies.simulate(&mut Game, &mut transforms);
+
+            assert!(softbodies.get_instance(entity).is_finite());
+            assert!(transforms.get_position(entity).is_finite());
+        }
+
+        // NaNs are caught and reset
+        softbodies.get_instance(entity).particles[0].position
+            = alg::Vec3::one() * std::f32::NAN;
+
+        softbodies.simulate(&mut Game, &mut transforms);
+        assert!(softbodies.get_instance(entity).is_finite());
+    }
+
+    #[test]
+    fn time_scale() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = transform::Manager::new(1);
+        let mut softbodies = Manager::new(1, 0, 0);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        softbodies.register(entity);
+        softbodies.build_instance()
+            .make_box_limb(alg::Vec3::one())
+            .for_entity(entity);
+
+        let displacement = |softbodies: &mut Manager| {
+            let particle = &softbodies.get_instance(entity).particles[0];
+            particle.position - particle.last
+        };
+
+        for _ in 0..10 {
+            softbodies.simulate(&mut Game, &mut transforms);
+        }
+
+        // Paused
+        softbodies.set_time_scale(0.0);
+        let before = softbodies.get_particle(entity, 0);
+        let moving = displacement(&mut softbodies);
+
+        softbodies.simulate(&mut Game, &mut transforms);
+        assert!(softbodies.get_particle(entity, 0) == before);
+
+        // Half speed
+        softbodies.set_time_scale(0.5);
+        softbodies.simulate(&mut Game, &mut transforms);
+
+        let gravity_dt = alg::Vec3::new(0., -9.8, 0.) * FIXED_DT * FIXED_DT;
+        let expected = moving * 0.5 + gravity_dt * 0.25;
+
+        let error = displacement(&mut softbodies).dist(expected);
+        eprintln!("Error: {}", error);
+        assert!(error < 0.000001);
+    }
+}
@@ -56,6 +56,8 @@ pub struct Manager {
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
+        debug_validate_unregistered!(self, entity);
+
         self.instances.insert(
             entity,
             render::Text::empty_3d_instance(),
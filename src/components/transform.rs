@@ -1,6 +1,11 @@
 use alg;
 use entity;
 use components;
+use debug;
+
+use components::scene;
+
+use components::Component;
 
 macro_rules! get_instance {
     ($self: ident, $entity: expr) => {{
@@ -10,28 +15,17 @@ macro_rules! get_instance {
     }}
 }
 
-#[allow(unused_macros)]
-macro_rules! get_mut_instance {
-    ($self: ident, $entity: expr) => {{
-        debug_validate_entity!($self, $entity);
-        let i = $entity.get_index() as usize;
-        $self.instances[i].as_mut().unwrap()
-    }}
-}
-
 macro_rules! get_mut_instance_raw {
-    ($self: expr, $index: expr) => {
-        unsafe {
-            debug_assert!($index < $self.instances.len());
-            debug_assert!($self.instances[$index].is_some());
-
-            let ptr = $self.instances.as_mut_ptr().offset($index as isize);
-            (*ptr).as_mut().unwrap()
-        }
-    }
+    ($self: expr, $index: expr) => {{
+        debug_assert!($index < $self.instances.len());
+        debug_assert!($self.instances[$index].is_some());
+        $self.instances[$index].as_mut().unwrap()
+    }}
 }
 
 pub struct Transform {
+    handle: entity::Handle,
+
           position: alg::Vec3,
     local_position: alg::Vec3,
           orientation: alg::Quat,
@@ -42,11 +36,20 @@ pub struct Transform {
     parent: Option<usize>,
     children: Vec<usize>,
     cached_transform: alg::Mat4,
+
+    bounds_radius: f32, // Local bounding sphere radius
+
+    // Worldspace state as of the previous fixed step
+    last_position: alg::Vec3,
+    last_orientation: alg::Quat,
+    stored: bool, // False until a fixed step records the last state
 }
 
 impl Transform {
-    fn blank(child_hint: usize) -> Transform {
+    fn blank(handle: entity::Handle, child_hint: usize) -> Transform {
         Transform {
+            handle,
+
                   position: alg::Vec3::zero(),
             local_position: alg::Vec3::zero(),
                   orientation: alg::Quat::id(),
@@ -57,32 +60,34 @@ impl Transform {
             parent: None,
             children: Vec::with_capacity(child_hint),
             cached_transform: alg::Mat4::id(),
+
+            bounds_radius: 0.0,
+
+            // Seeded with the current state until the first fixed step
+            last_position: alg::Vec3::zero(),
+            last_orientation: alg::Quat::id(),
+            stored: false,
         }
     }
 
-    /// Set/update transform with respect to parent. \
-    /// Note that the mutable reference to self could only have been obtained
-    /// unsafely.
-    fn update_cached(&mut self, manager: &Manager) {
+    /// Follow the current state until a fixed step has stored it,
+    /// so that new instances don't interpolate from their initial state
+    fn seed_last(&mut self) {
+        if !self.stored {
+            self.last_position = self.position;
+            self.last_orientation = self.orientation;
+        }
+    }
+
+    /// Set/update transform with respect to parent transform
+    fn update_cached(&mut self, parent_transform: alg::Mat4) {
         // The method is only called internally,
         // so we can assume this will succeed
         debug_assert!(self.parent.is_some());
-        let parent = manager.instances[self.parent.unwrap()].as_ref().unwrap();
-
-        // Check for non-uniform scale at runtime
-        #[cfg(debug_assertions)] {
-            let parent_scale = parent.cached_transform.to_scale();
-            if !parent_scale.is_uniform() {
-                eprintln!(
-                    "Warning: Non-uniform scale is not supported \
-                    in transform hierarchy"
-                );
-            }
-        }
 
         // Rebuild cached transform for this instance
         let transform =
-            parent.cached_transform
+            parent_transform
             * alg::Mat4::transform(
                 self.local_position,
                 self.local_orientation,
@@ -91,22 +96,50 @@ impl Transform {
 
         /* Assign transform data */
 
-        let scale = transform.to_scale();
+        let basis = transform.to_mat3();
+
+        // Uniformly scaled parents preserve orthogonal axes
+        let (mut scale, mut rotation) = if parent_transform.to_scale()
+            .is_uniform()
+        {
+            let scale = transform.to_scale();
+            (scale, transform.to_rotation_raw(scale))
+        }
+
+        // Non-uniformly scaled parents shear rotated children--
+        // separate rotation from scale using polar decomposition
+        else {
+            let stretch = (basis.transpose() * basis).sqrt();
+
+            (
+                alg::Vec3::new(stretch.x0, stretch.y1, stretch.z2),
+                basis * stretch.inverse(),
+            )
+        };
+
+        // Mirrored transform--move the reflection into the scale,
+        // keeping the extracted rotation proper
+        if basis.det() < 0.0 {
+            scale.x = -scale.x;
+            rotation = rotation * alg::Mat3::new_diagonal(-1.0, 1.0, 1.0);
+        }
 
         self.scale = scale;
-        self.orientation = transform.to_rotation_raw(scale).to_quat();
+        self.orientation = rotation.to_quat();
+
         self.position = transform.to_position();
         self.cached_transform = transform;
+        self.seed_last();
     }
+}
 
-    /// Recursively call `update_cached()` on all children
-    unsafe fn update_children(&self, manager: &mut Manager) {
-        for child_index in &self.children {
-            #[allow(unused_unsafe)]
-            let child = get_mut_instance_raw!(manager, *child_index);
-
-            child.update_cached(manager);
-            child.update_children(manager);
+// Zero scale collapses the transform and cannot be decomposed;
+// negative components (mirroring) are allowed
+#[inline] #[allow(unused_variables)]
+fn debug_validate_scale(scale: alg::Vec3) {
+    #[cfg(debug_assertions)] {
+        if scale.x == 0.0 || scale.y == 0.0 || scale.z == 0.0 {
+            panic!("Transform scale {} has a zero component", scale);
         }
     }
 }
@@ -119,19 +152,15 @@ pub struct Manager {
 
 impl components::Component for Manager {
     fn register(&mut self, entity: entity::Handle) {
+        debug_validate_unregistered!(self, entity);
         let i = entity.get_index() as usize;
 
         // Resize array to fit new entity
-        loop {
-            if i >= self.instances.len() {
-                self.instances.push(None);
-                continue;
-            }
-
-            break;
+        if i >= self.instances.len() {
+            self.instances.resize_with(i + 1, || None);
         }
 
-        self.instances[i] = Some(Transform::blank(0));
+        self.instances[i] = Some(Transform::blank(entity, 0));
         self.count += 1;
     }
 
@@ -167,32 +196,94 @@ impl Manager {
         }
 
         let transform_index = entity.get_index() as usize;
-        let transform = get_mut_instance_raw!(self, transform_index);
-
         let parent_index = parent.get_index() as usize;
-        let parent_transform = get_mut_instance_raw!(self, parent_index);
 
-        #[cfg(debug_assertions)] {
-            if transform.children.contains(&parent_index) {
+        // Walk up the ancestor chain of the parent to prevent cycles
+        if self.is_ancestor(transform_index, parent_index) {
+            #[cfg(debug_assertions)] {
                 panic!(
-                    "Attemped to parent entity {} to its child {}",
+                    "Attemped to parent entity {} to its descendant {}",
                     entity,
                     parent,
                 );
             }
+
+            #[cfg(not(debug_assertions))] {
+                eprintln!(
+                    "Warning: Ignored parenting entity {} \
+                    to its descendant {}",
+                    entity,
+                    parent,
+                );
+
+                return;
+            }
         }
 
-        transform.parent = Some(parent_index);
+        let previous = {
+            let transform = get_mut_instance_raw!(self, transform_index);
+            transform.parent.replace(parent_index)
+        };
 
-        if !parent_transform.children.contains(&transform_index) {
-            parent_transform.children.push(transform_index);
+        // Unlink from the previous parent when reparenting
+        if let Some(previous) = previous {
+            if previous != parent_index {
+                get_mut_instance_raw!(self, previous)
+                    .children.retain(|&child| child != transform_index);
+            }
+        }
+
+        {
+            let parent_transform = get_mut_instance_raw!(self, parent_index);
+
+            if !parent_transform.children.contains(&transform_index) {
+                parent_transform.children.push(transform_index);
+            }
         }
 
         // TODO: Potentially update local transform
         // relative to the new parent at the time of assignment
 
-        transform.update_cached(self);
-        unsafe { transform.update_children(self); }
+        self.update_cached(transform_index);
+        self.update_children(transform_index);
+    }
+
+    /// Returns iterator over all registered transforms and their cached
+    /// worldspace matrices, in index order
+    pub fn world_matrices<'a>(
+        &'a self,
+    ) -> impl Iterator<Item = (entity::Handle, &'a alg::Mat4)> + 'a {
+        self.instances.iter()
+            .filter_map(|instance| instance.as_ref())
+            .map(|transform| (transform.handle, &transform.cached_transform))
+    }
+
+    /// Returns the direct children of `entity`
+    pub fn children(&self, entity: entity::Handle) -> Vec<entity::Handle> {
+        let transform = get_instance!(self, entity);
+
+        transform.children.iter()
+            .map(|index| self.instances[*index].as_ref().unwrap().handle)
+            .collect()
+    }
+
+    /// Returns the parent of `entity`, if it has one
+    pub fn parent_of(&self, entity: entity::Handle) -> Option<entity::Handle> {
+        let transform = get_instance!(self, entity);
+
+        transform.parent
+            .map(|index| self.instances[index].as_ref().unwrap().handle)
+    }
+
+    /// Returns a depth-first iterator over all descendants of `entity`
+    /// (excluding `entity` itself)
+    pub fn descendants(&self, entity: entity::Handle) -> Descendants {
+        let transform = get_instance!(self, entity);
+
+        Descendants {
+            manager: self,
+            stack: transform.children.iter().rev().cloned().collect(),
+        }
     }
 
     /// Returns tuple of position, rotation, scale \
@@ -237,6 +328,44 @@ impl Manager {
         transform.scale
     }
 
+    /// Returns worldspace position and orientation blended between
+    /// the previous fixed step (`alpha` = 0) and the current state
+    /// (`alpha` = 1)
+    pub fn get_interpolated(
+        &self,
+        entity: entity::Handle,
+        alpha: f32,
+    ) -> (alg::Vec3, alg::Quat) {
+        let transform = get_instance!(self, entity);
+
+        (
+            transform.last_position.lerp(transform.position, alpha),
+            transform.last_orientation.slerp(transform.orientation, alpha),
+        )
+    }
+
+    /// Returns worldspace bounding sphere as tuple of center, radius. \
+    /// The radius is scaled by the largest axis of the world scale.
+    pub fn world_bounds(&self, entity: entity::Handle) -> (alg::Vec3, f32) {
+        let transform = get_instance!(self, entity);
+
+        let scale = transform.scale;
+        let max_scale = f32::max(
+            f32::max(scale.x.abs(), scale.y.abs()),
+            scale.z.abs(),
+        );
+
+        (transform.position, transform.bounds_radius * max_scale)
+    }
+
+    /// Set local bounding sphere radius (defaults to zero)
+    pub fn set_bounds_radius(&mut self, entity: entity::Handle, radius: f32) {
+        debug_assert!(radius >= 0.0);
+        debug_validate_entity!(self, entity);
+        let i = entity.get_index() as usize;
+        get_mut_instance_raw!(self, i).bounds_radius = radius;
+    }
+
     /// Set transform data \
     /// Faster than setting the fields individually
     pub fn set(
@@ -247,6 +376,7 @@ impl Manager {
         scale: alg::Vec3,
     ) {
         debug_validate_entity!(self, entity);
+        debug_validate_scale(scale);
         let i = entity.get_index() as usize;
         self.set_raw(i, position, orientation, scale);
     }
@@ -259,47 +389,223 @@ impl Manager {
     ) {
         debug_validate_entity!(self, entity);
         let i = entity.get_index() as usize;
-        let transform = get_mut_instance_raw!(self, i);
-        transform.local_position = position;
+        self.set_position_i(i, position);
+    }
+
+    /// Set transform position in world space,
+    /// converting it into the space of the parent (if any)
+    pub fn set_world_position(
+        &mut self,
+        entity: entity::Handle,
+        position: alg::Vec3,
+    ) {
+        let local = match get_instance!(self, entity).parent {
+            Some(parent) => {
+                let parent = self.instances[parent].as_ref().unwrap()
+                    .cached_transform;
+
+                parent.to_mat3().inverse() * (position - parent.to_position())
+            },
+            None => position,
+        };
+
+        let i = entity.get_index() as usize;
+        self.set_position_i(i, local);
+    }
+
+    /// Set transform orientation
+    pub fn set_orientation(
+        &mut self,
+        entity: entity::Handle,
+        orientation: alg::Quat,
+    ) {
+        debug_validate_entity!(self, entity);
+        let i = entity.get_index() as usize;
+        self.set_orientation_i(i, orientation);
+    }
+
+    /// Set transform scale
+    pub fn set_scale(
+        &mut self,
+        entity: entity::Handle,
+        scale: alg::Vec3,
+    ) {
+        debug_validate_entity!(self, entity);
+        debug_validate_scale(scale);
+        let i = entity.get_index() as usize;
+        self.set_scale_i(i, scale);
+    }
+
+    /// Offset transform local position by `delta`
+    pub fn translate(&mut self, entity: entity::Handle, delta: alg::Vec3) {
+        let position = get_instance!(self, entity).local_position;
+        self.set_position(entity, position + delta);
+    }
+
+    /// Apply rotation `delta` on top of the transform local orientation
+    pub fn rotate(&mut self, entity: entity::Handle, delta: alg::Quat) {
+        let orientation = get_instance!(self, entity).local_orientation;
+        self.set_orientation(entity, delta * orientation);
+    }
+
+    /// Multiply transform local scale component-wise by `factor`
+    pub fn scale_by(&mut self, entity: entity::Handle, factor: alg::Vec3) {
+        let scale = get_instance!(self, entity).local_scale;
+
+        self.set_scale(
+            entity,
+            alg::Vec3::new(
+                scale.x * factor.x,
+                scale.y * factor.y,
+                scale.z * factor.z,
+            ),
+        );
+    }
+
+    /// Orient transform so that its forward axis points toward `target`
+    /// in worldspace
+    pub fn look_at(
+        &mut self,
+        entity: entity::Handle,
+        target: alg::Vec3,
+        up: alg::Vec3,
+    ) {
+        let (position, parent) = {
+            let transform = get_instance!(self, entity);
+            (transform.position, transform.parent)
+        };
+
+        let orientation = alg::Quat::look_at(position, target, up);
+
+        // Convert worldspace orientation into local space
+        let local = match parent {
+            Some(index) => {
+                let parent = self.instances[index].as_ref().unwrap();
+                parent.orientation.conjugate() * orientation
+            },
+            None => orientation,
+        };
+
+        self.set_orientation(entity, local);
+    }
+
+    /// Draw worldspace axes of every transform,
+    /// each `scale` units long
+    #[allow(unused_variables)]
+    pub fn draw_all(&self, scale: f32, debug: &mut debug::Handler) {
+        #[cfg(debug_assertions)] {
+            for transform in self.instances.iter().filter_map(|i| i.as_ref()) {
+                debug.add_transform(
+                    transform.position,
+                    transform.orientation,
+                    scale,
+                );
+            }
+        }
+    }
+
+    /* "Unsafe" methods for components with similar data layouts.
+     * These technically invalidate the ECS model but are used
+     * for performance purposes.
+     */
+
+    /// Set transform data and update chain
+    pub(crate) fn set_raw(
+        &mut self,
+        index: usize,
+        position: alg::Vec3,
+        orientation: alg::Quat,
+        scale: alg::Vec3,
+    ) {
+        let has_parent = {
+            let transform = get_mut_instance_raw!(self, index);
+            transform.local_position = position;
+            transform.local_orientation = orientation;
+            transform.local_scale = scale;
+            transform.parent.is_some()
+        };
 
         /* Set worldspace transform data */
 
         // If this transform has a parent, update in chain
-        if transform.parent.is_some() {
-            transform.update_cached(self);
+        if has_parent {
+            self.update_cached(index);
         }
 
         // No parent (chain root)--just set data
         else {
+            let transform = get_mut_instance_raw!(self, index);
+            transform.position = position;
+            transform.orientation = orientation;
+            transform.scale = scale;
+            transform.seed_last();
+
+            transform.cached_transform = alg::Mat4::transform(
+                position,
+                orientation,
+                scale,
+            );
+        }
+
+        // Update children transforms
+        self.update_children(index);
+    }
+
+    /// Set position of the instance at `index` and update chain
+    pub(crate) fn set_position_i(
+        &mut self,
+        index: usize,
+        position: alg::Vec3,
+    ) {
+        let has_parent = {
+            let transform = get_mut_instance_raw!(self, index);
+            transform.local_position = position;
+            transform.parent.is_some()
+        };
+
+        /* Set worldspace transform data */
+
+        // If this transform has a parent, update in chain
+        if has_parent {
+            self.update_cached(index);
+        }
+
+        // No parent (chain root)--just set data
+        else {
+            let transform = get_mut_instance_raw!(self, index);
             transform.position = position;
             transform.cached_transform.set_translation(position);
+            transform.seed_last();
         }
 
         // Update children transforms
-        unsafe { transform.update_children(self); }
+        self.update_children(index);
     }
 
-    /// Set transform orientation
-    pub fn set_orientation(
+    /// Set orientation of the instance at `index` and update chain
+    pub(crate) fn set_orientation_i(
         &mut self,
-        entity: entity::Handle,
+        index: usize,
         orientation: alg::Quat,
     ) {
-        debug_validate_entity!(self, entity);
-        let i = entity.get_index() as usize;
-        let transform = get_mut_instance_raw!(self, i);
-        transform.local_orientation = orientation;
+        let has_parent = {
+            let transform = get_mut_instance_raw!(self, index);
+            transform.local_orientation = orientation;
+            transform.parent.is_some()
+        };
 
         /* Set worldspace transform data */
 
         // If this transform has a parent, update in chain
-        if transform.parent.is_some() {
-            transform.update_cached(self);
+        if has_parent {
+            self.update_cached(index);
         }
 
         // No parent (chain root)--just set data
         else {
+            let transform = get_mut_instance_raw!(self, index);
             transform.orientation = orientation;
+            transform.seed_last();
             transform.cached_transform = alg::Mat4::transform(
                 transform.position,
                 transform.orientation,
@@ -308,40 +614,31 @@ impl Manager {
         }
 
         // Update children transforms
-        unsafe { transform.update_children(self); }
+        self.update_children(index);
     }
 
-    /// Set transform scale
-    pub fn set_scale(
+    /// Set scale of the instance at `index` and update chain
+    pub(crate) fn set_scale_i(
         &mut self,
-        entity: entity::Handle,
+        index: usize,
         scale: alg::Vec3,
     ) {
-        debug_validate_entity!(self, entity);
-        let i = entity.get_index() as usize;
-        let transform = get_mut_instance_raw!(self, i);
-
-        // Check for non-uniform scale at assignment-time
-        #[cfg(debug_assertions)] {
-            if !transform.children.is_empty() && !scale.is_uniform() {
-                panic!(
-                    "Non-uniform scale is not supported \
-                    in transform hierarchy"
-                );
-            }
-        }
-
-        transform.local_scale = scale;
+        let has_parent = {
+            let transform = get_mut_instance_raw!(self, index);
+            transform.local_scale = scale;
+            transform.parent.is_some()
+        };
 
         /* Set worldspace transform data */
 
         // If this transform has a parent, update in chain
-        if transform.parent.is_some() {
-            transform.update_cached(self);
+        if has_parent {
+            self.update_cached(index);
         }
 
         // No parent (chain root)--just set data
         else {
+            let transform = get_mut_instance_raw!(self, index);
             transform.scale = scale;
             transform.cached_transform = alg::Mat4::transform(
                 transform.position,
@@ -351,59 +648,696 @@ impl Manager {
         }
 
         // Update children transforms
-        unsafe { transform.update_children(self); }
+        self.update_children(index);
     }
 
-    /* "Unsafe" methods for components with similar data layouts.
-     * These technically invalidate the ECS model but are used
-     * for performance purposes.
-     */
+    /// Record current worldspace state of all instances for interpolation
+    pub(crate) fn store_last(&mut self) {
+        for transform in self.instances.iter_mut().filter_map(|i| i.as_mut()) {
+            transform.last_position = transform.position;
+            transform.last_orientation = transform.orientation;
+            transform.stored = true;
+        }
+    }
 
-    /// Set transform data and update chain
-    pub(super) fn set_raw(
+    /// Write local transform data and hierarchy for all instances
+    pub(super) fn serialize(&self, writer: &mut scene::Writer) {
+        writer.usize(self.count);
+
+        for transform in self.instances.iter().filter_map(|i| i.as_ref()) {
+            writer.entity(transform.handle);
+            writer.vec3(transform.local_position);
+            writer.quat(transform.local_orientation);
+            writer.vec3(transform.local_scale);
+            writer.f32(transform.bounds_radius);
+
+            match transform.parent {
+                Some(index) => {
+                    let parent = self.instances[index].as_ref().unwrap();
+                    writer.bool(true);
+                    writer.entity(parent.handle);
+                },
+                None => writer.bool(false),
+            }
+        }
+    }
+
+    /// Register and initialize instances written by `serialize()`
+    pub(super) fn deserialize(
         &mut self,
-        index: usize,
-        position: alg::Vec3,
-        orientation: alg::Quat,
-        scale: alg::Vec3,
-    ) {
-        let transform = get_mut_instance_raw!(self, index);
+        reader: &mut scene::Reader,
+        handles: &scene::Handles,
+    ) -> Result<(), String> {
+        let mut parents = Vec::new();
+
+        for _ in 0..reader.usize()? {
+            let entity = handles.read(reader)?;
+            let position = reader.vec3()?;
+            let orientation = reader.quat()?;
+            let scale = reader.vec3()?;
+            let bounds_radius = reader.f32()?;
+
+            if reader.bool()? {
+                parents.push((entity, handles.read(reader)?));
+            }
 
-        // Check for non-uniform scale at assignment-time
-        #[cfg(debug_assertions)] {
-            if !transform.children.is_empty() && !scale.is_uniform() {
-                panic!(
-                    "Non-uniform scale is not supported \
-                    in transform hierarchy"
+            if self.registered(entity) {
+                return Err(format!("Duplicate transform for {}", entity));
+            }
+
+            if scale.x == 0.0 || scale.y == 0.0 || scale.z == 0.0 {
+                return Err(format!("Invalid transform scale {}", scale));
+            }
+
+            self.register(entity);
+            self.set(entity, position, orientation, scale);
+            self.set_bounds_radius(entity, bounds_radius);
+        }
+
+        // Link hierarchy once all instances exist
+        for (entity, parent) in parents {
+            let valid = self.registered(parent) && !self.is_ancestor(
+                entity.get_index() as usize,
+                parent.get_index() as usize,
+            );
+
+            if !valid {
+                return Err(
+                    format!("Invalid parent {} for entity {}", parent, entity)
                 );
             }
+
+            self.parent(entity, parent);
         }
 
-        transform.local_position = position;
-        transform.local_orientation = orientation;
-        transform.local_scale = scale;
+        Ok(())
+    }
 
-        /* Set worldspace transform data */
+    /// Check if the instance at `index` is `other` or one of its ancestors
+    fn is_ancestor(&self, index: usize, other: usize) -> bool {
+        let mut ancestor = Some(other);
 
-        // If this transform has a parent, update in chain
-        if transform.parent.is_some() {
-            transform.update_cached(self);
+        while let Some(current) = ancestor {
+            if current == index {
+                return true;
+            }
+
+            ancestor = self.instances[current].as_ref().unwrap().parent;
         }
 
-        // No parent (chain root)--just set data
-        else {
-            transform.position = position;
-            transform.orientation = orientation;
-            transform.scale = scale;
+        false
+    }
 
-            transform.cached_transform = alg::Mat4::transform(
-                position,
-                orientation,
-                scale,
+    /// Rebuild cached transform of the instance at `index`
+    /// with respect to its parent
+    fn update_cached(&mut self, index: usize) {
+        let parent_transform = {
+            let transform = self.instances[index].as_ref().unwrap();
+            debug_assert!(transform.parent.is_some());
+
+            self.instances[transform.parent.unwrap()].as_ref().unwrap()
+                .cached_transform
+        };
+
+        get_mut_instance_raw!(self, index).update_cached(parent_transform);
+    }
+
+    /// Call `update_cached()` on all descendants of the instance at `index`.
+    /// Iterative; parents are always updated before their children.
+    fn update_children(&mut self, index: usize) {
+        let mut stack = self.instances[index].as_ref().unwrap()
+            .children.clone();
+
+        while let Some(child_index) = stack.pop() {
+            self.update_cached(child_index);
+
+            stack.extend(
+                &self.instances[child_index].as_ref().unwrap().children
             );
         }
+    }
+}
 
-        // Update children transforms
-        unsafe { transform.update_children(self); }
+/// Depth-first (pre-order) iterator over a transform hierarchy
+pub struct Descendants<'a> {
+    manager: &'a Manager,
+    stack: Vec<usize>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = entity::Handle;
+
+    fn next(&mut self) -> Option<entity::Handle> {
+        let index = self.stack.pop()?;
+        let transform = self.manager.instances[index].as_ref().unwrap();
+
+        // Push in reverse so that siblings are visited in order
+        self.stack.extend(transform.children.iter().rev());
+
+        Some(transform.handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std;
+    use alg;
+    use entity;
+    use components::Component;
+    use components::transform::*;
+
+    #[test]
+    fn registered() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = Manager::new(2);
+
+        let first = entities.add();
+        let second = entities.add();
+
+        assert!(!transforms.registered(first));
+        assert!(!transforms.registered(second));
+
+        transforms.register(second);
+
+        // Index within bounds but not registered
+        assert!(!transforms.registered(first));
+        assert!(transforms.registered(second));
+
+        transforms.register(first);
+        assert!(transforms.registered(first));
+        assert!(transforms.count() == 2);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "already registered")]
+    fn register_twice() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = Manager::new(1);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        transforms.register(entity);
+    }
+
+    #[test]
+    fn set_indexed() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = Manager::new(2);
+
+        let parent = entities.add();
+        let child = entities.add();
+
+        transforms.register(parent);
+        transforms.register(child);
+        transforms.set_position(child, alg::Vec3::right());
+        transforms.parent(child, parent);
+
+        let i = parent.get_index() as usize;
+        let orientation = alg::Quat::axis_angle(
+            alg::Vec3::up(),
+            90f32.to_radians(),
+        );
+
+        transforms.set_position_i(i, alg::Vec3::up());
+        transforms.set_orientation_i(i, orientation);
+        transforms.set_scale_i(i, alg::Vec3::one() * 2.0);
+
+        let error = transforms.get_position(parent).dist(alg::Vec3::up())
+            + (transforms.get_scale(parent) - alg::Vec3::one() * 2.0).mag()
+            + transforms.get_position(child) // Chain is updated
+                .dist(alg::Vec3::new(0.0, 1.0, -2.0));
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn traversal() {
+        let mut entities = entity::Manager::new(5);
+        let mut transforms = Manager::new(5);
+
+        let handles: Vec<entity::Handle> = (0..5)
+            .map(|_| entities.add())
+            .collect();
+
+        for handle in &handles {
+            transforms.register(*handle);
+        }
+
+        let (root, a, b, c, d) = (
+            handles[0], handles[1], handles[2], handles[3], handles[4],
+        );
+
+        // root -> (a -> (c, d), b)
+        transforms.parent(a, root);
+        transforms.parent(b, root);
+        transforms.parent(c, a);
+        transforms.parent(d, a);
+
+        assert!(transforms.children(root) == vec![a, b]);
+        assert!(transforms.children(b).is_empty());
+        assert!(transforms.parent_of(c) == Some(a));
+        assert!(transforms.parent_of(root) == None);
+
+        let order: Vec<entity::Handle> = transforms.descendants(root)
+            .collect();
+
+        assert!(order == vec![a, c, d, b]);
+
+        // Reparenting moves the child out of its previous parent
+        transforms.parent(d, b);
+        assert!(transforms.children(a) == vec![c]);
+        assert!(transforms.children(b) == vec![d]);
+        assert!(transforms.parent_of(d) == Some(b));
+    }
+
+    #[test]
+    fn reparent() {
+        let mut entities = entity::Manager::new(4);
+        let mut transforms = Manager::new(4);
+
+        let handles: Vec<entity::Handle> = (0..4)
+            .map(|_| entities.add())
+            .collect();
+
+        for handle in &handles {
+            transforms.register(*handle);
+        }
+
+        let (root, a, b, c) = (handles[0], handles[1], handles[2], handles[3]);
+
+        // root -> (a -> c, b)
+        transforms.parent(a, root);
+        transforms.parent(b, root);
+        transforms.parent(c, a);
+
+        // root -> b -> a -> c
+        transforms.parent(a, b);
+        transforms.set_position(b, alg::Vec3::up());
+
+        let order: Vec<entity::Handle> = transforms.descendants(root)
+            .collect();
+
+        assert!(order == vec![b, a, c]);
+        assert!(transforms.children(root) == vec![b]);
+
+        // Moved subtree follows its new parent
+        let error = transforms.get_position(c).dist(alg::Vec3::up());
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn cycle() {
+        let mut entities = entity::Manager::new(3);
+        let mut transforms = Manager::new(3);
+
+        let a = entities.add();
+        let b = entities.add();
+        let c = entities.add();
+        transforms.register(a);
+        transforms.register(b);
+        transforms.register(c);
+
+        transforms.parent(b, a);
+        transforms.parent(c, b);
+
+        // Closing the loop must be rejected
+        let result = std::panic::catch_unwind(
+            std::panic::AssertUnwindSafe(|| transforms.parent(a, c))
+        );
+
+        assert!(result.is_err());
+        assert!(transforms.parent_of(a) == None);
+        assert!(transforms.parent_of(b) == Some(a));
+        assert!(transforms.parent_of(c) == Some(b));
+        assert!(transforms.children(c).is_empty());
+    }
+
+    #[test]
+    fn deep_chain() {
+        const DEPTH: usize = 10000;
+
+        let mut entities = entity::Manager::new(DEPTH);
+        let mut transforms = Manager::new(DEPTH);
+
+        let handles: Vec<entity::Handle> = (0..DEPTH)
+            .map(|_| entities.add())
+            .collect();
+
+        transforms.register(handles[0]);
+        for i in 1..DEPTH {
+            transforms.register(handles[i]);
+            transforms.set_position(handles[i], alg::Vec3::up());
+            transforms.parent(handles[i], handles[i - 1]);
+        }
+
+        // Propagate through the entire chain
+        transforms.set_position(handles[0], alg::Vec3::right());
+
+        let expected = alg::Vec3::new(1.0, (DEPTH - 1) as f32, 0.0);
+        let error = (transforms.get_position(handles[DEPTH - 1]) - expected)
+            .mag() / DEPTH as f32;
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn translate() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = Manager::new(2);
+
+        let parent = entities.add();
+        let child = entities.add();
+        transforms.register(parent);
+        transforms.register(child);
+        transforms.parent(child, parent);
+
+        for _ in 0..4 {
+            transforms.translate(parent, alg::Vec3::new(0.5, 0.0, -1.0));
+        }
+
+        let expected = alg::Vec3::new(2.0, 0.0, -4.0);
+        let error = (transforms.get_position(parent) - expected).mag()
+            + (transforms.get_position(child) - expected).mag();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn rotate() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = Manager::new(1);
+
+        let entity = entities.add();
+        transforms.register(entity);
+
+        let quarter = alg::Quat::axis_angle(
+            alg::Vec3::up(),
+            0.5 * std::f32::consts::PI,
+        );
+
+        transforms.set_orientation(entity, quarter);
+        transforms.rotate(entity, quarter);
+
+        let expected = alg::Quat::axis_angle(
+            alg::Vec3::up(),
+            std::f32::consts::PI,
+        );
+
+        let error = 1.0 - transforms.get_orientation(entity)
+            .dot(expected).abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn world_bounds() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = Manager::new(1);
+
+        let entity = entities.add();
+        transforms.register(entity);
+
+        transforms.set_position(entity, alg::Vec3::new(1.0, 2.0, 3.0));
+        transforms.set_scale(entity, alg::Vec3::new(1.0, 3.0, 2.0));
+        transforms.set_bounds_radius(entity, 2.0);
+
+        let (center, radius) = transforms.world_bounds(entity);
+        let error = (center - alg::Vec3::new(1.0, 2.0, 3.0)).mag()
+            + (radius - 6.0).abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn world_matrices() {
+        let mut entities = entity::Manager::new(3);
+        let mut transforms = Manager::new(3);
+
+        let handles: Vec<entity::Handle> = (0..3)
+            .map(|_| entities.add())
+            .collect();
+
+        for (i, handle) in handles.iter().enumerate() {
+            transforms.register(*handle);
+            transforms.set(
+                *handle,
+                alg::Vec3::new(i as f32, 1.0, -2.0),
+                alg::Quat::axis_angle(alg::Vec3::up(), i as f32),
+                alg::Vec3::one() * (i + 1) as f32,
+            );
+        }
+
+        let matrices: Vec<(entity::Handle, alg::Mat4)> = transforms
+            .world_matrices()
+            .map(|(handle, matrix)| (handle, *matrix))
+            .collect();
+
+        assert!(matrices.len() == handles.len());
+
+        let point = alg::Vec3::new(1.0, 2.0, 3.0);
+        for (&(handle, matrix), expected) in matrices.iter().zip(&handles) {
+            assert!(handle == *expected);
+
+            let error = (matrix * point - transforms.get_mat(handle) * point)
+                .mag();
+
+            eprintln!("Error: {}", error);
+            assert!(error < 0.0001);
+        }
+    }
+
+    #[test]
+    fn non_uniform_parent() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = Manager::new(2);
+
+        let parent = entities.add();
+        let child = entities.add();
+        transforms.register(parent);
+        transforms.register(child);
+
+        let parent_orientation = alg::Quat::axis_angle(
+            alg::Vec3::up(),
+            std::f32::consts::PI / 6.0,
+        );
+
+        let child_orientation = alg::Quat::axis_angle(
+            alg::Vec3::one(),
+            0.25 * std::f32::consts::PI,
+        );
+
+        transforms.set_scale(parent, alg::Vec3::new(1.0, 3.0, 0.5));
+        transforms.set_orientation(parent, parent_orientation);
+        transforms.set_orientation(child, child_orientation);
+
+        transforms.parent(child, parent);
+
+        let orientation = transforms.get_orientation(child);
+        let right = orientation * alg::Vec3::right();
+        let up = orientation * alg::Vec3::up();
+        let fwd = orientation * alg::Vec3::fwd();
+
+        let error = (right.mag() - 1.0).abs()
+            + (up.mag() - 1.0).abs()
+            + (fwd.mag() - 1.0).abs()
+            + right.dot(up).abs()
+            + up.dot(fwd).abs()
+            + fwd.dot(right).abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // The world basis is Rp * S * Rc, whose polar decomposition is
+        // (Rp * Rc) * (Rc^T * S * Rc)--the rotation ignores the scale
+        let expected = [
+            alg::Vec3::new( 0.54161,  0.50588, -0.67137),
+            alg::Vec3::new(-0.01606,  0.80474,  0.59341),
+            alg::Vec3::new( 0.84047, -0.31062,  0.44398),
+        ];
+
+        let error = right.dist(expected[0])
+            + up.dist(expected[1])
+            + fwd.dist(expected[2]);
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.001);
+    }
+
+    #[test]
+    fn mirrored_parent() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = Manager::new(2);
+
+        let parent = entities.add();
+        let child = entities.add();
+        transforms.register(parent);
+        transforms.register(child);
+
+        let angle = 0.25 * std::f32::consts::PI;
+
+        transforms.set_scale(parent, alg::Vec3::new(-1.0, 1.0, 1.0));
+        transforms.set_orientation(
+            child,
+            alg::Quat::axis_angle(alg::Vec3::up(), angle),
+        );
+
+        transforms.parent(child, parent);
+
+        // Reflection is carried by the scale
+        let scale = transforms.get_scale(child);
+        let error = scale.dist(alg::Vec3::new(-1.0, 1.0, 1.0));
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Mirroring across X reverses rotation about Y
+        let orientation = transforms.get_orientation(child);
+        assert!(
+            orientation.approx_eq(
+                alg::Quat::axis_angle(alg::Vec3::up(), -angle),
+                0.0001,
+            )
+        );
+
+        // Decomposition reproduces the cached matrix
+        let point = alg::Vec3::new(1.0, 2.0, 3.0);
+        let rebuilt = alg::Mat4::transform(
+            transforms.get_position(child),
+            orientation,
+            scale,
+        );
+
+        let error = (rebuilt * point - transforms.get_mat(child) * point)
+            .mag();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "zero component")]
+    fn zero_scale() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = Manager::new(1);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        transforms.set_scale(entity, alg::Vec3::new(1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn look_at() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = Manager::new(1);
+
+        let entity = entities.add();
+        transforms.register(entity);
+
+        transforms.look_at(entity, alg::Vec3::right(), alg::Vec3::up());
+
+        let fwd = transforms.get_orientation(entity) * alg::Vec3::fwd();
+        let error = (fwd - alg::Vec3::right()).mag();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn interpolated() {
+        let mut entities = entity::Manager::new(1);
+        let mut transforms = Manager::new(1);
+
+        let entity = entities.add();
+        transforms.register(entity);
+        transforms.store_last();
+
+        transforms.set_position(entity, alg::Vec3::right() * 2.0);
+        let (position, _) = transforms.get_interpolated(entity, 0.25);
+
+        let error = position.dist(alg::Vec3::right() * 0.5);
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Midpoint of two successive fixed steps
+        transforms.store_last();
+        transforms.set_position(entity, alg::Vec3::right() * 4.0);
+        transforms.set_orientation(
+            entity,
+            alg::Quat::axis_angle(alg::Vec3::up(), 2.0),
+        );
+
+        let (position, orientation) = transforms.get_interpolated(entity, 0.5);
+
+        let error = position.dist(alg::Vec3::right() * 3.0);
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        let error = (orientation * alg::Vec3::fwd()).dist(
+            alg::Quat::axis_angle(alg::Vec3::up(), 1.0) * alg::Vec3::fwd()
+        );
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Snapshot catches up to the current state
+        transforms.store_last();
+        let (position, _) = transforms.get_interpolated(entity, 0.25);
+
+        let error = position.dist(alg::Vec3::right() * 4.0);
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn interpolated_unstored() {
+        let mut entities = entity::Manager::new(2);
+        let mut transforms = Manager::new(2);
+
+        let parent = entities.add();
+        let child = entities.add();
+        transforms.register(parent);
+        transforms.register(child);
+        transforms.parent(child, parent);
+
+        // Placed before any fixed step; no motion from the origin
+        transforms.set_position(parent, alg::Vec3::right() * 2.0);
+        transforms.set_orientation(
+            parent,
+            alg::Quat::axis_angle(alg::Vec3::up(), 1.0),
+        );
+
+        transforms.set_position(child, alg::Vec3::fwd());
+
+        for &entity in &[parent, child] {
+            let (position, orientation) = transforms.get_interpolated(
+                entity,
+                0.0,
+            );
+
+            let error = position.dist(transforms.get_position(entity))
+                + (orientation * alg::Vec3::fwd()).dist(
+                    transforms.get_orientation(entity) * alg::Vec3::fwd()
+                );
+
+            eprintln!("Error: {}", error);
+            assert!(error < 0.0001);
+        }
+
+        // Later changes interpolate from the stored state
+        transforms.store_last();
+        transforms.set_position(parent, alg::Vec3::zero());
+        let (position, _) = transforms.get_interpolated(parent, 0.5);
+
+        let error = position.dist(alg::Vec3::right());
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
     }
 }
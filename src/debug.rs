@@ -4,9 +4,19 @@ use graphics;
 #[cfg(debug_assertions)]
 use render;
 
+// Brightness of lines at or beyond the far depth fade distance
+#[cfg(debug_assertions)]
+const DEPTH_FADE_MIN: f32 = 0.2;
+
 pub struct Handler {
     #[cfg(debug_assertions)]
     pub lines: Vec<render::DebugLine>,
+    #[cfg(debug_assertions)]
+    visible: Vec<render::DebugLine>, // Reused culling output
+    #[cfg(debug_assertions)]
+    culling: bool,
+    #[cfg(debug_assertions)]
+    depth_fade: Option<(f32, f32)>, // Near and far camera distance
 }
 
 impl Handler {
@@ -14,12 +24,81 @@ impl Handler {
         #[cfg(debug_assertions)] {
             Handler {
                 lines: Vec::new(),
+                visible: Vec::new(),
+                culling: false,
+                depth_fade: None,
             }
         }
 
         #[cfg(not(debug_assertions))] { Handler { } }
     }
 
+    /// Skip lines outside the camera frustum when uploading. \
+    /// Disabled by default, since culling costs CPU time per line.
+    #[allow(unused_variables)]
+    pub fn set_culling(&mut self, enabled: bool) {
+        #[cfg(debug_assertions)] {
+            self.culling = enabled;
+        }
+    }
+
+    /// Dim line endpoints with distance from the camera,
+    /// from full brightness at `near` to faint at `far`,
+    /// so that dense visualizations read in depth. \
+    /// Disabled (`None`) by default.
+    #[allow(unused_variables)]
+    pub fn set_depth_fade(&mut self, range: Option<(f32, f32)>) {
+        #[cfg(debug_assertions)] {
+            if let Some((near, far)) = range {
+                debug_assert!(near >= 0.0 && far > near);
+            }
+
+            self.depth_fade = range;
+        }
+    }
+
+    /// Returns the lines to render for a camera at `viewer`,
+    /// culled against its view-projection matrix if culling is enabled
+    /// and faded by distance if depth fade is enabled. \
+    /// Lines are culled only when both endpoints lie outside
+    /// the same frustum plane, so crossing lines are kept.
+    #[cfg(debug_assertions)]
+    pub fn visible_lines(
+        &mut self,
+        view_projection: alg::Mat4,
+        viewer: alg::Vec3,
+    ) -> &[render::DebugLine] {
+        if !self.culling && self.depth_fade.is_none() {
+            return &self.lines;
+        }
+
+        let frustum = view_projection.frustum_planes();
+        let planes: &[alg::Plane] = if self.culling { &frustum } else { &[] };
+
+        self.visible.clear();
+        self.visible.extend(
+            self.lines.iter().filter(|line| {
+                !planes.iter().any(|plane| {
+                    plane.dist(line.start.position) < 0.0
+                        && plane.dist(line.end.position) < 0.0
+                })
+            })
+        );
+
+        if let Some(range) = self.depth_fade {
+            for line in &mut self.visible {
+                for vertex in &mut [&mut line.start, &mut line.end] {
+                    vertex.color = vertex.color * depth_fade(
+                        vertex.position.dist(viewer),
+                        range,
+                    );
+                }
+            }
+        }
+
+        &self.visible
+    }
+
     #[allow(unused_variables)]
     pub fn add_line(
         &mut self,
@@ -132,6 +211,36 @@ impl Handler {
         }
     }
 
+    /// Draw oriented basis vectors at `position`,
+    /// each `scale` units long (X red, Y green, Z blue)
+    #[allow(unused_variables)]
+    pub fn add_transform(
+        &mut self,
+        position: alg::Vec3,
+        orientation: alg::Quat,
+        scale: f32,
+    ) {
+        #[cfg(debug_assertions)] {
+            let axes = [
+                (alg::Vec3::right(), graphics::Color::red()),
+                (alg::Vec3::up(),    graphics::Color::green()),
+                (alg::Vec3::fwd(),   graphics::Color::blue()),
+            ];
+
+            for &(axis, color) in &axes {
+                self.lines.push(
+                    render::DebugLine::new(
+                        alg::Line::new(
+                            position,
+                            position + orientation * axis * scale,
+                        ),
+                        color,
+                    )
+                );
+            }
+        }
+    }
+
     #[allow(unused_variables)]
     pub fn add_cross(
         &mut self,
@@ -157,6 +266,7 @@ impl Handler {
         }
     }
 
+    /// Remove all lines, keeping allocated storage for the next frame
     pub fn clear_lines(&mut self) {
         #[cfg(debug_assertions)] {
             self.lines.clear();
@@ -164,3 +274,149 @@ impl Handler {
     }
 }
 
+// Brightness scale for a point `distance` away from the camera
+#[cfg(debug_assertions)]
+fn depth_fade(distance: f32, (near, far): (f32, f32)) -> f32 {
+    let t = ((distance - near) / (far - near)).max(0.0).min(1.0);
+    1.0 - t * (1.0 - DEPTH_FADE_MIN)
+}
+
+// Debug line storage is compiled out without debug assertions
+#[cfg(all(test, debug_assertions))]
+mod tests {
+    use alg;
+    use render;
+    use graphics;
+    use debug::*;
+
+    #[test]
+    fn add_transform() {
+        let mut debug = Handler::new();
+
+        let position = alg::Vec3::new(1.0, 2.0, 3.0);
+        let orientation = alg::Quat::axis_angle(
+            alg::Vec3::up(),
+            90f32.to_radians(),
+        );
+
+        debug.add_transform(position, orientation, 2.0);
+
+        let expected = [
+            (-alg::Vec3::fwd(),   graphics::Color::red()),
+            ( alg::Vec3::up(),    graphics::Color::green()),
+            ( alg::Vec3::right(), graphics::Color::blue()),
+        ];
+
+        assert!(debug.lines.len() == 3);
+
+        for (line, &(direction, color)) in debug.lines.iter().zip(&expected) {
+            let error = line.start.position.dist(position)
+                + line.end.position.dist(position + direction * 2.0);
+
+            eprintln!("Error: {}", error);
+            assert!(error < 0.0001);
+            assert!(line.start.color == color && line.end.color == color);
+        }
+    }
+
+    #[test]
+    fn visible_lines() {
+        let mut debug = Handler::new();
+        let view_projection = alg::Mat4::perspective(90.0, 1.0, 0.1, 10.0);
+
+        let visible = alg::Line::new(
+            alg::Vec3::new(0.0, 0.0, 5.0),
+            alg::Vec3::new(1.0, 1.0, 5.0),
+        );
+
+        // Endpoints outside the frustum on opposite sides
+        let crossing = alg::Line::new(
+            alg::Vec3::new(-9.0, 0.0, 5.0),
+            alg::Vec3::new( 9.0, 0.0, 5.0),
+        );
+
+        let behind = alg::Line::new(
+            alg::Vec3::new(0.0, 0.0, -5.0),
+            alg::Vec3::new(1.0, 1.0, -5.0),
+        );
+
+        let right = alg::Line::new(
+            alg::Vec3::new(8.0, 0.0, 5.0),
+            alg::Vec3::new(9.0, 0.0, 2.0),
+        );
+
+        debug.add_line(visible, graphics::Color::red());
+        debug.add_line(behind, graphics::Color::red());
+        debug.add_line(crossing, graphics::Color::green());
+        debug.add_line(right, graphics::Color::red());
+
+        assert!(
+            debug.visible_lines(view_projection, alg::Vec3::zero()).len() == 4
+        );
+
+        debug.set_culling(true);
+
+        let expected = [
+            render::DebugLine::new(visible, graphics::Color::red()),
+            render::DebugLine::new(crossing, graphics::Color::green()),
+        ];
+
+        assert!(
+            debug.visible_lines(view_projection, alg::Vec3::zero())
+                == &expected[..]
+        );
+        assert!(debug.lines.len() == 4); // Source lines are untouched
+    }
+
+    #[test]
+    fn depth_fade() {
+        let mut debug = Handler::new();
+        let viewer = alg::Vec3::new(0.0, 0.0, -2.0);
+
+        let line = |z: f32| alg::Line::new(
+            alg::Vec3::new(0.0, 0.0, z),
+            alg::Vec3::new(1.0, 0.0, z),
+        );
+
+        debug.add_line(line(-1.0), graphics::Color::white()); // Near
+        debug.add_line(line(20.0), graphics::Color::white()); // Far
+
+        debug.set_depth_fade(Some((2.0, 10.0)));
+
+        let lines = debug.visible_lines(alg::Mat4::id(), viewer);
+        assert!(lines.len() == 2); // Culling stays off
+
+        let (near, far) = (lines[0].start.color, lines[1].start.color);
+        eprintln!("Near: {}, far: {}", near.r, far.r);
+
+        assert!(near == graphics::Color::white());
+        assert!(far.r < near.r && far.r > 0.0);
+
+        // Source colors are untouched
+        assert!(debug.lines[1].start.color == graphics::Color::white());
+    }
+
+    #[test]
+    fn clear_lines() {
+        let mut debug = Handler::new();
+
+        for _ in 0..4096 {
+            debug.add_cross(alg::Vec3::zero(), 1.0, graphics::Color::red());
+        }
+
+        let capacity = debug.lines.capacity();
+        assert!(capacity >= 8192);
+
+        for _ in 0..3 {
+            debug.clear_lines();
+            assert!(debug.lines.is_empty());
+
+            for _ in 0..1024 {
+                debug.add_cross(alg::Vec3::zero(), 1.0, graphics::Color::red());
+            }
+
+            // No reallocation below the high-water mark
+            assert!(debug.lines.capacity() == capacity);
+        }
+    }
+}
@@ -28,6 +28,8 @@ pub struct Manager {
     data: fnv::FnvHashSet<Handle>,
     index: u32,
     count: u32,
+    tags: fnv::FnvHashMap<Handle, String>,
+    tagged: fnv::FnvHashMap<String, Vec<Handle>>, // In tagging order
 }
 
 impl Manager {
@@ -37,7 +39,13 @@ impl Manager {
             Default::default(),
         );
 
-        Manager { data, index: 0, count: 0 }
+        Manager {
+            data,
+            index: 0,
+            count: 0,
+            tags: fnv::FnvHashMap::default(),
+            tagged: fnv::FnvHashMap::default(),
+        }
     }
 
     pub fn add(&mut self) -> Handle {
@@ -75,10 +83,91 @@ impl Manager {
         if self.data.remove(&handle) {
             // Decrement counter
             self.count -= 1;
+            self.clear_tag(handle);
         }
     }
 
     pub fn count(&self) -> usize {
         self.data.len()
     }
+
+    /// Name an entity for later lookup, replacing any previous tag. \
+    /// Several entities may share a tag.
+    pub fn set_tag(&mut self, handle: Handle, tag: &str) {
+        #[cfg(debug_assertions)] {
+            if !self.check(handle) {
+                panic!("Cannot tag entity {}, which does not exist", handle);
+            }
+        }
+
+        self.clear_tag(handle);
+
+        self.tags.insert(handle, tag.to_string());
+        self.tagged.entry(tag.to_string())
+            .or_insert_with(Vec::new)
+            .push(handle);
+    }
+
+    pub fn get_tag(&self, handle: Handle) -> Option<&str> {
+        self.tags.get(&handle).map(|tag| tag.as_str())
+    }
+
+    pub fn clear_tag(&mut self, handle: Handle) {
+        if let Some(tag) = self.tags.remove(&handle) {
+            let empty = {
+                let handles = self.tagged.get_mut(&tag).unwrap();
+                handles.retain(|&other| other != handle);
+                handles.is_empty()
+            };
+
+            if empty {
+                self.tagged.remove(&tag);
+            }
+        }
+    }
+
+    /// Returns the first entity given `tag`
+    pub fn find_by_tag(&self, tag: &str) -> Option<Handle> {
+        self.find_all_by_tag(tag).first().cloned()
+    }
+
+    /// Returns every entity given `tag`, in tagging order
+    pub fn find_all_by_tag(&self, tag: &str) -> &[Handle] {
+        self.tagged.get(tag).map(|handles| handles.as_slice())
+            .unwrap_or(&[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entity::*;
+
+    #[test]
+    fn tags() {
+        let mut entities = Manager::new(3);
+
+        let player = entities.add();
+        let enemy = entities.add();
+        let other = entities.add();
+
+        entities.set_tag(player, "player");
+        entities.set_tag(enemy, "enemy");
+        entities.set_tag(other, "enemy");
+
+        assert!(entities.find_by_tag("player") == Some(player));
+        assert!(entities.find_by_tag("enemy") == Some(enemy));
+        assert!(entities.find_all_by_tag("enemy") == &[enemy, other]);
+        assert!(entities.find_by_tag("camera") == None);
+        assert!(entities.find_all_by_tag("camera").is_empty());
+
+        // Retagging moves the entity
+        entities.set_tag(enemy, "boss");
+        assert!(entities.get_tag(enemy) == Some("boss"));
+        assert!(entities.find_all_by_tag("enemy") == &[other]);
+
+        // Removed entities are no longer found
+        entities.remove(player);
+        assert!(entities.find_by_tag("player") == None);
+        assert!(entities.get_tag(player) == None);
+    }
 }
@@ -0,0 +1,626 @@
+use std;
+use alg;
+use render;
+use entity;
+use components;
+use input;
+use debug;
+
+use {Start, Update, FixedUpdate, Metadata, ScreenData, FIXED_DT};
+use {Accumulator, Resize};
+
+/// Default screen dimensions reported to the game
+const SCREEN_WIDTH: u32 = 1280;
+const SCREEN_HEIGHT: u32 = 720;
+
+/// Engine loop without a window or Vulkan context,
+/// for testing game logic and running servers
+pub struct Headless<T> {
+    pub game: T,
+    pub metadata: Metadata,
+    pub screen: ScreenData,
+    pub parameters: render::Parameters,
+    pub entities: entity::Manager,
+    pub components: components::Container,
+    pub input: input::Manager,
+    pub debug: debug::Handler,
+    time: f64,
+    accumulator: Accumulator,
+    resize: Resize,
+}
+
+impl<T> Headless<T>
+where
+    T: Start + Update + FixedUpdate
+        + components::softbody::Iterate
+{
+    /// Initialize engine state and start game
+    pub fn new(game: T) -> Headless<T> {
+        Headless::with_axes(game, alg::Axes::default())
+    }
+
+    /// Initialize engine state for a world coordinate convention
+    /// and start game
+    pub fn with_axes(mut game: T, axes: alg::Axes) -> Headless<T> {
+        let instances = render::Instances::new(0, &Vec::new(), None);
+
+        let mut entities = entity::Manager::new(1);
+        let mut components = ::init_components(instances, axes);
+
+        game.start(&mut entities, &mut components);
+
+        Headless {
+            game,
+            metadata: Metadata::new(),
+            screen: ScreenData {
+                width: SCREEN_WIDTH,
+                height: SCREEN_HEIGHT,
+            },
+            parameters: render::Parameters::new(),
+            entities,
+            components,
+            input: input::Manager::new(),
+            debug: debug::Handler::new(),
+            time: 0.0,
+            accumulator: Accumulator::new(FIXED_DT as f64),
+            resize: Resize::new(),
+        }
+    }
+
+    /// Change screen dimensions reported from the next frame onward,
+    /// as a window resize event would. \
+    /// Only the latest size before a frame applies; empty sizes are ignored.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        self.resize.record(width as f64, height as f64, 1.0);
+    }
+
+    /// Advance `frames` frames, each exactly one fixed step long
+    pub fn step(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.advance(FIXED_DT as f64);
+        }
+    }
+
+    /// Advance a single frame lasting `delta` seconds, running `Update`,
+    /// any fixed steps that fall due, and component updates
+    pub fn advance(&mut self, delta: f64) {
+        let frame_start = std::time::Instant::now();
+        self.input.increment_key_states();
+        self.input.reset_deltas();
+
+        if let Some((width, height)) = self.resize.take() {
+            self.screen = ScreenData {
+                width,
+                height,
+            };
+        }
+
+        self.input.set_screen_size(self.screen.width, self.screen.height);
+
+        self.time += delta;
+        self.accumulator.add(delta);
+
+        self.game.update(
+            self.time,
+            delta,
+            self.metadata,
+            self.screen,
+            &mut self.parameters,
+            &mut self.entities,
+            &mut self.components,
+            &self.input,
+            &mut self.debug,
+        );
+
+        self.metadata.physics_time = 0.0;
+
+        while self.accumulator.consume_unless_paused(&mut self.parameters) {
+            ::fixed_step_update(
+                &mut self.game,
+                self.time,
+                &mut self.metadata,
+                self.screen,
+                &mut self.parameters,
+                &mut self.entities,
+                &mut self.components,
+                &self.input,
+                &mut self.debug,
+            );
+        }
+
+        // Remainder once every due step has run
+        self.metadata.alpha = self.accumulator.alpha();
+
+        ::update_components(
+            &mut self.components,
+            delta as f32,
+            self.metadata.alpha,
+        );
+
+        // No renderer; only culling applies
+        self.metadata.render_stats.lights_culled =
+            self.components.draws.lights_culled() as u32;
+
+        self.metadata.frame_time = ::seconds(frame_start.elapsed());
+        self.metadata.frame += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use render;
+    use components;
+    use components::Component;
+    use entity;
+    use input;
+    use debug;
+    use headless::*;
+
+    struct Demo {
+        body: Option<entity::Handle>,
+    }
+
+    default_traits!(Demo, [
+        Update,
+        FixedUpdate,
+        components::softbody::Iterate,
+    ]);
+
+    impl Start for Demo {
+        fn start(
+            &mut self,
+            entities:   &mut entity::Manager,
+            components: &mut components::Container,
+        ) {
+            let body = entities.add();
+            components.transforms.register(body);
+            components.softbodies.register(body);
+
+            components.softbodies.build_instance()
+                .make_box_limb(alg::Vec3::one())
+                .initial_pos(alg::Vec3::up())
+                .for_entity(body);
+
+            components.softbodies.add_plane(
+                alg::Plane::new(alg::Vec3::up(), 0.0)
+            );
+
+            self.body = Some(body);
+        }
+    }
+
+    #[test]
+    fn softbody_settles() {
+        let mut headless = Headless::new(Demo { body: None });
+        headless.step(100);
+
+        assert!(headless.metadata.frame == 100);
+        assert!(headless.metadata.fixed_frame == 100);
+
+        let body = headless.game.body.unwrap();
+        let instance = headless.components.softbodies.get_instance(body);
+
+        let speed = instance.velocity().mag();
+        let (min, _) = instance.bounds();
+
+        eprintln!("Speed: {}", speed);
+        eprintln!("Lowest point: {}", min.y);
+
+        assert!(speed < 0.01);
+        assert!(min.y > -0.01);
+    }
+
+    #[test]
+    fn timings() {
+        let mut headless = Headless::new(Demo { body: None });
+        headless.step(1);
+
+        let metadata = headless.metadata;
+
+        assert!(metadata.physics_time > 0.0);
+        assert!(metadata.frame_time >= metadata.physics_time);
+        assert!(metadata.render_time == 0.0); // No renderer
+        assert!(headless.parameters.interop.is_none());
+    }
+
+    #[test]
+    fn pause() {
+        let mut headless = Headless::new(Demo { body: None });
+        headless.step(3);
+
+        headless.parameters.pause();
+        headless.step(5);
+        assert!(headless.metadata.fixed_frame == 3);
+        assert!(headless.metadata.frame == 8); // Frames still run
+
+        let body = headless.game.body.unwrap();
+        let frozen = headless.components.transforms.get_position(body);
+
+        headless.parameters.step_once();
+        headless.step(2);
+        assert!(headless.metadata.fixed_frame == 4);
+
+        let position = headless.components.transforms.get_position(body);
+        assert!(position != frozen);
+
+        // Time spent paused is not caught up on
+        headless.parameters.resume();
+        headless.step(1);
+        assert!(headless.metadata.fixed_frame == 5);
+    }
+
+    struct Recorder {
+        alphas: Vec<(f32, u32)>, // Alpha and fixed frame during update
+    }
+
+    default_traits!(Recorder, [
+        Start,
+        FixedUpdate,
+        components::softbody::Iterate,
+    ]);
+
+    impl Update for Recorder {
+        #[allow(unused_variables)]
+        fn update(
+            &mut self,
+            time:  f64,
+            delta: f64,
+            metadata: Metadata,
+            screen: ScreenData,
+            parameters: &mut render::Parameters,
+            entities: &mut entity::Manager,
+            components: &mut components::Container,
+            input: &input::Manager,
+            debug: &mut debug::Handler,
+        ) {
+            self.alphas.push((metadata.alpha, metadata.fixed_frame));
+        }
+    }
+
+    #[test]
+    fn alpha() {
+        let mut headless = Headless::new(Recorder { alphas: Vec::new() });
+
+        let mut frames = Vec::new();
+
+        // Four frames per fixed step
+        for _ in 0..8 {
+            headless.advance(FIXED_DT as f64 * 0.25);
+            let metadata = headless.metadata;
+            frames.push((metadata.alpha, metadata.fixed_frame));
+        }
+
+        // Alpha follows the fixed steps run in the same frame
+        let expected = [
+            (0.25, 0), (0.5, 0), (0.75, 0), (0.0, 1),
+            (0.25, 1), (0.5, 1), (0.75, 1), (0.0, 2),
+        ];
+
+        for (actual, expected) in frames.iter().zip(&expected) {
+            let error = (actual.0 - expected.0).abs();
+
+            eprintln!("Error: {}", error);
+            assert!(error < 0.0001);
+            assert!(actual.1 == expected.1);
+        }
+
+        // Update sees the alpha left by the previous frame
+        let alphas = &headless.game.alphas;
+        assert!(alphas[0].0 == 0.0);
+
+        for (update, frame) in alphas[1..].iter().zip(&frames) {
+            assert!(update.0 == frame.0);
+        }
+
+        assert!(headless.metadata.fixed_frame == 2);
+    }
+
+    struct Schedule {
+        body: Option<entity::Handle>,
+        calls: Vec<&'static str>, // Callbacks in the order they ran
+        before: alg::Vec3, // Body position entering the physics step
+        moved: bool, // Physics results visible in after_physics()
+    }
+
+    impl Schedule {
+        fn record(&mut self, call: &'static str) {
+            // Iterate runs once per solver iteration
+            if self.calls.last() != Some(&call) {
+                self.calls.push(call);
+            }
+        }
+    }
+
+    impl Start for Schedule {
+        fn start(
+            &mut self,
+            entities:   &mut entity::Manager,
+            components: &mut components::Container,
+        ) {
+            let body = entities.add();
+            components.transforms.register(body);
+            components.softbodies.register(body);
+
+            components.softbodies.build_instance()
+                .make_box_limb(alg::Vec3::one())
+                .for_entity(body);
+
+            self.body = Some(body);
+        }
+    }
+
+    impl Update for Schedule {
+        #[allow(unused_variables)]
+        fn update(
+            &mut self,
+            time:  f64,
+            delta: f64,
+            metadata: Metadata,
+            screen: ScreenData,
+            parameters: &mut render::Parameters,
+            entities: &mut entity::Manager,
+            components: &mut components::Container,
+            input: &input::Manager,
+            debug: &mut debug::Handler,
+        ) {
+            self.record("update");
+        }
+    }
+
+    impl FixedUpdate for Schedule {
+        #[allow(unused_variables)]
+        fn fixed_update(
+            &mut self,
+            time: f64,
+            fixed_delta: f32,
+            metadata: Metadata,
+            screen: ScreenData,
+            parameters: &mut render::Parameters,
+            entities: &mut entity::Manager,
+            components: &mut components::Container,
+            input: &input::Manager,
+            debug: &mut debug::Handler,
+        ) {
+            self.record("fixed_update");
+        }
+
+        #[allow(unused_variables)]
+        fn before_physics(
+            &mut self,
+            fixed_delta: f32,
+            entities: &mut entity::Manager,
+            components: &mut components::Container,
+        ) {
+            self.record("before_physics");
+            self.before = components.transforms
+                .get_position(self.body.unwrap());
+        }
+
+        #[allow(unused_variables)]
+        fn after_physics(
+            &mut self,
+            fixed_delta: f32,
+            entities: &mut entity::Manager,
+            components: &mut components::Container,
+        ) {
+            self.record("after_physics");
+            self.moved = components.transforms
+                .get_position(self.body.unwrap()) != self.before;
+        }
+    }
+
+    impl components::softbody::Iterate for Schedule {
+        #[allow(unused_variables)]
+        fn iterate(
+            &mut self,
+            fixed_delta: f32,
+            iterations: usize,
+            softbodies: &mut components::softbody::Manager,
+        ) {
+            self.record("iterate");
+        }
+    }
+
+    #[test]
+    fn schedule() {
+        let mut headless = Headless::new(
+            Schedule {
+                body: None,
+                calls: Vec::new(),
+                before: alg::Vec3::zero(),
+                moved: false,
+            }
+        );
+
+        headless.step(2);
+
+        let expected = [
+            "update",
+            "fixed_update", "before_physics", "iterate", "after_physics",
+        ];
+
+        let calls = &headless.game.calls;
+        eprintln!("Calls: {:?}", calls);
+
+        assert!(calls.len() == expected.len() * 2);
+        assert!(calls[..5] == expected && calls[5..] == expected);
+        assert!(headless.game.moved);
+    }
+
+    struct View {
+        aspect: f32, // Projection aspect ratio computed during update
+    }
+
+    default_traits!(View, [
+        FixedUpdate,
+        components::softbody::Iterate,
+    ]);
+
+    impl Start for View {
+        fn start(
+            &mut self,
+            entities:   &mut entity::Manager,
+            components: &mut components::Container,
+        ) {
+            let camera = entities.add();
+            components.transforms.register(camera);
+            components.cameras.register(camera);
+        }
+    }
+
+    impl Update for View {
+        #[allow(unused_variables)]
+        fn update(
+            &mut self,
+            time:  f64,
+            delta: f64,
+            metadata: Metadata,
+            screen: ScreenData,
+            parameters: &mut render::Parameters,
+            entities: &mut entity::Manager,
+            components: &mut components::Container,
+            input: &input::Manager,
+            debug: &mut debug::Handler,
+        ) {
+            let projection = components.cameras.compute(
+                &components.transforms,
+                screen,
+            )[0].0.projection();
+
+            self.aspect = (projection.y1 / projection.x0).abs();
+        }
+    }
+
+    #[test]
+    fn resize() {
+        let mut headless = Headless::new(View { aspect: 0.0 });
+
+        headless.step(1);
+        let error = (headless.game.aspect - 1280.0 / 720.0).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // New dimensions apply on the very next frame
+        headless.resize(1000, 500);
+        headless.step(1);
+
+        let error = (headless.game.aspect - 2.0).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Only the latest size applies; minimizing keeps it
+        headless.resize(640, 640);
+        headless.resize(900, 300);
+        headless.resize(0, 0);
+        headless.step(1);
+
+        assert!(headless.screen.width == 900);
+        assert!(headless.screen.height == 300);
+
+        let error = (headless.game.aspect - 3.0).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn resize_physical() {
+        let mut resize = ::Resize::new();
+        assert!(resize.take() == None);
+
+        // Logical window sizes are scaled to physical pixels
+        resize.record(640.0, 360.0, 1.5);
+        assert!(resize.take() == Some((960, 540)));
+        assert!(resize.take() == None);
+
+        resize.record(0.2, 100.0, 2.0);
+        assert!(resize.take() == None);
+    }
+
+    struct Blender {
+        body: Option<entity::Handle>,
+        screen: Vec<alg::Vec3>, // Projected test points during update
+    }
+
+    default_traits!(Blender, [
+        FixedUpdate,
+        components::softbody::Iterate,
+    ]);
+
+    impl Start for Blender {
+        fn start(
+            &mut self,
+            entities:   &mut entity::Manager,
+            components: &mut components::Container,
+        ) {
+            let up = components.cameras.axes().up();
+
+            let camera = entities.add();
+            components.transforms.register(camera);
+            components.cameras.register(camera);
+            components.transforms.set_position(camera, alg::Vec3::up() * -5.0);
+            components.transforms.look_at(camera, alg::Vec3::zero(), up);
+
+            let body = entities.add();
+            components.transforms.register(body);
+            components.softbodies.register(body);
+            components.softbodies.build_instance()
+                .make_box_limb(alg::Vec3::one())
+                .for_entity(body);
+
+            self.body = Some(body);
+        }
+    }
+
+    impl Update for Blender {
+        #[allow(unused_variables)]
+        fn update(
+            &mut self,
+            time:  f64,
+            delta: f64,
+            metadata: Metadata,
+            screen: ScreenData,
+            parameters: &mut render::Parameters,
+            entities: &mut entity::Manager,
+            components: &mut components::Container,
+            input: &input::Manager,
+            debug: &mut debug::Handler,
+        ) {
+            let ubo = components.cameras.compute(
+                &components.transforms,
+                screen,
+            )[0].0;
+
+            let view_projection = ubo.projection() * ubo.view();
+
+            self.screen = vec![
+                view_projection * alg::Vec3::right(),
+                view_projection * alg::Vec3::fwd(), // World up
+            ];
+        }
+    }
+
+    #[test]
+    fn z_up() {
+        let axes = alg::Axes::new(alg::UpAxis::Z, alg::Handedness::Right);
+        let mut headless = Headless::with_axes(
+            Blender { body: None, screen: Vec::new() },
+            axes,
+        );
+
+        headless.step(10);
+
+        let body = headless.game.body.unwrap();
+        let position = headless.components.transforms.get_position(body);
+
+        eprintln!("Position: {}", position);
+        assert!(position.z < -0.01);
+        assert!(position.x.abs() < 0.0001 && position.y.abs() < 0.0001);
+
+        // World right is screen right, world up is screen up
+        // (Vulkan clip space points down)
+        let screen = &headless.game.screen;
+        assert!(screen[0].x > 0.0 && screen[0].y.abs() < 0.0001);
+        assert!(screen[1].y < 0.0 && screen[1].x.abs() < 0.0001);
+    }
+}
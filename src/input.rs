@@ -1,13 +1,26 @@
 extern crate voodoo_winit as vdw;
 
+use std::cell::Cell;
+
 use alg;
 
 pub const KEY_COUNT: usize = 22;
 
+// Pixel-based scrolling (e.g. touchpads) is converted to lines
+const SCROLL_PIXELS_PER_LINE: f32 = 20.0;
+
 pub struct Manager {
     key_map: [KeyState; KEY_COUNT],
-    pub cursor_coords: alg::Vec2,
+    pub cursor_coords: alg::Vec2, // Physical pixels, origin top-left
     pub mouse_delta: alg::Vec2,
+    scroll: alg::Vec2, // In lines, accumulated this frame
+    text: String, // Characters received this frame
+    screen: alg::Vec2, // Window dimensions in physical pixels
+    cursor_grabbed: bool, // Applied by the window
+    focused: bool,
+    grab_request: Cell<bool>,
+    visible_request: Cell<bool>,
+    cursor_dirty: Cell<bool>, // Requests not yet applied to the window
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -87,7 +100,155 @@ impl Manager {
             key_map: [KeyState::default(); KEY_COUNT],
             cursor_coords: alg::Vec2::zero(),
             mouse_delta: alg::Vec2::zero(),
+            scroll: alg::Vec2::zero(),
+            text: String::new(),
+            screen: alg::Vec2::zero(),
+            cursor_grabbed: false,
+            focused: false,
+            grab_request: Cell::new(true),
+            visible_request: Cell::new(true),
+            cursor_dirty: Cell::new(false),
+        }
+    }
+
+    /// Clear per-frame mouse movement, scrolling, and text input
+    pub(crate) fn reset_deltas(&mut self) {
+        self.mouse_delta = alg::Vec2::zero();
+        self.scroll = alg::Vec2::zero();
+        self.text.clear();
+    }
+
+    /* Text */
+
+    /// Record a character from the OS (including key repeats);
+    /// control characters such as backspace are left to the key API
+    pub(crate) fn add_character(&mut self, character: char) {
+        if !character.is_control() {
+            self.text.push(character);
+        }
+    }
+
+    /// Returns text typed this frame
+    pub fn text_input(&self) -> &str {
+        &self.text
+    }
+
+    /* Scrolling */
+
+    pub(crate) fn add_scroll_lines(&mut self, x: f32, y: f32) {
+        self.scroll = self.scroll + alg::Vec2::new(x, y);
+    }
+
+    pub(crate) fn add_scroll_pixels(&mut self, x: f32, y: f32) {
+        self.add_scroll_lines(
+            x / SCROLL_PIXELS_PER_LINE,
+            y / SCROLL_PIXELS_PER_LINE,
+        );
+    }
+
+    /// Returns scrolling this frame in lines,
+    /// x for horizontal and y for vertical (positive is up)
+    pub fn scroll_delta(&self) -> alg::Vec2 {
+        self.scroll
+    }
+
+    /* Cursor */
+
+    /// Record cursor position given in logical pixels
+    pub(crate) fn set_cursor_position(
+        &mut self,
+        x: f64,
+        y: f64,
+        hidpi_factor: f64,
+    ) {
+        self.cursor_coords = alg::Vec2::new(
+            (x * hidpi_factor) as f32,
+            (y * hidpi_factor) as f32,
+        );
+    }
+
+    pub(crate) fn set_screen_size(&mut self, width: u32, height: u32) {
+        self.screen = alg::Vec2::new(width as f32, height as f32);
+    }
+
+    pub(crate) fn add_mouse_delta(&mut self, x: f32, y: f32) {
+        self.mouse_delta = self.mouse_delta + alg::Vec2::new(x, y);
+    }
+
+    /// Request the cursor be locked to the window (default) or released,
+    /// applied from the next frame while the window is focused. \
+    /// `mouse_delta` keeps reporting relative movement while grabbed.
+    pub fn set_cursor_grabbed(&self, grabbed: bool) {
+        self.grab_request.set(grabbed);
+        self.cursor_dirty.set(true);
+    }
+
+    /// Request the OS cursor be shown (default) or hidden,
+    /// applied from the next frame while the window is focused
+    pub fn set_cursor_visible(&self, visible: bool) {
+        self.visible_request.set(visible);
+        self.cursor_dirty.set(true);
+    }
+
+    /// Check if the cursor is currently grabbed by the window
+    pub fn cursor_grabbed(&self) -> bool {
+        self.cursor_grabbed
+    }
+
+    /// Record window focus; the grab is released on focus loss
+    /// and requested again on focus gain
+    pub(crate) fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+
+        if focused {
+            self.cursor_dirty.set(true);
+        } else {
+            self.cursor_grabbed = false;
+        }
+    }
+
+    /// Returns requested (grabbed, visible) state if it needs applying
+    pub(crate) fn take_cursor_request(&mut self) -> Option<(bool, bool)> {
+        if !self.focused || !self.cursor_dirty.get() {
+            return None;
+        }
+
+        self.cursor_dirty.set(false);
+        Some((self.grab_request.get(), self.visible_request.get()))
+    }
+
+    /// Record grab state once applied by the window
+    pub(crate) fn confirm_cursor_grabbed(&mut self, grabbed: bool) {
+        self.cursor_grabbed = grabbed;
+    }
+
+    /// Returns cursor position in physical pixels, origin top-left. \
+    /// Clamped to the window while the cursor is grabbed.
+    pub fn mouse_position(&self) -> alg::Vec2 {
+        if !self.cursor_grabbed {
+            return self.cursor_coords;
         }
+
+        alg::Vec2::new(
+            self.cursor_coords.x.max(0.0).min(self.screen.x),
+            self.cursor_coords.y.max(0.0).min(self.screen.y),
+        )
+    }
+
+    /// Returns cursor position in normalized device coordinates,
+    /// from -1 at the top-left to 1 at the bottom-right
+    /// (matching Vulkan clip space)
+    pub fn mouse_position_ndc(&self) -> alg::Vec2 {
+        if self.screen.x == 0.0 || self.screen.y == 0.0 {
+            return alg::Vec2::zero();
+        }
+
+        let position = self.mouse_position();
+
+        alg::Vec2::new(
+            2.0 * position.x / self.screen.x - 1.0,
+            2.0 * position.y / self.screen.y - 1.0,
+        )
     }
 
     /* Key states */
@@ -130,3 +291,96 @@ impl Manager {
         keys
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alg;
+    use input::*;
+
+    #[test]
+    fn text_input() {
+        let mut input = Manager::new();
+
+        for character in "hé\u{8}y!".chars() {
+            input.add_character(character);
+        }
+
+        assert!(input.text_input() == "héy!");
+
+        // Next frame
+        input.reset_deltas();
+        assert!(input.text_input().is_empty());
+    }
+
+    #[test]
+    fn scroll_delta() {
+        let mut input = Manager::new();
+
+        input.add_scroll_lines(0.0, 1.0);
+        input.add_scroll_pixels(10.0, 40.0);
+        assert!(input.scroll_delta() == alg::Vec2::new(0.5, 3.0));
+
+        // Next frame
+        input.reset_deltas();
+        assert!(input.scroll_delta() == alg::Vec2::zero());
+    }
+
+    #[test]
+    fn cursor_grab() {
+        let mut input = Manager::new();
+
+        // Nothing applies until focused
+        input.set_cursor_visible(false);
+        assert!(input.take_cursor_request().is_none());
+
+        input.set_focused(true);
+        assert!(input.take_cursor_request() == Some((true, false)));
+        assert!(input.take_cursor_request().is_none());
+        input.confirm_cursor_grabbed(true);
+        assert!(input.cursor_grabbed());
+
+        // Relative movement accumulates while grabbed
+        input.add_mouse_delta(2.0, -1.0);
+        input.add_mouse_delta(3.0, 0.5);
+        assert!(input.mouse_delta == alg::Vec2::new(5.0, -0.5));
+
+        input.set_cursor_grabbed(false);
+        assert!(input.take_cursor_request() == Some((false, false)));
+        input.confirm_cursor_grabbed(false);
+        assert!(!input.cursor_grabbed());
+
+        // Focus loss releases; focus gain requests again
+        input.set_cursor_grabbed(true);
+        input.confirm_cursor_grabbed(true);
+        input.set_focused(false);
+        assert!(!input.cursor_grabbed());
+        assert!(input.take_cursor_request().is_none());
+
+        input.set_focused(true);
+        assert!(input.take_cursor_request() == Some((true, false)));
+    }
+
+    #[test]
+    fn mouse_position() {
+        let mut input = Manager::new();
+        input.set_screen_size(800, 600);
+
+        // Logical pixels at twice the density
+        input.set_cursor_position(100.0, 225.0, 2.0);
+
+        assert!(input.mouse_position() == alg::Vec2::new(200.0, 450.0));
+        assert!(input.mouse_position_ndc() == alg::Vec2::new(-0.5, 0.5));
+
+        // Both representations agree on the window edges
+        input.set_cursor_position(400.0, 0.0, 2.0);
+        assert!(input.mouse_position_ndc() == alg::Vec2::new(1.0, -1.0));
+
+        // Outside the window
+        input.set_cursor_position(-50.0, 400.0, 2.0);
+        assert!(input.mouse_position() == alg::Vec2::new(-100.0, 800.0));
+
+        input.confirm_cursor_grabbed(true);
+        assert!(input.mouse_position() == alg::Vec2::new(0.0, 600.0));
+        assert!(input.mouse_position_ndc() == alg::Vec2::new(-1.0, 1.0));
+    }
+}
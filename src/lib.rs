@@ -35,6 +35,7 @@ macro_rules! default_traits {
 }
 
 pub mod alg;
+pub mod anim;
 pub mod render;
 pub mod graphics;
 pub mod entity;
@@ -44,9 +45,13 @@ pub mod input;
 pub mod obj_loader;
 pub mod debug;
 pub mod font;
+pub mod headless;
+#[cfg(feature = "audio")] pub mod audio;
 mod statics;
 mod util;
 
+pub use util::rng;
+
 use std::thread;
 
 const FIXED_DT: f32 = 1. / 100.;
@@ -57,6 +62,20 @@ pub struct Metadata {
     pub frame: u32,
     pub fixed_frame: u32,
     pub fps: u32,
+
+    // Range 0 - 1; progress toward the next fixed step once this frame's
+    // fixed steps have run. Use to interpolate between physics states.
+    // Set after the fixed steps, so `Update` sees the previous frame's.
+    pub alpha: f32,
+
+    /* Timings of the last frame in seconds, for profiling */
+
+    pub frame_time: f64, // Total CPU time, excluding the frame limiter
+    pub physics_time: f64, // Softbody simulation, summed over fixed steps
+    pub render_time: f64, // Renderer update and draw submission
+
+    // Submitted geometry and light culling of the last frame
+    pub render_stats: render::RenderStats,
 }
 
 impl Metadata {
@@ -65,8 +84,96 @@ impl Metadata {
             frame: 0,
             fixed_frame: 0,
             fps: 0,
+            alpha: 0.0,
+            frame_time: 0.0,
+            physics_time: 0.0,
+            render_time: 0.0,
+            render_stats: render::RenderStats::default(),
+        }
+    }
+}
+
+/// Convert duration to seconds
+fn seconds(duration: std::time::Duration) -> f64 {
+    duration.as_secs() as f64
+        + (duration.subsec_nanos() as f64 / 1_000_000_000.)
+}
+
+/// Fixed-framerate accumulator
+struct Accumulator {
+    time: f64,
+    step: f64,
+}
+
+impl Accumulator {
+    fn new(step: f64) -> Accumulator {
+        Accumulator {
+            time: 0.0,
+            step,
+        }
+    }
+
+    fn add(&mut self, delta: f64) {
+        self.time += delta;
+    }
+
+    /// Returns true if a fixed step is due, consuming it
+    fn consume(&mut self) -> bool {
+        if self.time >= self.step {
+            self.time -= self.step;
+            return true;
+        }
+
+        false
+    }
+
+    /// Fraction of a step remaining after all due steps are consumed
+    fn alpha(&self) -> f32 {
+        ((self.time % self.step) / self.step) as f32
+    }
+
+    /// Returns true if a fixed step should run, consuming it. \
+    /// While paused, time is discarded and only requested steps run.
+    fn consume_unless_paused(
+        &mut self,
+        parameters: &mut render::Parameters,
+    ) -> bool {
+        if parameters.paused() {
+            while self.consume() { }
+            return parameters.take_step();
+        }
+
+        self.consume()
+    }
+}
+
+/// Window size changes received while polling events,
+/// applied once before the frame's update
+struct Resize {
+    size: Option<(u32, u32)>, // Latest size in physical pixels
+}
+
+impl Resize {
+    fn new() -> Resize {
+        Resize {
+            size: None,
         }
     }
+
+    /// Record a window size in logical pixels. \
+    /// Empty sizes (e.g. a minimized window) are ignored.
+    fn record(&mut self, width: f64, height: f64, hidpi_factor: f64) {
+        let width = (width * hidpi_factor).round() as u32;
+        let height = (height * hidpi_factor).round() as u32;
+
+        if width == 0 || height == 0 { return; }
+        self.size = Some((width, height));
+    }
+
+    /// Returns the latest recorded size, if any, clearing it
+    fn take(&mut self) -> Option<(u32, u32)> {
+        self.size.take()
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -100,6 +207,15 @@ pub trait Update {
     ) { }
 }
 
+/// Per-frame schedule: \
+/// 1. `Update::update()` \
+/// 2. For every fixed step due:
+///    `fixed_update()`, `before_physics()`,
+///    softbody simulation (calling `softbody::Iterate::iterate()`),
+///    `after_physics()` \
+/// 3. Cameras, lights, draws, texts and labels
+///    read the final transforms \
+/// 4. Camera compute and rendering
 pub trait FixedUpdate {
     #[allow(unused_variables)]
     fn fixed_update(
@@ -114,6 +230,24 @@ pub trait FixedUpdate {
         input: &input::Manager,
         debug: &mut debug::Handler,
     ) { }
+
+    /// Runs after `fixed_update()`, right before the physics step
+    #[allow(unused_variables)]
+    fn before_physics(
+        &mut self,
+        fixed_delta: f32,
+        entities: &mut entity::Manager,
+        components: &mut components::Container,
+    ) { }
+
+    /// Runs right after the physics step has written back transforms
+    #[allow(unused_variables)]
+    fn after_physics(
+        &mut self,
+        fixed_delta: f32,
+        entities: &mut entity::Manager,
+        components: &mut components::Container,
+    ) { }
 }
 
 pub fn go<T>(model_data: Vec<render::ModelData>, mut game: T)
@@ -131,6 +265,12 @@ where
     };
 
     let mut parameters = render::Parameters::new();
+
+    parameters.interop = match context.interop() {
+        Ok(interop) => Some(interop),
+        Err(e) => panic!("Could not create Vulkan context: {}", e)
+    };
+
     let instances = render::Instances::new(
         context.models.len(),
         &context.model_names,
@@ -141,15 +281,7 @@ where
     let mut entities = entity::Manager::new(1);
 
     // Initialize core components
-    let mut components = components::Container {
-        transforms: components::transform::Manager::new(1),
-        cameras:    components::camera::Manager::new(1),
-        lights:     components::light::Manager::new(8),
-        draws:      components::draw::Manager::new(1, instances),
-        softbodies: components::softbody::Manager::new(1, 1, 1),
-        texts:      components::text::Manager::new(8),
-        labels:     components::label::Manager::new(8),
-    };
+    let mut components = init_components(instances, load_axes());
 
     // Create input manager
     let mut input = input::Manager::new();
@@ -177,6 +309,50 @@ where
     context.device.wait_idle();
 }
 
+fn init_components(
+    instances: render::Instances,
+    axes: alg::Axes,
+) -> components::Container {
+    let mut components = components::Container {
+        transforms: components::transform::Manager::new(1),
+        cameras:    components::camera::Manager::new(1),
+        lights:     components::light::Manager::new(8),
+        draws:      components::draw::Manager::new(1, instances),
+        softbodies: components::softbody::Manager::new(1, 1, 1),
+        texts:      components::text::Manager::new(8),
+        labels:     components::label::Manager::new(8),
+    };
+
+    components.cameras.set_axes(axes);
+    components.softbodies.set_gravity(
+        axes.up() * -components::softbody::MNGR_DEFAULT_GRAVITY
+    );
+
+    components
+}
+
+/// Load world coordinate convention from the engine config. \
+/// Defaults to Y-up and left-handed if unset.
+fn load_axes() -> alg::Axes {
+    let settings = config::load_section(&config::ENGINE_CONFIG, "settings");
+
+    let up_axis = match settings.get("up_axis").map(|value| value.as_str()) {
+        None | Some("y") => alg::UpAxis::Y,
+        Some("z") => alg::UpAxis::Z,
+        Some(value) => panic!("Invalid up axis \"{}\"", value),
+    };
+
+    let handedness = match settings.get("handedness")
+        .map(|value| value.as_str())
+    {
+        None | Some("left") => alg::Handedness::Left,
+        Some("right") => alg::Handedness::Right,
+        Some(value) => panic!("Invalid handedness \"{}\"", value),
+    };
+
+    alg::Axes::new(up_axis, handedness)
+}
+
 fn init_window() -> (vdw::winit::EventsLoop, vdw::winit::Window) {
     let events = vdw::winit::EventsLoop::new();
 
@@ -209,7 +385,6 @@ fn begin_update<T>(
 
     let start = std::time::Instant::now();
     let mut last_time = 0f64;
-    let mut accumulator = 0f64; // Fixed-framerate accumulator
     let mut last_updated_counter = start;
     let mut last_updated_renderer = start;
     let mut last_frame = 0u32;
@@ -245,27 +420,31 @@ fn begin_update<T>(
         FIXED_DT * fixed_step_factor
     ) as f64;
 
+    let mut accumulator = Accumulator::new(fixed_step);
+
     loop {
         // Update last frame of input
         input.increment_key_states();
 
         // Reset dirty input
-        input.mouse_delta = alg::Vec2::zero();
+        input.reset_deltas();
+
+        // Latest window size, if it changed this frame
+        let mut resize = Resize::new();
 
         // Handle window events
         events.poll_events(|event| {
             match event {
-                // Rebuild the swapchain if the window changes size
+                // Record new window size in physical pixels
                 vdw::winit::Event::WindowEvent {
                     event: vdw::winit::WindowEvent::Resized(size),
                     ..
                 } => {
-                    let (width, height) = size.into();
-                    if width == 0 || height == 0 { return; }
-
-                    if let Err(e) = context.refresh_swapchain(width, height) {
-                        panic!("{}", e);
-                    }
+                    resize.record(
+                        size.width,
+                        size.height,
+                        window.get_hidpi_factor(),
+                    );
                 },
 
                 // Stop the application if the window was closed
@@ -276,13 +455,20 @@ fn begin_update<T>(
                     running = false;
                 },
 
-                // Grab mouse cursor if window is focused; release otherwise
+                // Release mouse cursor if window loses focus;
+                // requested cursor state is applied again on focus
                 vdw::winit::Event::WindowEvent {
                     event: vdw::winit::WindowEvent::Focused(focused),
                     ..
                 } => {
-                    if let Err(e) = window.grab_cursor(focused) {
-                        eprintln!("{}", e);
+                    input.set_focused(focused);
+
+                    if !focused {
+                        if let Err(e) = window.grab_cursor(false) {
+                            eprintln!("{}", e);
+                        }
+
+                        window.hide_cursor(false);
                     }
                 },
 
@@ -309,6 +495,16 @@ fn begin_update<T>(
                     }
                 },
 
+                // Text input
+                vdw::winit::Event::WindowEvent {
+                    event: vdw::winit::WindowEvent::ReceivedCharacter(
+                        character
+                    ),
+                    ..
+                } => {
+                    input.add_character(character);
+                },
+
                 // Mouse input
                 vdw::winit::Event::WindowEvent {
                     event: vdw::winit::WindowEvent::CursorMoved {
@@ -317,19 +513,45 @@ fn begin_update<T>(
                     },
                     ..
                 } => {
-                    input.cursor_coords = alg::Vec2::new(
-                        position.x as f32,
-                        position.y as f32,
+                    input.set_cursor_position(
+                        position.x,
+                        position.y,
+                        window.get_hidpi_factor(),
                     );
                 },
 
+                vdw::winit::Event::WindowEvent {
+                    event: vdw::winit::WindowEvent::MouseWheel {
+                        delta,
+                        ..
+                    },
+                    ..
+                } => {
+                    match delta {
+                        vdw::winit::MouseScrollDelta::LineDelta(x, y) => {
+                            input.add_scroll_lines(x, y);
+                        },
+
+                        vdw::winit::MouseScrollDelta::PixelDelta(position) => {
+                            let position = position.to_physical(
+                                window.get_hidpi_factor()
+                            );
+
+                            input.add_scroll_pixels(
+                                position.x as f32,
+                                position.y as f32,
+                            );
+                        },
+                    }
+                },
+
                 vdw::winit::Event::DeviceEvent {
                     event: vdw::winit::DeviceEvent::MouseMotion {
                         delta
                     },
                     ..
                 } => {
-                    input.mouse_delta = alg::Vec2::new(
+                    input.add_mouse_delta(
                         delta.0 as f32,
                         delta.1 as f32,
                     );
@@ -341,13 +563,32 @@ fn begin_update<T>(
 
         if !running { break; }
 
+        // Apply cursor grab and visibility requested by the game
+        if let Some((grabbed, visible)) = input.take_cursor_request() {
+            match window.grab_cursor(grabbed) {
+                Ok(()) => input.confirm_cursor_grabbed(grabbed),
+                Err(e) => eprintln!("{}", e),
+            }
+
+            window.hide_cursor(!visible);
+        }
+
+        /* Rebuild the swapchain once if the window changed size,
+         * so that the screen data (and camera projections) reflect
+         * the new dimensions this frame
+         */
+
+        if let Some((width, height)) = resize.take() {
+            if let Err(e) = context.refresh_swapchain(width, height) {
+                panic!("{}", e);
+            }
+        }
+
         /* Time calculations */
 
         let now = std::time::Instant::now();
-        let duration = now.duration_since(start);
-
-        let time = duration.as_secs() as f64
-            + (duration.subsec_nanos() as f64 / 1_000_000_000.);
+        let frame_start = now;
+        let time = seconds(now.duration_since(start));
 
         let delta = time - last_time;
         last_time = time;
@@ -362,6 +603,10 @@ fn begin_update<T>(
             }
         };
 
+        input.set_screen_size(screen.width, screen.height);
+
+        accumulator.add(delta);
+
         // Update game via callback
         game.update(
             time,
@@ -377,13 +622,13 @@ fn begin_update<T>(
 
         /* Fixed update loop */
 
-        accumulator += delta;
+        metadata.physics_time = 0.0;
 
-        while accumulator >= fixed_step {
-            game.fixed_update(
+        while accumulator.consume_unless_paused(parameters) {
+            fixed_step_update(
+                &mut game,
                 time,
-                FIXED_DT,
-                metadata,
+                &mut metadata,
                 screen,
                 parameters,
                 entities,
@@ -391,51 +636,63 @@ fn begin_update<T>(
                 input,
                 debug,
             );
-
-            // Update physics component
-            components.softbodies.simulate(
-                &mut game,
-                &mut components.transforms,
-            );
-
-            accumulator -= fixed_step;
-            metadata.fixed_frame += 1;
         }
 
+        // Remainder once every due step has run
+        metadata.alpha = accumulator.alpha();
+
         // Update render-related components
-        components.lights.update(&components.transforms);
-        components.draws.transfer(
-            &components.transforms,
-            &components.softbodies,
-            &components.lights,
-        );
+        update_components(components, delta as f32, metadata.alpha);
 
-        // Get shared UBO from camera component
-        let shared_ubo = components.cameras.compute(
-            &components.transforms,
-            screen,
-        );
-        components.texts.update(&components.transforms);
-        components.labels.update(&components.transforms);
+        // Get shared UBOs from camera component, in render order
+        let shared_ubos: Vec<_> = components.cameras
+            .compute(&components.transforms, screen)
+            .into_iter()
+            .map(|(ubo, clear_depth, target)| {
+                (ubo.with_fog(parameters), clear_depth, target)
+            }).collect();
 
         // Update renderer
+        let render_start = std::time::Instant::now();
+
         if let Err(e) = context.update(
             &components.draws.instances,
-            shared_ubo,
+            &shared_ubos,
         ) {
             // Irrecoverable error
             panic!("{}", e);
         }
 
         #[cfg(debug_assertions)] {
-            if let Err(e) = context.update_debug(&debug.lines) {
+            let viewer = components.cameras.active()
+                .map(|camera| components.transforms.get_position(camera))
+                .unwrap_or(alg::Vec3::zero());
+
+            // Debug lines are drawn by the first camera
+            let shared_ubo = shared_ubos[0].0;
+
+            let lines = debug.visible_lines(
+                shared_ubo.projection() * shared_ubo.view(),
+                viewer,
+            );
+
+            if let Err(e) = context.update_debug(lines) {
                 // Irrecoverable error
                 panic!("{}", e);
             }
         }
 
+        if let Err(e) = context.update_points(&parameters.points) {
+            // Irrecoverable error
+            panic!("{}", e);
+        }
+
+        let mut render_duration = render_start.elapsed();
+
         /* Limit frames per second */
 
+        let limit_start = std::time::Instant::now();
+
         loop {
             let ns_since_update = std::time::Instant::now()
                 .duration_since(last_updated_renderer)
@@ -457,6 +714,8 @@ fn begin_update<T>(
         let now = std::time::Instant::now();
         last_updated_renderer = now;
 
+        let limit_duration = now.duration_since(limit_start);
+
         // Render frame
         if let Err(e) = context.draw(
             &parameters,
@@ -473,6 +732,8 @@ fn begin_update<T>(
                 {
                     // Use existing window size
                     if let Some(size) = window.get_inner_size() {
+                        let size = size.to_physical(window.get_hidpi_factor());
+
                         match context.refresh_swapchain(
                             size.width as u32,
                             size.height as u32,
@@ -491,6 +752,18 @@ fn begin_update<T>(
             panic!("{}", e);
         }
 
+        render_duration += now.elapsed();
+        metadata.render_time = seconds(render_duration);
+
+        metadata.render_stats = context.stats(
+            &parameters,
+            &components.draws.instances,
+        );
+
+        metadata.render_stats.lights_culled =
+            components.draws.lights_culled() as u32;
+        metadata.frame_time = seconds(frame_start.elapsed() - limit_duration);
+
         // Increment frame counter
         metadata.frame += 1;
 
@@ -508,6 +781,74 @@ fn begin_update<T>(
     }
 }
 
+/// Run game fixed update followed by a physics step,
+/// with hooks on either side of it
+fn fixed_step_update<T>(
+    game:       &mut T,
+    time:       f64,
+    metadata:   &mut Metadata,
+    screen:     ScreenData,
+    parameters: &mut render::Parameters,
+    entities:   &mut entity::Manager,
+    components: &mut components::Container,
+    input:      &input::Manager,
+    debug:      &mut debug::Handler,
+) where
+    T: FixedUpdate + components::softbody::Iterate
+{
+    // Keep previous state for interpolation
+    components.transforms.store_last();
+
+    game.fixed_update(
+        time,
+        FIXED_DT,
+        *metadata,
+        screen,
+        parameters,
+        entities,
+        components,
+        input,
+        debug,
+    );
+
+    game.before_physics(FIXED_DT, entities, components);
+
+    // Update physics component
+    let physics_start = std::time::Instant::now();
+
+    components.softbodies.simulate(
+        game,
+        &mut components.transforms,
+    );
+
+    metadata.physics_time += seconds(physics_start.elapsed());
+
+    game.after_physics(FIXED_DT, entities, components);
+
+    metadata.fixed_frame += 1;
+}
+
+/// Update render-related components from the current transforms,
+/// drawing models `alpha` of the way from the previous fixed step
+fn update_components(
+    components: &mut components::Container,
+    delta: f32,
+    alpha: f32,
+) {
+    components.cameras.update(&mut components.transforms, delta);
+    components.lights.update(&components.transforms);
+    components.draws.transfer(
+        &components.transforms,
+        &components.softbodies,
+        &components.lights,
+        &components.cameras,
+        alpha,
+    );
+
+    components.texts.update(&components.transforms);
+    components.labels.update(&components.transforms);
+}
+
 fn vdw_key_to_key(keycode: vdw::winit::VirtualKeyCode) -> Option<input::Key> {
     use vdw::winit::VirtualKeyCode;
     use input::Key;
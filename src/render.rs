@@ -1,5 +1,6 @@
 extern crate voodoo as vd;
 extern crate voodoo_winit as vdw;
+extern crate fnv;
 
 use std;
 use alg;
@@ -28,8 +29,12 @@ const VALIDATION_LAYERS: &[&str] = &["VK_LAYER_LUNARG_standard_validation"];
 const DEVICE_EXTENSIONS: &[&str] = &["VK_KHR_swapchain"];
 
 const MAX_INSTANCES: u64 = 1024;
+
+/// Maximum number of cameras rendered in a single frame
+pub const MAX_CAMERAS: usize = 4;
 #[cfg(debug_assertions)]
-const MAX_DEBUG_LINES: u64 = 1024;
+const INITIAL_DEBUG_LINES: u64 = 1024; // Grows with the high-water mark
+const INITIAL_POINTS: u64 = 1024; // Grows with the high-water mark
 
 /* Good GPUs have a minimum alignment of 256,
  * which gives us some extra space to pack offset vectors
@@ -38,6 +43,8 @@ const MAX_DEBUG_LINES: u64 = 1024;
 
 const DYNAMIC_UBO_WIDTH: usize = 996;
 
+// More lights per instance leave less room for softbody offsets;
+// fails to compile if the lights no longer fit
 pub const MAX_SOFTBODY_VERT: usize = (
     DYNAMIC_UBO_WIDTH
         - std::mem::size_of::<alg::Mat4>()
@@ -46,9 +53,21 @@ pub const MAX_SOFTBODY_VERT: usize = (
 ) / std::mem::size_of::<PaddedVec3>()
   / 2; // There are two offset arrays
 
+/// Lights sent to the shaders per instance. \
+/// Enable the `lights-8` or `lights-16` feature for more,
+/// and rebuild the shaders with the same `MAX_INSTANCE_LIGHTS`;
+/// loading shaders built for a different count fails.
+#[cfg(not(any(feature = "lights-8", feature = "lights-16")))]
 pub const MAX_INSTANCE_LIGHTS: usize = 4;
+#[cfg(all(feature = "lights-8", not(feature = "lights-16")))]
+pub const MAX_INSTANCE_LIGHTS: usize = 8;
+#[cfg(feature = "lights-16")]
+pub const MAX_INSTANCE_LIGHTS: usize = 16;
 
 const MAX_CHAR_COUNT: u32 = 2048;
+
+// Attribute tolerance when merging duplicate vertices
+const VERTEX_MERGE_EPSILON: f32 = 0.00001;
 const MAX_INSTANCE_TEXTS: usize = 64;
 
 #[allow(dead_code)]
@@ -77,17 +96,22 @@ pub struct Context<'a> {
     command_fences:  Vec<vd::Fence>,
     shader_stages:   [vd::PipelineShaderStageCreateInfo<'a>; 2],
     depth_format:    vd::Format,
+    samples:         vd::SampleCountFlags,
     assembly:        vd::PipelineInputAssemblyStateCreateInfo<'a>,
     rasterizer:      vd::PipelineRasterizationStateCreateInfo<'a>,
     multisampling:   vd::PipelineMultisampleStateCreateInfo<'a>,
     ubo_layout:      vd::DescriptorSetLayout,
     pipeline_layout: vd::PipelineLayout,
     render_pass:     vd::RenderPass,
-    pipeline:        vd::GraphicsPipeline,
+    offscreen_pass:  vd::RenderPass, // Compatible with render_pass
+    pipelines:       Vec<vd::GraphicsPipeline>, // Indexed by MeshPass
     framebuffers:    Vec<vd::Framebuffer>,
     ubo_alignment:   u64,
-    descriptor_sets: Vec<vd::DescriptorSet>,
+    shared_alignment: u64,
+    descriptor_sets: Vec<vd::DescriptorSet>, // One per camera
     command_buffers: Vec<vd::CommandBuffer>,
+    camera_clears:   Vec<bool>, // Depth clear for each rendered camera
+    camera_targets:  Vec<RenderTarget>, // Image for each rendered camera
 
     /* Unsafe data */
 
@@ -95,11 +119,14 @@ pub struct Context<'a> {
     vertex_memory:  vd::DeviceMemoryHandle,
     index_buffer:   vd::BufferHandle,
     index_memory:   vd::DeviceMemoryHandle,
+    index_type:     vd::IndexType,
     depth_memory:   vd::DeviceMemoryHandle,
+    color_memory:   Option<vd::DeviceMemoryHandle>, // Multisampled only
     ubo_buffer:     vd::BufferHandle,
     ubo_memory:     vd::DeviceMemoryHandle,
     dyn_ubo_buffer: vd::BufferHandle,
     dyn_ubo_memory: vd::DeviceMemoryHandle,
+    offscreen:      Vec<Option<OffscreenTarget>>, // Indexed by camera
 
     /* Text data */
 
@@ -109,16 +136,30 @@ pub struct Context<'a> {
     text_meta:      TextMeta,
     font_alignment: u64,
 
+    /* Background data */
+
+    background_data: BackgroundData,
+
     /* Debug data */
 
     debug_data: Option<DebugData>,
     debug_line_count: u32,
 
+    /* Point data */
+
+    point_data: PointData,
+    point_count: u32,
+
+    /* Capture data */
+
+    presented: Option<u32>, // Most recently presented swapchain image
+
     /* Persistent data */
 
     _vert_mod:        vd::ShaderModule,
     _frag_mod:        vd::ShaderModule,
     _depth_image:     vd::Image,
+    _color_image:     Option<vd::Image>,
     _views:           Vec<vd::ImageView>,
     _descriptor_pool: vd::DescriptorPool,
 }
@@ -154,6 +195,7 @@ impl<'a> Context<'a> {
             vertex_memory,
             index_buffer,
             index_memory,
+            index_type,
             models,
             model_names,
         ) = load_models(
@@ -163,6 +205,8 @@ impl<'a> Context<'a> {
             graphics_family,
         )?;
 
+        let samples = init_samples(&device);
+
         let (
             depth_format,
             assembly,
@@ -170,7 +214,7 @@ impl<'a> Context<'a> {
             multisampling,
             ubo_layout,
             pipeline_layout,
-        ) = init_fixed(device.clone())?;
+        ) = init_fixed(device.clone(), samples)?;
 
         let (swapchain, command_fences, _views) = init_swapchain(
             &device,
@@ -184,12 +228,23 @@ impl<'a> Context<'a> {
         )?;
 
         let render_pass = init_render_pass(
-            &swapchain,
+            swapchain.image_format(),
+            depth_format,
+            samples,
+            vd::ImageLayout::PresentSrcKhr,
+            &device,
+        )?;
+
+        // Offscreen images are sampled after rendering
+        let offscreen_pass = init_render_pass(
+            swapchain.image_format(),
             depth_format,
+            samples,
+            vd::ImageLayout::ShaderReadOnlyOptimal,
             &device,
         )?;
 
-        let pipeline = init_pipeline(
+        let pipelines = init_pipelines(
             &swapchain,
             &shader_stages,
             &assembly,
@@ -200,20 +255,42 @@ impl<'a> Context<'a> {
             &device,
         )?;
 
+        let background_data = init_background(
+            &swapchain,
+            &render_pass,
+            &pipeline_layout,
+            samples,
+            &device,
+        )?;
+
         /* Optional debug data */
 
         let debug_data = init_debug(
             &swapchain,
             &render_pass,
             &pipeline_layout,
+            samples,
             &device,
         )?;
 
         let debug_line_count = 0;
 
+        let point_data = init_points(
+            &swapchain,
+            &render_pass,
+            &pipeline_layout,
+            samples,
+            &device,
+        )?;
+
+        let point_count = 0;
+        let presented = None;
+
         let (
             _depth_image,
             depth_memory,
+            _color_image,
+            color_memory,
             framebuffers,
             ubo_buffer,
             ubo_memory,
@@ -227,6 +304,7 @@ impl<'a> Context<'a> {
         ) = init_drawing(
             &swapchain,
             depth_format,
+            samples,
             &_views,
             &render_pass,
             &device,
@@ -299,36 +377,49 @@ impl<'a> Context<'a> {
                 command_fences,
                 shader_stages,
                 depth_format,
+                samples,
                 assembly,
                 rasterizer,
                 multisampling,
                 ubo_layout,
                 pipeline_layout,
                 render_pass,
-                pipeline,
+                offscreen_pass,
+                pipelines,
                 framebuffers,
                 ubo_alignment,
+                shared_alignment,
                 descriptor_sets,
                 command_buffers,
+                camera_clears: vec![true],
+                camera_targets: vec![RenderTarget::Swapchain],
                 vertex_buffer,
                 vertex_memory,
                 index_buffer,
                 index_memory,
+                index_type,
                 depth_memory,
+                color_memory,
                 ubo_buffer,
                 ubo_memory,
                 dyn_ubo_buffer,
                 dyn_ubo_memory,
+                offscreen: vec![None],
                 text_display,
                 label_display,
                 font_data,
                 text_meta,
                 font_alignment,
+                background_data,
                 debug_data,
                 debug_line_count,
+                point_data,
+                point_count,
+                presented,
                 _vert_mod,
                 _frag_mod,
                 _depth_image,
+                _color_image,
                 _views,
                 _descriptor_pool,
             }
@@ -350,12 +441,14 @@ impl<'a> Context<'a> {
         )?;
 
         let render_pass = init_render_pass(
-            &swapchain,
+            swapchain.image_format(),
             self.depth_format,
+            self.samples,
+            vd::ImageLayout::PresentSrcKhr,
             &self.device,
         )?;
 
-        let pipeline = init_pipeline(
+        let pipelines = init_pipelines(
             &swapchain,
             &self.shader_stages,
             &self.assembly,
@@ -366,17 +459,36 @@ impl<'a> Context<'a> {
             &self.device,
         )?;
 
+        let background_data = init_background(
+            &swapchain,
+            &render_pass,
+            &self.pipeline_layout,
+            self.samples,
+            &self.device,
+        )?;
+
         #[allow(unused_variables)]
         let debug_data = init_debug(
             &swapchain,
             &render_pass,
             &self.pipeline_layout,
+            self.samples,
+            &self.device,
+        )?;
+
+        let point_data = init_points(
+            &swapchain,
+            &render_pass,
+            &self.pipeline_layout,
+            self.samples,
             &self.device,
         )?;
 
         let (
             _depth_image,
             depth_memory,
+            _color_image,
+            color_memory,
             framebuffers,
             ubo_buffer,
             ubo_memory,
@@ -390,6 +502,7 @@ impl<'a> Context<'a> {
         ) = init_drawing(
             &swapchain,
             self.depth_format,
+            self.samples,
             &_views,
             &render_pass,
             &self.device,
@@ -409,9 +522,10 @@ impl<'a> Context<'a> {
         /* Coup */
 
         self.command_fences = command_fences;
-        self.pipeline = pipeline;
+        self.pipelines = pipelines;
         self.framebuffers = framebuffers;
         self.ubo_alignment = ubo_alignment;
+        self.shared_alignment = shared_alignment;
         self.font_alignment = font_alignment;
         self.descriptor_sets = descriptor_sets;
         self.command_buffers = command_buffers;
@@ -457,19 +571,30 @@ impl<'a> Context<'a> {
         self.swapchain = swapchain;
         self.render_pass = render_pass;
         self.depth_memory = depth_memory;
+        self.color_memory = color_memory;
         self.ubo_buffer = ubo_buffer;
         self.ubo_memory = ubo_memory;
         self.dyn_ubo_buffer = dyn_ubo_buffer;
         self.dyn_ubo_memory = dyn_ubo_memory;
 
         self._depth_image = _depth_image;
+        self._color_image = _color_image;
         self._views = _views;
         self._descriptor_pool = _descriptor_pool;
 
+        self.background_data = background_data;
+
         #[cfg(debug_assertions)] {
             self.debug_data = debug_data;
         }
 
+        // Points are uploaded again next frame
+        self.point_data = point_data;
+        self.point_count = 0;
+
+        // New swapchain images have no contents yet
+        self.presented = None;
+
         Ok(())
     }
 
@@ -480,8 +605,30 @@ impl<'a> Context<'a> {
 
         if self.debug_line_count == 0 {
             return Ok(());
-        } else if self.debug_line_count > MAX_DEBUG_LINES as u32 {
-            return Err("Exceeded maximum number of debug lines".into());
+        }
+
+        // Only reallocate when the high-water mark increases
+        if lines.len() as u64 > self.debug_data.as_ref().unwrap().capacity {
+            let capacity = (lines.len() as u64).next_power_of_two();
+
+            let (buffer, memory) = create_debug_buffer(
+                capacity,
+                &self.device,
+            )?;
+
+            let data = self.debug_data.as_mut().unwrap();
+
+            // Old buffer may still be bound by an in-flight frame
+            self.device.wait_idle();
+
+            unsafe {
+                self.device.destroy_buffer(data.buffer, None);
+                self.device.free_memory(data.memory, None);
+            }
+
+            data.buffer = buffer;
+            data.memory = memory;
+            data.capacity = capacity;
         }
 
         /* Copy debug data to GPU */
@@ -498,23 +645,177 @@ impl<'a> Context<'a> {
         Ok(())
     }
 
-    /// Update rendering data and transfer to GPU
+    /// Returns Vulkan handles for external rendering code
+    pub fn interop(&self) -> vd::Result<Interop> {
+        let graphics_queue = self.device.get_device_queue(
+            self.graphics_family,
+            0,
+        ).ok_or("no graphics queue")?;
+
+        Ok(
+            Interop {
+                device: self.device.clone(),
+                graphics_family: self.graphics_family,
+                graphics_queue,
+            }
+        )
+    }
+
+    /// Upload camera-facing points to draw this frame
+    pub fn update_points(&mut self, points: &[Point]) -> vd::Result<()> {
+        self.point_count = points.len() as u32;
+
+        if self.point_count == 0 {
+            return Ok(());
+        }
+
+        // Only reallocate when the high-water mark increases
+        if points.len() as u64 > self.point_data.capacity {
+            let capacity = (points.len() as u64).next_power_of_two();
+            let (buffer, memory) = create_point_buffer(capacity, &self.device)?;
+
+            // Old buffer may still be bound by an in-flight frame
+            self.device.wait_idle();
+
+            unsafe {
+                self.device.destroy_buffer(self.point_data.buffer, None);
+                self.device.free_memory(self.point_data.memory, None);
+            }
+
+            self.point_data.buffer = buffer;
+            self.point_data.memory = memory;
+            self.point_data.capacity = capacity;
+        }
+
+        /* Copy point data to GPU */
+
+        let vertices: Vec<Vertex> = points.iter()
+            .flat_map(|point| point.vertices().to_vec())
+            .collect();
+
+        unsafe {
+            copy_buffer(
+                &self.device,
+                self.point_data.memory,
+                (vertices.len() * std::mem::size_of::<Vertex>()) as u64,
+                &vertices,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Create, resize, or free offscreen images to match the cameras
+    fn update_offscreen(&mut self) -> vd::Result<()> {
+        // Returns true if the existing image matches the target
+        let current = |image: &Option<OffscreenTarget>, target: RenderTarget| {
+            match (image, target) {
+                (&None, RenderTarget::Swapchain) => true,
+                (
+                    &Some(ref image),
+                    RenderTarget::Offscreen { width, height },
+                ) => image.width == width && image.height == height,
+                _ => false,
+            }
+        };
+
+        let unchanged = self.offscreen.len() == self.camera_targets.len()
+            && self.offscreen.iter().zip(&self.camera_targets)
+                .all(|(image, target)| current(image, *target));
+
+        if unchanged { return Ok(()); }
+
+        // Images may still be in use by the previous frame
+        self.device.wait_idle();
+
+        let count = self.camera_targets.len();
+
+        if self.offscreen.len() > count {
+            for image in self.offscreen.drain(count..) {
+                if let Some(image) = image {
+                    unsafe { image.free(&self.device); }
+                }
+            }
+        }
+
+        while self.offscreen.len() < count {
+            self.offscreen.push(None);
+        }
+
+        for i in 0..count {
+            let target = self.camera_targets[i];
+            if current(&self.offscreen[i], target) { continue; }
+
+            if let Some(image) = self.offscreen[i].take() {
+                unsafe { image.free(&self.device); }
+            }
+
+            if let RenderTarget::Offscreen { width, height } = target {
+                self.offscreen[i] = Some(
+                    create_offscreen(
+                        width,
+                        height,
+                        self.swapchain.image_format(),
+                        self.depth_format,
+                        self.samples,
+                        &self.offscreen_pass,
+                        &self.device,
+                    )?
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Update rendering data and transfer to GPU. \
+    /// Cameras are rendered in order, each paired with whether depth
+    /// is cleared before it is drawn (ignored for the first camera
+    /// drawn to the swapchain) and the image it renders into.
     pub fn update(
         &mut self,
         instances: &Instances,
-        shared_ubo: SharedUBO,
+        cameras: &[(SharedUBO, bool, RenderTarget)],
     ) -> vd::Result<()> {
-        /* Copy shared UBO to GPU */
+        if cameras.is_empty() || cameras.len() > MAX_CAMERAS {
+            return Err(format!(
+                "Invalid camera count {} (maximum {})",
+                cameras.len(),
+                MAX_CAMERAS,
+            ).into());
+        }
+
+        /* Copy shared UBOs to GPU */
+
+        // Not optimal: requires copies and a heap allocation
+        let mut shared_buffer = util::AlignedBuffer::<SharedUBO>::new(
+            self.shared_alignment as usize,
+            cameras.len(),
+        );
+
+        for &(shared_ubo, _, _) in cameras {
+            shared_buffer.push(shared_ubo);
+        }
 
         unsafe {
             copy_buffer(
                 &self.device,
                 self.ubo_memory,
-                std::mem::size_of::<SharedUBO>() as u64,
-                &[shared_ubo],
+                shared_buffer.size() as u64,
+                &shared_buffer.finalize(),
             )?;
         }
 
+        self.camera_clears = cameras.iter()
+            .map(|&(_, clear_depth, _)| clear_depth)
+            .collect();
+
+        self.camera_targets = cameras.iter()
+            .map(|&(_, _, target)| target)
+            .collect();
+
+        self.update_offscreen()?;
+
         /* Copy instance UBOs to GPU */
 
         let count = instances.count();
@@ -549,6 +850,94 @@ impl<'a> Context<'a> {
         Ok(())
     }
 
+    /// Record every mesh pass for the given camera (by render list index)
+    /// into the current render pass, covering an image of `extent`
+    fn draw_models(
+        &self,
+        cmd_buffer: &vd::CommandBuffer,
+        parameters: &Parameters,
+        instances: &Instances,
+        camera: usize,
+        extent: vd::Extent2d,
+    ) {
+        let handle = cmd_buffer.handle();
+
+        // Model pipelines use a dynamic viewport to fit every target
+        let viewport = vd::Viewport::builder()
+            .x(0f32)
+            .y(0f32)
+            .width(extent.width() as f32)
+            .height(extent.height() as f32)
+            .min_depth(0f32)
+            .max_depth(1f32)
+            .build();
+
+        let scissor = vd::Rect2d::builder()
+            .offset(
+                vd::Offset2d::builder()
+                    .x(0)
+                    .y(0)
+                    .build()
+            ).extent(extent)
+            .build();
+
+        unsafe {
+            self.device.cmd_set_viewport(handle, 0, &[viewport]);
+            self.device.cmd_set_scissor(handle, 0, &[scissor]);
+
+            self.device.cmd_bind_vertex_buffers(
+                handle,
+                0,
+                &[self.vertex_buffer],
+                &[0],
+            );
+
+            self.device.cmd_bind_index_buffer(
+                handle,
+                self.index_buffer,
+                0,
+                self.index_type,
+            );
+        }
+
+        for &pass in parameters.mesh_passes() {
+            cmd_buffer.bind_pipeline(
+                vd::PipelineBindPoint::Graphics,
+                &self.pipelines[pass as usize].handle(),
+            );
+
+            let mut instance = 0;
+            for j in 0..self.models.len() {
+                // Render each instance
+                for k in 0..instances.data[j].len() {
+                    // Bind uniform data
+                    cmd_buffer.bind_descriptor_sets(
+                        vd::PipelineBindPoint::Graphics,
+                        &self.pipeline_layout,
+                        0,
+                        &[&self.descriptor_sets[camera]],
+                        // Offset dynamic uniform buffer
+                        &[self.ubo_alignment as u32 * instance as u32],
+                    );
+
+                    instance += 1;
+
+                    // Skip drawing hidden instances
+                    if instances.data[j][k].1.hide { continue; }
+
+                    // Draw call
+                    cmd_buffer.draw_indexed(
+                        self.models[j].index_count,
+                        1,
+                        self.models[j].index_offset,
+                        self.models[j].vertex_offset,
+                        0,
+                    );
+                }
+            }
+        }
+    }
+
     /// Execute command buffers and render frame
     pub fn draw(
         &mut self,
@@ -564,6 +953,37 @@ impl<'a> Context<'a> {
             None,
         )?;
 
+        let gradient = parameters.clear_gradient.is_some();
+
+        if gradient {
+            let (top, bottom) = parameters.clear_colors();
+
+            let corner = |x: f32, y: f32, color: graphics::Color| Vertex {
+                position: alg::Vec3::new(x, y, 0.),
+                color,
+                .. Default::default()
+            };
+
+            // Fullscreen quad in clip space (y points down)
+            let vertices = [
+                corner(-1., -1., top),
+                corner( 1., -1., top),
+                corner( 1.,  1., bottom),
+                corner( 1.,  1., bottom),
+                corner(-1.,  1., bottom),
+                corner(-1., -1., top),
+            ];
+
+            unsafe {
+                copy_buffer(
+                    &self.device,
+                    self.background_data.memory,
+                    (vertices.len() * std::mem::size_of::<Vertex>()) as u64,
+                    &vertices,
+                )?;
+            }
+        }
+
         // Get command buffer to use this frame
         let cmd_buffer = &self.command_buffers[index as usize];
 
@@ -618,21 +1038,59 @@ impl<'a> Context<'a> {
 
         debug_assert!(index < self.framebuffers.len() as u32);
 
+        let render_area = vd::Rect2d::builder()
+            .offset(
+                vd::Offset2d::builder()
+                    .x(0)
+                    .y(0)
+                    .build()
+            ).extent(self.swapchain.extent().clone())
+            .build();
+
         let pass_info = vd::RenderPassBeginInfo::builder()
             .render_pass(self.render_pass.handle())
             .framebuffer(&self.framebuffers[index as usize])
-            .render_area(
-                vd::Rect2d::builder()
-                    .offset(
-                        vd::Offset2d::builder()
-                            .x(0)
-                            .y(0)
-                            .build()
-                    ).extent(self.swapchain.extent().clone())
-                    .build()
-            ).clear_values(&clears)
+            .render_area(render_area.clone())
+            .clear_values(&clears)
             .build();
 
+        /* Render offscreen cameras first, so later passes can sample them */
+
+        for (camera, image) in self.offscreen.iter().enumerate() {
+            let image = match *image {
+                Some(ref image) => image,
+                None => continue,
+            };
+
+            let extent = vd::Extent2d::builder()
+                .width(image.width)
+                .height(image.height)
+                .build();
+
+            let offscreen_info = vd::RenderPassBeginInfo::builder()
+                .render_pass(self.offscreen_pass.handle())
+                .framebuffer(&image.framebuffer)
+                .render_area(
+                    vd::Rect2d::builder()
+                        .offset(
+                            vd::Offset2d::builder()
+                                .x(0)
+                                .y(0)
+                                .build()
+                        ).extent(extent.clone())
+                        .build()
+                ).clear_values(&clears)
+                .build();
+
+            cmd_buffer.begin_render_pass(
+                &offscreen_info,
+                vd::SubpassContents::Inline,
+            );
+
+            self.draw_models(cmd_buffer, parameters, instances, camera, extent);
+            cmd_buffer.end_render_pass();
+        }
+
         /* Execute render pass */
 
         cmd_buffer.begin_render_pass(
@@ -640,57 +1098,74 @@ impl<'a> Context<'a> {
             vd::SubpassContents::Inline,
         );
 
-        cmd_buffer.bind_pipeline(
-            vd::PipelineBindPoint::Graphics,
-            &self.pipeline.handle(),
-        );
-
-        unsafe {
-            self.device.cmd_bind_vertex_buffers(
-                handle,
-                0,
-                &[self.vertex_buffer],
-                &[0],
+        // Fill background before geometry
+        if gradient {
+            cmd_buffer.bind_pipeline(
+                vd::PipelineBindPoint::Graphics,
+                &self.background_data.pipeline.handle(),
             );
 
-            self.device.cmd_bind_index_buffer(
-                handle,
-                self.index_buffer,
-                0,
-                vd::IndexType::Uint32,
-            );
+            unsafe {
+                self.device.cmd_bind_vertex_buffers(
+                    handle,
+                    0,
+                    &[self.background_data.buffer],
+                    &[0],
+                );
+            }
+
+            cmd_buffer.draw(6, 1, 0, 0);
         }
 
         debug_assert!(self.models.len() == instances.data.len());
+        debug_assert!(self.camera_clears.len() <= MAX_CAMERAS);
+        debug_assert!(self.camera_clears.len() == self.offscreen.len());
+
+        // Render all swapchain cameras in order; the first is cleared
+        // with the render pass
+        let mut cleared = true;
+
+        for (camera, &clear_depth) in self.camera_clears.iter().enumerate() {
+            if self.offscreen[camera].is_some() { continue; }
+
+            // Later cameras (e.g. overlays) may draw over earlier ones
+            if !cleared && clear_depth {
+                let attachment = vd::ClearAttachment::builder()
+                    .aspect_mask(vd::ImageAspectFlags::DEPTH)
+                    .color_attachment(0) // Ignored for depth
+                    .clear_value(
+                        vd::ClearValue {
+                            depthStencil: vd::vks::VkClearDepthStencilValue {
+                                depth: 1., // Initialized to max depth
+                                stencil: 0,
+                            }
+                        }
+                    ).build();
 
-        let mut instance = 0;
-        for j in 0..self.models.len() {
-            // Render each instance
-            for k in 0..instances.data[j].len() {
-                // Bind uniform data
-                cmd_buffer.bind_descriptor_sets(
-                    vd::PipelineBindPoint::Graphics,
-                    &self.pipeline_layout,
-                    0,
-                    &[&self.descriptor_sets[0]], // Single descriptor set
-                    // Offset dynamic uniform buffer
-                    &[self.ubo_alignment as u32 * instance as u32],
-                );
+                let rect = vd::ClearRect::builder()
+                    .rect(render_area.clone())
+                    .base_array_layer(0)
+                    .layer_count(1)
+                    .build();
 
-                instance += 1;
+                unsafe {
+                    self.device.cmd_clear_attachments(
+                        handle,
+                        &[attachment],
+                        &[rect],
+                    );
+                }
+            }
 
-                // Skip drawing hidden instances
-                if instances.data[j][k].1.hide { continue; }
+            cleared = false;
 
-                // Draw call
-                cmd_buffer.draw_indexed(
-                    self.models[j].index_count,
-                    1,
-                    self.models[j].index_offset,
-                    self.models[j].vertex_offset,
-                    0,
-                );
-            }
+            self.draw_models(
+                cmd_buffer,
+                parameters,
+                instances,
+                camera,
+                self.swapchain.extent().clone(),
+            );
         }
 
         let framebuffer_width = self.swapchain.extent().width();
@@ -772,6 +1247,36 @@ impl<'a> Context<'a> {
             self.font_alignment,
         )?;
 
+        if self.point_count > 0 {
+
+            /* Draw points */
+
+            cmd_buffer.bind_pipeline(
+                vd::PipelineBindPoint::Graphics,
+                &self.point_data.pipeline.handle(),
+            );
+
+            cmd_buffer.bind_descriptor_sets(
+                vd::PipelineBindPoint::Graphics,
+                &self.pipeline_layout,
+                0,
+                &[&self.descriptor_sets[0]], // First camera
+                &[0], // Ignore the dynamic uniform buffer
+            );
+
+            unsafe {
+                self.device.cmd_bind_vertex_buffers(
+                    handle,
+                    0,
+                    &[self.point_data.buffer],
+                    &[0],
+                );
+            }
+
+            // Two triangles per point
+            cmd_buffer.draw(self.point_count * 6, 1, 0, 0);
+        }
+
         #[cfg(debug_assertions)] {
             if self.debug_line_count > 0 {
 
@@ -786,7 +1291,7 @@ impl<'a> Context<'a> {
                     vd::PipelineBindPoint::Graphics,
                     &self.pipeline_layout,
                     0,
-                    &[&self.descriptor_sets[0]], // Single descriptor set
+                    &[&self.descriptor_sets[0]], // First camera
                     &[0], // Ignore the dynamic uniform buffer
                 );
 
@@ -799,10 +1304,8 @@ impl<'a> Context<'a> {
                     );
                 }
 
-                for i in 0..self.debug_line_count {
-                    // Draw two vertices at a time
-                    cmd_buffer.draw(2, 1, i * 2, 0);
-                }
+                // Two vertices per line
+                cmd_buffer.draw(self.debug_line_count * 2, 1, 0, 0);
             }
         }
 
@@ -855,6 +1358,8 @@ impl<'a> Context<'a> {
                             self.device.queue_present_khr(pq, &info)?;
                         }
 
+                        self.presented = Some(index);
+
                         // Synchronize with GPU in debug mode
                         // (prevents memory leaks from the validation layers)
                         #[cfg(debug_assertions)] {
@@ -872,10 +1377,300 @@ impl<'a> Context<'a> {
         Ok(())
     }
 
-    // Free memory allocated on the GPU at init
-    unsafe fn free_device_init(&mut self) {
-        // Vertex buffer
-        self.device.destroy_buffer(self.vertex_buffer, None);
+    /// Replace the vertex data of a loaded model. \
+    /// Existing indices are kept and must address the new vertices. \
+    /// The vertex buffer is reallocated only if the model grows.
+    /// Blocks until in-flight frames are complete.
+    pub fn update_model_vertices(
+        &mut self,
+        model_index: usize,
+        vertices: &[Vertex],
+    ) -> vd::Result<()> {
+        if model_index >= self.models.len() {
+            return Err(format!("Invalid model index {}", model_index).into());
+        }
+
+        let grow = self.models[model_index].check_vertices(vertices.len())?;
+
+        let stride = std::mem::size_of::<Vertex>() as u64;
+        let properties = self.device.physical_device().memory_properties();
+
+        // Frames in flight may still be reading the vertex buffer
+        self.device.wait_idle();
+
+        if grow {
+            let model = &self.models[model_index];
+            let extra = (vertices.len() - model.vertex_count) as u64;
+
+            let split = (model.vertex_offset as u64
+                + model.vertex_count as u64) * stride;
+
+            let size = self.models.iter()
+                .map(|model| model.vertex_count as u64)
+                .sum::<u64>() * stride;
+
+            let (buffer, memory) = create_buffer(
+                size + extra * stride,
+                  vd::BufferUsageFlags::VERTEX_BUFFER
+                | vd::BufferUsageFlags::TRANSFER_SRC
+                | vd::BufferUsageFlags::TRANSFER_DST,
+                &self.device,
+                vd::MemoryPropertyFlags::DEVICE_LOCAL,
+                &properties,
+            )?;
+
+            // Move later models to make room
+            let mut copies = vec![
+                vd::BufferCopy::builder()
+                    .src_offset(0)
+                    .dst_offset(0)
+                    .size(split)
+                    .build(),
+            ];
+
+            if size > split {
+                copies.push(
+                    vd::BufferCopy::builder()
+                        .src_offset(split)
+                        .dst_offset(split + extra * stride)
+                        .size(size - split)
+                        .build()
+                );
+            }
+
+            let transfer_buffer = get_transfer_buffer(&self.transient_pool)?;
+
+            unsafe {
+                self.device.cmd_copy_buffer(
+                    transfer_buffer.handle(),
+                    self.vertex_buffer,
+                    buffer,
+                    &copies,
+                );
+            }
+
+            end_transfer_buffer(
+                &transfer_buffer,
+                &self.device,
+                self.graphics_family,
+            )?;
+
+            unsafe {
+                self.device.destroy_buffer(self.vertex_buffer, None);
+                self.device.free_memory(self.vertex_memory, None);
+            }
+
+            self.vertex_buffer = buffer;
+            self.vertex_memory = memory;
+
+            self.models[model_index].vertex_count = vertices.len();
+            for model in &mut self.models[model_index + 1..] {
+                model.vertex_offset += extra as i32;
+            }
+        }
+
+        stage_buffer(
+            vertices,
+            self.vertex_buffer,
+            self.models[model_index].vertex_offset as u64 * stride,
+            &properties,
+            &self.device,
+            &self.transient_pool,
+            self.graphics_family,
+        )
+    }
+
+    /// Returns model draw statistics for a frame rendered
+    /// with these parameters and instances
+    pub fn stats(
+        &self,
+        parameters: &Parameters,
+        instances: &Instances,
+    ) -> RenderStats {
+        let index_counts: Vec<u32> = self.models.iter()
+            .map(|model| model.index_count)
+            .collect();
+
+        let mut stats = instances.stats(
+            &index_counts,
+            parameters.mesh_passes().len(),
+        );
+
+        if self.point_count > 0 {
+            stats.draw_calls += 1;
+            stats.triangles += self.point_count * 2;
+        }
+
+        stats
+    }
+
+    /// Copy the most recently presented frame to the CPU
+    /// as tightly packed RGBA rows, top to bottom. \
+    /// Returns the width, height, and pixel data. Blocks until idle.
+    pub fn capture_frame(&self) -> vd::Result<(u32, u32, Vec<u8>)> {
+        let index = match self.presented {
+            Some(index) => index,
+            None => return Err("no frame has been presented".into()),
+        };
+
+        let image = &self.swapchain.images()[index as usize];
+
+        self.capture_image(
+            image,
+            vd::ImageLayout::PresentSrcKhr,
+            self.swapchain.extent().width(),
+            self.swapchain.extent().height(),
+        )
+    }
+
+    /// Image view and sampler for the offscreen image rendered by the
+    /// camera at the given render list index, or `None` if that camera
+    /// renders to the swapchain. \
+    /// The image is in the shader read-only layout after each frame.
+    pub fn target_texture(
+        &self,
+        camera: usize,
+    ) -> Option<(&vd::ImageView, &vd::Sampler)> {
+        match self.offscreen.get(camera) {
+            Some(&Some(ref image)) => Some((&image.color.view, &image.sampler)),
+            _ => None,
+        }
+    }
+
+    /// Read back the offscreen image rendered by the camera
+    /// at the given render list index as RGBA8 rows, top to bottom
+    pub fn capture_target(
+        &self,
+        camera: usize,
+    ) -> vd::Result<(u32, u32, Vec<u8>)> {
+        let image = match self.offscreen.get(camera) {
+            Some(&Some(ref image)) => image,
+            _ => return Err("camera has no offscreen target".into()),
+        };
+
+        self.capture_image(
+            &image.color.image,
+            vd::ImageLayout::ShaderReadOnlyOptimal,
+            image.width,
+            image.height,
+        )
+    }
+
+    /// Copy a color image in the swapchain format to host memory,
+    /// restoring its layout afterwards
+    fn capture_image(
+        &self,
+        image: &vd::Image,
+        layout: vd::ImageLayout,
+        width: u32,
+        height: u32,
+    ) -> vd::Result<(u32, u32, Vec<u8>)> {
+        let bgra = match self.surface_format.format() {
+            vd::Format::B8G8R8A8Unorm | vd::Format::B8G8R8A8Srgb => true,
+            vd::Format::R8G8B8A8Unorm | vd::Format::R8G8B8A8Srgb => false,
+            _ => return Err("unsupported swapchain format for capture".into()),
+        };
+
+        let size = width as u64 * height as u64 * 4;
+
+        let properties = self.device.physical_device().memory_properties();
+
+        let (buffer, memory) = create_buffer(
+            size,
+            vd::BufferUsageFlags::TRANSFER_DST,
+            &self.device,
+              vd::MemoryPropertyFlags::HOST_VISIBLE
+            | vd::MemoryPropertyFlags::HOST_COHERENT,
+            &properties,
+        )?;
+
+        // Finish rendering before reading back
+        self.device.wait_idle();
+
+        let copy_cmd = get_transfer_buffer(&self.transient_pool)?;
+
+        set_image_layout(
+            &copy_cmd,
+            image,
+            vd::ImageAspectFlags::COLOR,
+            layout,
+            vd::ImageLayout::TransferSrcOptimal,
+            vd::PipelineStageFlags::ALL_COMMANDS,
+            vd::PipelineStageFlags::TRANSFER,
+        );
+
+        let image_subresource_layers = vd::ImageSubresourceLayers::builder()
+            .aspect_mask(vd::ImageAspectFlags::COLOR)
+            .mip_level(0)
+            .layer_count(1)
+            .build();
+
+        // Zero row length and height pack the rows tightly
+        let region = vd::BufferImageCopy::builder()
+            .buffer_row_length(0)
+            .buffer_image_height(0)
+            .image_subresource(image_subresource_layers)
+            .image_extent(
+                vd::Extent3d::builder()
+                    .width(width)
+                    .height(height)
+                    .depth(1)
+                    .build()
+            ).build();
+
+        unsafe {
+            self.device.cmd_copy_image_to_buffer(
+                copy_cmd.handle(),
+                image.handle(),
+                vd::ImageLayout::TransferSrcOptimal,
+                buffer,
+                &[region],
+            );
+        }
+
+        // Restore layout for presentation or sampling
+        set_image_layout(
+            &copy_cmd,
+            image,
+            vd::ImageAspectFlags::COLOR,
+            vd::ImageLayout::TransferSrcOptimal,
+            layout,
+            vd::PipelineStageFlags::TRANSFER,
+            vd::PipelineStageFlags::BOTTOM_OF_PIPE,
+        );
+
+        end_transfer_buffer(&copy_cmd, &self.device, self.graphics_family)?;
+
+        let mut pixels = vec![0u8; size as usize];
+
+        unsafe {
+            let ptr = self.device.map_memory::<u8>(
+                memory,
+                0,
+                size,
+                vd::MemoryMapFlags::empty(),
+            )?;
+
+            pixels.copy_from_slice(
+                std::slice::from_raw_parts(ptr, size as usize)
+            );
+
+            self.device.unmap_memory(memory);
+            self.device.destroy_buffer(buffer, None);
+            self.device.free_memory(memory, None);
+        }
+
+        if bgra {
+            swizzle_bgra(&mut pixels);
+        }
+
+        Ok((width, height, pixels))
+    }
+
+    // Free memory allocated on the GPU at init
+    unsafe fn free_device_init(&mut self) {
+        // Vertex buffer
+        self.device.destroy_buffer(self.vertex_buffer, None);
         self.device.free_memory(self.vertex_memory, None);
 
         // Index buffer
@@ -888,6 +1683,11 @@ impl<'a> Context<'a> {
         // Depth image
         self.device.free_memory(self.depth_memory, None);
 
+        // Multisampled color image
+        if let Some(memory) = self.color_memory {
+            self.device.free_memory(memory, None);
+        }
+
         // Uniform buffers
         self.device.destroy_buffer(self.ubo_buffer, None);
         self.device.free_memory(self.ubo_memory, None);
@@ -913,6 +1713,14 @@ impl<'a> Context<'a> {
         self.device.destroy_buffer(self.label_display.font_ubo_buffer, None);
         self.device.free_memory(self.label_display.font_ubo_memory, None);
 
+        // Background resources
+        self.device.destroy_buffer(self.background_data.buffer, None);
+        self.device.free_memory(self.background_data.memory, None);
+
+        // Point resources
+        self.device.destroy_buffer(self.point_data.buffer, None);
+        self.device.free_memory(self.point_data.memory, None);
+
         #[cfg(debug_assertions)] {
             /* Debug buffer */
 
@@ -932,6 +1740,12 @@ impl<'a> Context<'a> {
 impl<'a> Drop for Context<'a> {
     fn drop(&mut self) {
         unsafe {
+            for image in self.offscreen.drain(..) {
+                if let Some(image) = image {
+                    image.free(&self.device);
+                }
+            }
+
             self.free_device_refresh();
             self.free_device_init();
         }
@@ -941,20 +1755,230 @@ impl<'a> Drop for Context<'a> {
 /// High-level control settings for drawing
 pub struct Parameters {
     pub clear_color: graphics::Color,
+
+    // Top and bottom colors of a vertical background gradient.
+    // Overrides `clear_color` when set.
+    pub clear_gradient: Option<(graphics::Color, graphics::Color)>,
+
+    // Render model depth before shading, so that each pixel is shaded
+    // at most once. Worthwhile for scenes with heavy overdraw.
+    pub depth_prepass: bool,
+
+    // Blend models toward `fog_color` with view depth,
+    // from none at `fog_start` to full at `fog_end`.
+    // Disabled unless `fog_end` is greater than `fog_start`.
+    pub fog_color: graphics::Color,
+    pub fog_start: f32,
+    pub fog_end: f32,
+
+    // Camera-facing points (e.g. particles, stars) drawn every frame
+    // until changed
+    pub points: Vec<Point>,
+
+    // Vulkan handles for rendering alongside the engine.
+    // Set by the engine; `None` without a renderer (headless).
+    pub interop: Option<Interop>,
+
+    paused: bool, // Skip fixed steps (and physics)
+    step_pending: bool, // Run a single fixed step while paused
+}
+
+/// Vulkan handles shared with external rendering code
+/// (e.g. UI or custom effect passes). \
+/// Resources created on the device must outlive their use by the
+/// external code, and must be destroyed before the engine exits. \
+/// Work submitted to the graphics queue is not synchronized with engine
+/// rendering; wait on the queue (or device) before touching resources
+/// the engine may be reading, and never submit from another thread.
+#[derive(Clone)]
+pub struct Interop {
+    pub device: vd::Device, // Also provides physical device and instance
+    pub graphics_family: u32,
+    pub graphics_queue: vd::QueueHandle,
 }
 
 impl Parameters {
     pub fn new() -> Parameters {
         Parameters {
             clear_color: graphics::Color::black(),
+            clear_gradient: None,
+            depth_prepass: false,
+            fog_color: graphics::Color::black(),
+            fog_start: 0.0,
+            fog_end: 0.0,
+            points: Vec::new(),
+            interop: None,
+            paused: false,
+            step_pending: false,
+        }
+    }
+
+    /// Stop running fixed updates and physics. \
+    /// Frames continue to update and render.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.step_pending = false;
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Run exactly one fixed update and physics step
+    /// on the next frame, while paused
+    pub fn step_once(&mut self) {
+        debug_assert!(self.paused);
+        self.step_pending = true;
+    }
+
+    /// Returns true if a single step was requested, consuming it
+    pub(crate) fn take_step(&mut self) -> bool {
+        let pending = self.step_pending;
+        self.step_pending = false;
+        pending
+    }
+
+    /// Returns top and bottom background colors for this frame
+    pub fn clear_colors(&self) -> (graphics::Color, graphics::Color) {
+        self.clear_gradient.unwrap_or((self.clear_color, self.clear_color))
+    }
+
+    /// Returns fog blend (0 - 1) for a surface at view depth `depth`,
+    /// matching the model fragment shader
+    pub fn fog_factor(&self, depth: f32) -> f32 {
+        if self.fog_end <= self.fog_start {
+            return 0.0;
+        }
+
+        ((depth - self.fog_start) / (self.fog_end - self.fog_start))
+            .max(0.0).min(1.0)
+    }
+
+    /// Returns the passes used to draw models this frame, in order
+    pub fn mesh_passes(&self) -> &'static [MeshPass] {
+        if self.depth_prepass {
+            &[MeshPass::Depth, MeshPass::ColorEqual]
+        } else {
+            &[MeshPass::Color]
+        }
+    }
+}
+
+/// Destination image for a camera
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RenderTarget {
+    Swapchain,
+    Offscreen { width: u32, height: u32 }, // Color texture of given size
+}
+
+impl Default for RenderTarget {
+    fn default() -> RenderTarget {
+        RenderTarget::Swapchain
+    }
+}
+
+impl RenderTarget {
+    /// Returns target dimensions, given the current screen dimensions
+    pub fn size(self, screen: ::ScreenData) -> (u32, u32) {
+        match self {
+            RenderTarget::Swapchain => (screen.width, screen.height),
+            RenderTarget::Offscreen { width, height } => (width, height),
+        }
+    }
+}
+
+/// Model rendering pass, each with its own pipeline
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MeshPass {
+    Color,      // Shaded, with standard depth testing
+    Depth,      // Depth only, no color writes
+    ColorEqual, // Shaded, only where depth matches the prepass
+}
+
+const MESH_PASSES: [MeshPass; 3] = [
+    MeshPass::Color,
+    MeshPass::Depth,
+    MeshPass::ColorEqual,
+];
+
+/// Image with its own device memory and a view
+struct Attachment {
+    view:   vd::ImageView, // Destroyed before the image
+    image:  vd::Image,
+    memory: vd::DeviceMemoryHandle,
+}
+
+impl Attachment {
+    unsafe fn free(self, device: &vd::Device) {
+        let memory = self.memory;
+
+        drop(self); // Destroy the view and image first
+        device.free_memory(memory, None);
+    }
+}
+
+/// Color image rendered by an offscreen camera and sampled afterwards
+struct OffscreenTarget {
+    width:        u32,
+    height:       u32,
+    framebuffer:  vd::Framebuffer,
+    sampler:      vd::Sampler,
+    color:        Attachment, // Left in the shader read-only layout
+    depth:        Attachment,
+    multisampled: Option<Attachment>, // Resolved into `color`
+}
+
+impl OffscreenTarget {
+    unsafe fn free(self, device: &vd::Device) {
+        let OffscreenTarget {
+            framebuffer,
+            sampler,
+            color,
+            depth,
+            multisampled,
+            ..
+        } = self;
+
+        // Framebuffer references the attachment views
+        drop(framebuffer);
+        drop(sampler);
+
+        color.free(device);
+        depth.free(device);
+
+        if let Some(multisampled) = multisampled {
+            multisampled.free(device);
         }
     }
 }
 
+#[allow(dead_code)]
+struct BackgroundData {
+    buffer: vd::BufferHandle,
+    memory: vd::DeviceMemoryHandle,
+    pipeline: vd::GraphicsPipeline,
+    _vert: vd::ShaderModule,
+    _frag: vd::ShaderModule,
+}
+
+struct PointData {
+    buffer: vd::BufferHandle,
+    memory: vd::DeviceMemoryHandle,
+    capacity: u64, // In points
+    pipeline: vd::GraphicsPipeline,
+    _vert: vd::ShaderModule,
+    _frag: vd::ShaderModule,
+}
+
 #[allow(dead_code)]
 struct DebugData {
     buffer: vd::BufferHandle,
     memory: vd::DeviceMemoryHandle,
+    capacity: u64, // In lines
     pipeline: vd::GraphicsPipeline,
     _vert: vd::ShaderModule,
     _frag: vd::ShaderModule,
@@ -964,6 +1988,21 @@ struct DebugData {
 #[derive(Copy, Clone, PartialEq)]
 pub enum NormalMode { Flat, Smooth }
 
+/// Width of the indices uploaded for a model
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum IndexType { U16, U32 }
+
+impl IndexType {
+    /// Narrowest index type able to address `vertex_count` vertices
+    pub fn for_vertex_count(vertex_count: usize) -> IndexType {
+        if vertex_count <= u16::max_value() as usize + 1 {
+            IndexType::U16
+        } else {
+            IndexType::U32
+        }
+    }
+}
+
 /// Raw model data structure
 #[derive(Clone)]
 pub struct ModelData {
@@ -971,6 +2010,9 @@ pub struct ModelData {
     pub computed_normals: bool,
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
+    index_type: IndexType,
+    bounds: (alg::Vec3, alg::Vec3), // Local AABB as (min, max)
+    sphere: (alg::Vec3, f32), // Local bounding sphere as (center, radius)
 }
 
 impl ModelData {
@@ -1020,11 +2062,18 @@ impl ModelData {
             }
         }
 
+        let index_type = IndexType::for_vertex_count(vertices.len());
+        let bounds = ModelData::compute_bounds(&vertices);
+        let sphere = ModelData::compute_sphere(&vertices);
+
         ModelData {
             name: name.to_string(),
             computed_normals: true,
             vertices,
             indices,
+            index_type,
+            bounds,
+            sphere,
         }
     }
 
@@ -1034,79 +2083,378 @@ impl ModelData {
         vertices: Vec<Vertex>,
         indices: Vec<u32>,
     ) -> ModelData {
+        let index_type = IndexType::for_vertex_count(vertices.len());
+        let bounds = ModelData::compute_bounds(&vertices);
+        let sphere = ModelData::compute_sphere(&vertices);
+
         ModelData {
             name: name.to_string(),
             computed_normals: false,
             vertices,
             indices,
+            index_type,
+            bounds,
+            sphere,
         }
     }
-}
 
-/// Model reference values used at runtime
-pub struct Model {
-    index_count: u32,
-    index_offset: u32,
-    vertex_count: usize,
-    vertex_offset: i32,
-}
+    /// Returns local axis-aligned bounding box of model vertices
+    /// as a (min, max) pair.
+    pub fn bounds(&self) -> (alg::Vec3, alg::Vec3) {
+        self.bounds
+    }
 
-impl Model {
-    fn new(
-        index_count: u32,
-        index_offset: u32,
-        vertex_count: usize,
-        vertex_offset: i32,
-    ) -> Model {
-        Model {
-            index_count,
-            index_offset,
-            vertex_count,
-            vertex_offset,
-        }
+    /// Returns local bounding sphere of model vertices
+    /// as tuple of center, radius.
+    pub fn bounding_sphere(&self) -> (alg::Vec3, f32) {
+        self.sphere
     }
-}
 
-/// Dynamic collection of instance data
-pub struct Instances {
-    names: fnv::FnvHashMap<String, usize>,
-    data: Vec<Vec<(InstanceUBO, InstanceMeta)>>,
-}
+    fn compute_bounds(vertices: &[Vertex]) -> (alg::Vec3, alg::Vec3) {
+        if vertices.is_empty() {
+            return (alg::Vec3::zero(), alg::Vec3::zero());
+        }
 
-impl Instances {
-    pub fn new(
-        model_count: usize,
-        model_names: &Vec<String>,
-        hints: Option<&[usize]>,
-    ) -> Instances {
-        debug_assert!(model_count == model_names.len());
-        let mut data = Vec::with_capacity(model_count);
+        vertices.iter().fold(
+            (
+                alg::Vec3::one() * std::f32::MAX,
+                alg::Vec3::one() * std::f32::MIN,
+            ),
+            |(min, max), vertex| {
+                let p = vertex.position;
+                (
+                    alg::Vec3::new(
+                        f32::min(min.x, p.x),
+                        f32::min(min.y, p.y),
+                        f32::min(min.z, p.z),
+                    ),
+                    alg::Vec3::new(
+                        f32::max(max.x, p.x),
+                        f32::max(max.y, p.y),
+                        f32::max(max.z, p.z),
+                    ),
+                )
+            },
+        )
+    }
 
-        match hints {
-            Some(hints) => {
-                assert!(model_count == hints.len());
-                hints.iter().for_each(
-                    |hint| data.push(Vec::with_capacity(*hint))
-                );
-            }
+    /// Ritter's approximate bounding sphere
+    fn compute_sphere(vertices: &[Vertex]) -> (alg::Vec3, f32) {
+        if vertices.is_empty() {
+            return (alg::Vec3::zero(), 0.0);
+        }
 
-            None => {
-                for _ in 0..model_count {
-                    data.push(Vec::new());
-                }
-            }
+        let farthest = |from: alg::Vec3| {
+            vertices.iter().map(|vertex| vertex.position).fold(
+                from,
+                |best, p| if p.dist(from) > best.dist(from) { p } else { best },
+            )
         };
 
-        let mut names = fnv::FnvHashMap::with_capacity_and_hasher(
-            model_count,
-            Default::default(),
-        );
+        // Initial sphere spans an approximately maximal pair
+        let a = farthest(vertices[0].position);
+        let b = farthest(a);
 
-        for (i, name) in model_names.iter().enumerate() {
-            names.insert(name.clone(), i);
-        };
+        let mut center = (a + b) * 0.5;
+        let mut radius = a.dist(b) * 0.5;
 
-        Instances { names, data }
+        // Grow to enclose any outliers
+        for vertex in vertices {
+            let distance = vertex.position.dist(center);
+
+            if distance > radius {
+                let new_radius = (radius + distance) * 0.5;
+                let direction = (vertex.position - center) / distance;
+
+                center = center + direction * (new_radius - radius);
+                radius = new_radius;
+            }
+        }
+
+        (center, radius)
+    }
+
+    /// Merge duplicate vertices (within a small tolerance on every
+    /// attribute) and rewrite the indices to reference the merged set. \
+    /// Vertices with distinct normals, e.g. at flat-shaded edges,
+    /// or distinct bone data are kept apart.
+    /// Returns the number of vertices removed.
+    pub fn optimize(&mut self) -> usize {
+        let quantize = |value: f32| {
+            (value / VERTEX_MERGE_EPSILON).round() as i64
+        };
+
+        // Skinning data must match exactly
+        let key = |vertex: &Vertex| [
+            quantize(vertex.position.x),
+            quantize(vertex.position.y),
+            quantize(vertex.position.z),
+            quantize(vertex.normal.x),
+            quantize(vertex.normal.y),
+            quantize(vertex.normal.z),
+            quantize(vertex.color.r),
+            quantize(vertex.color.g),
+            quantize(vertex.color.b),
+            quantize(vertex.uv.x),
+            quantize(vertex.uv.y),
+            vertex.bone_indices[0] as i64,
+            vertex.bone_indices[1] as i64,
+            vertex.bone_indices[2] as i64,
+            vertex.bone_indices[3] as i64,
+            vertex.bone_weights[0].to_bits() as i64,
+            vertex.bone_weights[1].to_bits() as i64,
+            vertex.bone_weights[2].to_bits() as i64,
+            vertex.bone_weights[3].to_bits() as i64,
+        ];
+
+        let mut merged = Vec::with_capacity(self.vertices.len());
+        let mut remap = Vec::with_capacity(self.vertices.len());
+        let mut lookup = fnv::FnvHashMap::default();
+
+        for vertex in &self.vertices {
+            let index = *lookup.entry(key(vertex)).or_insert_with(|| {
+                merged.push(*vertex);
+                merged.len() as u32 - 1
+            });
+
+            remap.push(index);
+        }
+
+        for index in &mut self.indices {
+            *index = remap[*index as usize];
+        }
+
+        let removed = self.vertices.len() - merged.len();
+        self.vertices = merged;
+
+        // Fewer vertices may fit a narrower index type
+        if self.index_type == IndexType::U32 {
+            self.index_type = IndexType::for_vertex_count(self.vertices.len());
+        }
+
+        removed
+    }
+
+    /// Index type selected for upload
+    pub fn index_type(&self) -> IndexType {
+        self.index_type
+    }
+
+    /// Override the automatically selected index type,
+    /// e.g. to keep 32-bit indices for a mesh that will grow
+    pub fn with_index_type(mut self, index_type: IndexType) -> ModelData {
+        #[cfg(debug_assertions)] {
+            if index_type == IndexType::U16
+                && index_type != IndexType::for_vertex_count(
+                    self.vertices.len()
+                )
+            {
+                panic!(
+                    "Model \"{}\" has too many vertices for 16-bit indices",
+                    self.name,
+                );
+            }
+        }
+
+        self.index_type = index_type;
+        self
+    }
+
+    /// Convert vertex data between world coordinate conventions,
+    /// e.g. for meshes authored in Z-up tools. \
+    /// Preserves front faces when the handedness changes.
+    pub fn convert_axes(&mut self, from: alg::Axes, to: alg::Axes) {
+        let basis = from.convert(to);
+
+        for vertex in &mut self.vertices {
+            vertex.position = basis * vertex.position;
+            vertex.normal = basis * vertex.normal;
+        }
+
+        self.bounds = ModelData::compute_bounds(&self.vertices);
+        self.sphere = ModelData::compute_sphere(&self.vertices);
+
+        // Mirroring reverses the winding order
+        if basis.det() < 0.0 {
+            for triangle in self.indices.chunks_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+    }
+}
+
+/// Incremental `ModelData` construction. \
+/// Triangles are checked against the vertices added so far.
+pub struct ModelBuilder {
+    name: String,
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    normals: Option<NormalMode>, // Keep vertex normals when unset
+}
+
+impl ModelBuilder {
+    pub fn new(name: &str) -> ModelBuilder {
+        ModelBuilder {
+            name: name.to_string(),
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            normals: None,
+        }
+    }
+
+    pub fn name(&mut self, name: &str) -> &mut ModelBuilder {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Append vertex, addressed by triangles at the next index
+    /// (see `vertex_count()`)
+    pub fn add_vertex(&mut self, vertex: Vertex) -> &mut ModelBuilder {
+        self.vertices.push(vertex);
+        self
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Append triangle referencing previously added vertices
+    pub fn add_triangle(
+        &mut self,
+        a: u32,
+        b: u32,
+        c: u32,
+    ) -> &mut ModelBuilder {
+        #[cfg(debug_assertions)] {
+            let count = self.vertices.len();
+
+            for &index in &[a, b, c] {
+                if index as usize >= count {
+                    panic!(
+                        "Model \"{}\" triangle index {} is out of range \
+                        ({} vertices)",
+                        self.name,
+                        index,
+                        count,
+                    );
+                }
+            }
+        }
+
+        self.indices.extend_from_slice(&[a, b, c]);
+        self
+    }
+
+    /// Compute normals on build
+    pub fn normals(&mut self, mode: NormalMode) -> &mut ModelBuilder {
+        self.normals = Some(mode);
+        self
+    }
+
+    /// Finalize model. \
+    /// Without any triangles, the vertices are treated as a soup
+    /// and indexed in order, three per triangle.
+    pub fn build(&self) -> ModelData {
+        let indices = if self.indices.is_empty() {
+            debug_assert!(self.vertices.len() % 3 == 0);
+            (0..self.vertices.len() as u32).collect()
+        } else {
+            self.indices.clone()
+        };
+
+        match self.normals {
+            Some(mode) => ModelData::new_with_normals(
+                &self.name,
+                self.vertices.clone(),
+                indices,
+                mode,
+            ),
+            None => ModelData::new(&self.name, self.vertices.clone(), indices),
+        }
+    }
+}
+
+/// Model reference values used at runtime
+pub struct Model {
+    index_count: u32,
+    index_offset: u32,
+    index_bound: u32, // Largest referenced index, plus one
+    vertex_count: usize, // Reserved in the vertex buffer
+    vertex_offset: i32,
+}
+
+impl Model {
+    fn new(
+        index_count: u32,
+        index_offset: u32,
+        index_bound: u32,
+        vertex_count: usize,
+        vertex_offset: i32,
+    ) -> Model {
+        Model {
+            index_count,
+            index_offset,
+            index_bound,
+            vertex_count,
+            vertex_offset,
+        }
+    }
+
+    /// Check a replacement vertex count against the model indices. \
+    /// Returns whether the vertex buffer must grow to fit.
+    fn check_vertices(&self, vertex_count: usize) -> Result<bool, String> {
+        if vertex_count < self.index_bound as usize {
+            return Err(format!(
+                "{} vertices given for a model indexing {}",
+                vertex_count,
+                self.index_bound,
+            ));
+        }
+
+        Ok(vertex_count > self.vertex_count)
+    }
+}
+
+/// Dynamic collection of instance data
+pub struct Instances {
+    names: fnv::FnvHashMap<String, usize>,
+    data: Vec<Vec<(InstanceUBO, InstanceMeta)>>,
+}
+
+impl Instances {
+    pub fn new(
+        model_count: usize,
+        model_names: &Vec<String>,
+        hints: Option<&[usize]>,
+    ) -> Instances {
+        debug_assert!(model_count == model_names.len());
+        let mut data = Vec::with_capacity(model_count);
+
+        match hints {
+            Some(hints) => {
+                assert!(model_count == hints.len());
+                hints.iter().for_each(
+                    |hint| data.push(Vec::with_capacity(*hint))
+                );
+            }
+
+            None => {
+                for _ in 0..model_count {
+                    data.push(Vec::new());
+                }
+            }
+        };
+
+        let mut names = fnv::FnvHashMap::with_capacity_and_hasher(
+            model_count,
+            Default::default(),
+        );
+
+        for (i, name) in model_names.iter().enumerate() {
+            names.insert(name.clone(), i);
+        };
+
+        Instances { names, data }
     }
 
     /// Returns model index for given input string
@@ -1147,6 +2495,12 @@ impl Instances {
         self.data[m][i].0 = ubo;
     }
 
+    /// Returns data for an existing instance
+    pub fn get(&self, handle: InstanceHandle) -> &(InstanceUBO, InstanceMeta) {
+        &self.data[handle.model_index() as usize]
+            [handle.instance_index() as usize]
+    }
+
     /// Modify metadata for an existing instance
     pub fn update_meta(&mut self, handle: InstanceHandle, meta: InstanceMeta) {
         let (m, i) = (
@@ -1157,6 +2511,27 @@ impl Instances {
         self.data[m][i].1 = meta;
     }
 
+    /// Tally the model draws submitted for these instances,
+    /// given the index count of each model and the number of passes. \
+    /// Hidden instances are skipped. Does not count lights.
+    pub fn stats(&self, index_counts: &[u32], passes: usize) -> RenderStats {
+        debug_assert!(index_counts.len() == self.data.len());
+
+        let mut stats = RenderStats::default();
+
+        for (model, index_count) in self.data.iter().zip(index_counts) {
+            let visible = model.iter()
+                .filter(|instance| !instance.1.hide)
+                .count() as u32;
+
+            stats.instances += visible;
+            stats.draw_calls += visible * passes as u32;
+            stats.triangles += visible * index_count / 3;
+        }
+
+        stats
+    }
+
     /// Count instances (linear time)
     pub fn count(&self) -> usize {
         let mut count = 0;
@@ -1169,6 +2544,15 @@ impl Instances {
     }
 }
 
+/// Rendering totals for a single frame
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct RenderStats {
+    pub draw_calls: u32, // Model draws, over all mesh passes
+    pub triangles: u32, // Shaded triangles, counted once per instance
+    pub instances: u32, // Visible model instances
+    pub lights_culled: u32, // Lights rejected, summed over instances
+}
+
 #[derive(Clone, Copy)]
 pub struct InstanceHandle {
     _value: u32,
@@ -1235,8 +2619,14 @@ pub struct Vertex {
     pub normal: alg::Vec3,
     pub color: graphics::Color,
     pub uv: alg::Vec2,
+    pub bone_indices: [u8; 4], // Reserved for skinning
+    pub bone_weights: [f32; 4], // Sum to one
 }
 
+// Full weight on the first bone
+const BONE_INDICES_DEFAULT: [u8; 4] = [0; 4];
+const BONE_WEIGHTS_DEFAULT: [f32; 4] = [1.0, 0.0, 0.0, 0.0];
+
 impl Vertex {
     pub fn zero() -> Vertex {
         Vertex {
@@ -1244,6 +2634,8 @@ impl Vertex {
             normal: alg::Vec3::zero(),
             color: graphics::Color::black(),
             uv: alg::Vec2::zero(),
+            bone_indices: BONE_INDICES_DEFAULT,
+            bone_weights: BONE_WEIGHTS_DEFAULT,
         }
     }
 
@@ -1258,6 +2650,8 @@ impl Vertex {
             normal: alg::Vec3::new(nx, ny, nz),
             color: graphics::Color::new(r, g, b),
             uv: alg::Vec2::new(u, v),
+            bone_indices: BONE_INDICES_DEFAULT,
+            bone_weights: BONE_WEIGHTS_DEFAULT,
         }
     }
 
@@ -1272,6 +2666,28 @@ impl Vertex {
         }
     }
 
+    /// Bind vertex to up to four bones. \
+    /// Weights are normalized to sum to one.
+    pub fn with_bones(mut self, indices: [u8; 4], weights: [f32; 4]) -> Vertex {
+        let sum: f32 = weights.iter().sum();
+
+        #[cfg(debug_assertions)] {
+            if weights.iter().any(|weight| *weight < 0.0) || sum <= 0.0 {
+                panic!("Invalid bone weights {:?}", weights);
+            }
+        }
+
+        self.bone_indices = indices;
+        self.bone_weights = [
+            weights[0] / sum,
+            weights[1] / sum,
+            weights[2] / sum,
+            weights[3] / sum,
+        ];
+
+        self
+    }
+
     fn binding_description() -> vd::VertexInputBindingDescription {
         vd::VertexInputBindingDescription::builder()
             .binding(0)
@@ -1280,7 +2696,7 @@ impl Vertex {
             .build()
     }
 
-    fn attribute_descriptions() -> [vd::VertexInputAttributeDescription; 4] {
+    fn attribute_descriptions() -> [vd::VertexInputAttributeDescription; 6] {
         [
             vd::VertexInputAttributeDescription::builder()
                 .binding(0)
@@ -1306,6 +2722,18 @@ impl Vertex {
                 .format(vd::Format::R32G32Sfloat)
                 .offset(offset_of!(Vertex, uv))
                 .build(),
+            vd::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(4)
+                .format(vd::Format::R8G8B8A8Uint)
+                .offset(offset_of!(Vertex, bone_indices))
+                .build(),
+            vd::VertexInputAttributeDescription::builder()
+                .binding(0)
+                .location(5)
+                .format(vd::Format::R32G32B32A32Sfloat)
+                .offset(offset_of!(Vertex, bone_weights))
+                .build(),
         ]
     }
 }
@@ -1319,6 +2747,8 @@ impl Default for Vertex {
             normal: alg::Vec3::zero(),
             color: graphics::Color::white(),
             uv: alg::Vec2::zero(),
+            bone_indices: BONE_INDICES_DEFAULT,
+            bone_weights: BONE_WEIGHTS_DEFAULT,
         }
     }
 }
@@ -1326,8 +2756,8 @@ impl Default for Vertex {
 #[cfg(debug_assertions)]
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct DebugLine {
-    start: Vertex,
-    end: Vertex,
+    pub start: Vertex,
+    pub end: Vertex,
 }
 
 #[cfg(debug_assertions)]
@@ -1347,6 +2777,43 @@ impl DebugLine {
     }
 }
 
+/// Camera-facing square of `size` world units, e.g. a particle or star
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Point {
+    pub position: alg::Vec3,
+    pub color: graphics::Color,
+    pub size: f32,
+}
+
+impl Point {
+    pub fn new(
+        position: alg::Vec3,
+        color: graphics::Color,
+        size: f32,
+    ) -> Point {
+        Point { position, color, size }
+    }
+
+    /// Returns two triangles at the point center. \
+    /// The view-space corner offset is stored in the normal;
+    /// the point vertex shader expands it to face the camera.
+    pub fn vertices(&self) -> [Vertex; 6] {
+        let half = 0.5 * self.size;
+
+        let corner = |x: f32, y: f32| Vertex {
+            position: self.position,
+            normal: alg::Vec3::new(x * half, y * half, 0.0),
+            color: self.color,
+            .. Default::default()
+        };
+
+        [
+            corner(-1.0, -1.0), corner(1.0, -1.0), corner(1.0, 1.0),
+            corner(1.0, 1.0), corner(-1.0, 1.0), corner(-1.0, -1.0),
+        ]
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 #[repr(C)]
 pub struct PaddedVec3 {
@@ -1375,6 +2842,10 @@ impl Default for PaddedVec3 {
 pub struct SharedUBO {
     view:       alg::Mat4,
     projection: alg::Mat4,
+    fog_color:  graphics::Color,
+    fog_start:  f32,
+    fog_end:    f32,
+    _pad:       [f32; 3], // std140 block size
 }
 
 impl SharedUBO {
@@ -1382,8 +2853,30 @@ impl SharedUBO {
         SharedUBO {
             view,
             projection,
+            fog_color: graphics::Color::black(),
+            fog_start: 0.0,
+            fog_end: 0.0, // Disabled
+            _pad: [0.0; 3],
+        }
+    }
+
+    /// Returns copy with the fog settings from `parameters`
+    pub fn with_fog(self, parameters: &Parameters) -> SharedUBO {
+        SharedUBO {
+            fog_color: parameters.fog_color,
+            fog_start: parameters.fog_start,
+            fog_end: parameters.fog_end,
+            .. self
         }
     }
+
+    pub fn view(&self) -> alg::Mat4 {
+        self.view
+    }
+
+    pub fn projection(&self) -> alg::Mat4 {
+        self.projection
+    }
 }
 
 /// Uniform data sent to each individual instance
@@ -1415,6 +2908,10 @@ impl InstanceUBO {
             base_vertex: 0, // Set internally
         }
     }
+
+    pub fn model(&self) -> alg::Mat4 {
+        self.model
+    }
 }
 
 impl Default for InstanceUBO {
@@ -1749,19 +3246,462 @@ fn get_swapchain_details(
         return Err("no valid surface format".into())
     }
 
-    if present_modes.is_empty() {
-        return Err("no valid present mode".into())
-    }
+    if present_modes.is_empty() {
+        return Err("no valid present mode".into())
+    }
+
+    Ok((formats.into_vec(), present_modes.into_vec()))
+}
+
+/// Load base vertex and fragment shaders
+fn load_shaders<'a>(device: vd::Device) -> vd::Result<(
+    vd::ShaderModule,
+    vd::ShaderModule,
+    [vd::PipelineShaderStageCreateInfo<'a>; 2],
+)> {
+    let path = {
+        let mut path = &config::load_section_setting::<String>(
+            &config::ENGINE_CONFIG,
+            "settings",
+            "shader_path"
+        );
+
+        [path, "/"].concat()
+    };
+
+    println!("Loading shaders from \"{}\"", path);
+
+    // The shader build records its light count next to the binaries
+    let lights = std::fs::read_to_string(
+        format!("{}{}", path, "max_instance_lights")
+    ).ok().and_then(|lights| lights.trim().parse::<usize>().ok());
+
+    if lights != Some(MAX_INSTANCE_LIGHTS) {
+        return Err(format!(
+            "Shaders were built for {} lights per instance, expected {}; \
+            rebuild them with MAX_INSTANCE_LIGHTS={}",
+            lights.map_or("an unknown number of".to_string(), |lights| {
+                lights.to_string()
+            }),
+            MAX_INSTANCE_LIGHTS,
+            MAX_INSTANCE_LIGHTS,
+        ).into());
+    }
+
+    let vert_buffer = vd::util::read_spir_v_file(
+        format!("{}{}", path, "base_vert.spv")
+    )?;
+
+    let frag_buffer = vd::util::read_spir_v_file(
+        format!("{}{}", path, "base_frag.spv")
+    )?;
+
+    let vert_mod = vd::ShaderModule::new(device.clone(), &vert_buffer)?;
+    let frag_mod = vd::ShaderModule::new(device, &frag_buffer)?;
+
+    let main = std::ffi::CStr::from_bytes_with_nul(b"main\0").unwrap();
+
+    let vert_stage = vd::PipelineShaderStageCreateInfo::builder()
+        .stage(vd::ShaderStageFlags::VERTEX)
+        .module(&vert_mod)
+        .name(main)
+        .build();
+
+    let frag_stage = vd::PipelineShaderStageCreateInfo::builder()
+        .stage(vd::ShaderStageFlags::FRAGMENT)
+        .module(&frag_mod)
+        .name(main)
+        .build();
+
+    Ok((
+        vert_mod,
+        frag_mod,
+        [vert_stage, frag_stage],
+    ))
+}
+
+/// Convert model data to concatenated vertex and index buffers
+fn load_models(
+    model_data: Vec<ModelData>,
+    device: &vd::Device,
+    transient_pool: &vd::CommandPool,
+    graphics_family: u32,
+) -> vd::Result<(
+    vd::BufferHandle,
+    vd::DeviceMemoryHandle,
+    vd::BufferHandle,
+    vd::DeviceMemoryHandle,
+    vd::IndexType,
+    Vec<Model>,
+    Vec<String>,
+)> {
+    /* If there's no model data, make up some
+     * (really only useful for debugging purposes).
+     */
+
+    let model_data = if model_data.is_empty() {
+        vec![ModelData::new("", vec![Vertex::zero()], vec![0])]
+    } else { model_data };
+
+    // Models share one index buffer, so narrow indices only if all fit
+    let narrow = model_data.iter()
+        .all(|data| data.index_type == IndexType::U16);
+
+    /* Concatenate model data */
+
+    let (vertices_len, indices_len) = {
+        let mut i = 0usize;
+        let mut j = 0usize;
+
+        for data in &model_data {
+            i += data.vertices.len();
+            j += data.indices.len();
+        }
+
+        (i, j)
+    };
+
+    let (vertices, indices, models, names) = {
+        let mut vertices = Vec::with_capacity(vertices_len);
+        let mut indices = Vec::with_capacity(indices_len);
+        let mut models = Vec::with_capacity(model_data.len());
+        let mut names = Vec::with_capacity(model_data.len());
+
+        let mut index_offset = 0;
+        let mut vertex_offset = 0;
+
+        for mut data in model_data {
+            let vertex_count = data.vertices.len();
+            vertices.append(&mut data.vertices); // Destructive
+
+            let index_count = data.indices.len() as u32;
+            let index_bound = data.indices.iter()
+                .max()
+                .map_or(0, |index| index + 1);
+
+            indices.append(&mut data.indices); // Destructive
+
+            let model = Model::new(
+                index_count,
+                index_offset,
+                index_bound,
+                vertex_count,
+                vertex_offset,
+            );
+
+            index_offset += model.index_count;
+            vertex_offset += model.vertex_count as i32;
+
+            models.push(model);
+            names.push(data.name);
+        }
+
+        (vertices, indices, models, names)
+    };
+
+    /* Vertex buffer */
+
+    let properties = device.physical_device().memory_properties();
+
+    let (vertex_buffer, vertex_memory) = create_buffers(
+        &vertices,
+        &properties,
+        device,
+        vd::BufferUsageFlags::VERTEX_BUFFER,
+        transient_pool,
+        graphics_family,
+    )?;
+
+    /* Index buffer */
+
+    let (index_buffer, index_memory, index_type) = if narrow {
+        let indices: Vec<u16> = indices.iter()
+            .map(|index| *index as u16)
+            .collect();
+
+        let (buffer, memory) = create_buffers(
+            &indices,
+            &properties,
+            device,
+            vd::BufferUsageFlags::INDEX_BUFFER,
+            transient_pool,
+            graphics_family,
+        )?;
+
+        (buffer, memory, vd::IndexType::Uint16)
+    } else {
+        let (buffer, memory) = create_buffers(
+            &indices,
+            &properties,
+            device,
+            vd::BufferUsageFlags::INDEX_BUFFER,
+            transient_pool,
+            graphics_family,
+        )?;
+
+        (buffer, memory, vd::IndexType::Uint32)
+    };
+
+    Ok((
+        vertex_buffer,
+        vertex_memory,
+        index_buffer,
+        index_memory,
+        index_type,
+        models,
+        names,
+    ))
+}
+
+/// Determine the MSAA sample count from the engine config,
+/// limited to what the device supports for color and depth
+fn init_samples(device: &vd::Device) -> vd::SampleCountFlags {
+    let settings = config::load_section(&config::ENGINE_CONFIG, "settings");
+
+    let requested = match settings.get("msaa_samples") {
+        Some(value) => value.parse::<u32>().unwrap_or_else(
+            |_| panic!("Invalid MSAA sample count \"{}\"", value)
+        ),
+        None => 1,
+    };
+
+    let supported = {
+        let properties = device.physical_device().properties();
+        let limits = properties.limits();
+
+        limits.framebuffer_color_sample_counts()
+            & limits.framebuffer_depth_sample_counts()
+    };
+
+    let samples = select_samples(requested, supported.bits());
+    println!("MSAA samples: {}", samples);
+
+    vd::SampleCountFlags::from_bits_truncate(samples)
+}
+
+/// Returns the largest supported sample count not exceeding `requested`. \
+/// `supported` is a mask of supported counts (each a power of two).
+fn select_samples(requested: u32, supported: u32) -> u32 {
+    debug_assert!(supported & 1 != 0); // Single sample is always valid
+
+    // Largest power of two in 1..8 not exceeding the request
+    let mut samples = 8;
+    while samples > 1 && samples > requested {
+        samples >>= 1;
+    }
+
+    while samples > 1 && supported & samples == 0 {
+        samples >>= 1;
+    }
+
+    if samples != requested.max(1) {
+        eprintln!(
+            "Warning: {}x MSAA is not supported; falling back to {}x",
+            requested,
+            samples,
+        );
+    }
+
+    samples
+}
+
+/// Swap the red and blue channels of packed four-byte pixels in place
+fn swizzle_bgra(pixels: &mut [u8]) {
+    debug_assert!(pixels.len() % 4 == 0);
+
+    for pixel in pixels.chunks_mut(4) {
+        pixel.swap(0, 2);
+    }
+}
+
+/// Create fullscreen background gradient pipeline and vertex buffer
+fn init_background(
+    swapchain: &vd::SwapchainKhr,
+    render_pass: &vd::RenderPass,
+    pipeline_layout: &vd::PipelineLayout,
+    samples: vd::SampleCountFlags,
+    device: &vd::Device,
+) -> vd::Result<BackgroundData> {
+    let properties = device.physical_device().memory_properties();
+
+    // Two triangles
+    let (buffer, memory) = create_buffer(
+        6 * std::mem::size_of::<Vertex>() as u64,
+        vd::BufferUsageFlags::VERTEX_BUFFER,
+        device,
+        vd::MemoryPropertyFlags::HOST_VISIBLE,
+        &properties,
+    )?;
+
+    /* Load background shaders */
+
+    let path = {
+        let mut path = &config::load_section_setting::<String>(
+            &config::ENGINE_CONFIG,
+            "settings",
+            "shader_path"
+        );
+
+        [path, "/"].concat()
+    };
+
+    let vert_buffer = vd::util::read_spir_v_file(
+        format!("{}{}", path, "background_vert.spv")
+    )?;
+
+    let frag_buffer = vd::util::read_spir_v_file(
+        format!("{}{}", path, "background_frag.spv")
+    )?;
+
+    let vert_mod = vd::ShaderModule::new(device.clone(), &vert_buffer)?;
+    let frag_mod = vd::ShaderModule::new(device.clone(), &frag_buffer)?;
+
+    let main = std::ffi::CStr::from_bytes_with_nul(b"main\0").unwrap();
+
+    let vert_stage = vd::PipelineShaderStageCreateInfo::builder()
+        .stage(vd::ShaderStageFlags::VERTEX)
+        .module(&vert_mod)
+        .name(main)
+        .build();
+
+    let frag_stage = vd::PipelineShaderStageCreateInfo::builder()
+        .stage(vd::ShaderStageFlags::FRAGMENT)
+        .module(&frag_mod)
+        .name(main)
+        .build();
+
+    /* Create background pipeline */
+
+    let assembly = vd::PipelineInputAssemblyStateCreateInfo::builder()
+        .topology(vd::PrimitiveTopology::TriangleList)
+        .primitive_restart_enable(false)
+        .build();
+
+    let rasterizer = vd::PipelineRasterizationStateCreateInfo::builder()
+        .depth_clamp_enable(false)
+        .rasterizer_discard_enable(false)
+        .polygon_mode(vd::PolygonMode::Fill)
+        .cull_mode(vd::CullModeFlags::NONE)
+        .depth_bias_enable(false)
+        .line_width(1f32)
+        .build();
+
+    let multisampling = vd::PipelineMultisampleStateCreateInfo::builder()
+        .rasterization_samples(samples)
+        .sample_shading_enable(false)
+        .min_sample_shading(1f32)
+        .alpha_to_coverage_enable(false)
+        .alpha_to_one_enable(false)
+        .build();
+
+    let binding_description = [Vertex::binding_description()];
+    let attribute_descriptions = Vertex::attribute_descriptions();
+
+    let vert_info = vd::PipelineVertexInputStateCreateInfo::builder()
+        .vertex_binding_descriptions(&binding_description)
+        .vertex_attribute_descriptions(&attribute_descriptions)
+        .build();
+
+    // Don't blend
+    let attachments = [
+        vd::PipelineColorBlendAttachmentState::builder()
+            .blend_enable(false)
+            .color_write_mask(
+                  vd::ColorComponentFlags::R
+                | vd::ColorComponentFlags::G
+                | vd::ColorComponentFlags::B
+            ).build()
+    ];
+
+    let blending = vd::PipelineColorBlendStateCreateInfo::builder()
+        .logic_op_enable(false)
+        .attachments(&attachments)
+        .blend_constants([0f32; 4])
+        .build();
+
+    // Leave the cleared depth untouched for geometry
+    let stencil = vd::PipelineDepthStencilStateCreateInfo::builder()
+        .depth_test_enable(false)
+        .depth_write_enable(false)
+        .depth_compare_op(vd::CompareOp::Never)
+        .depth_bounds_test_enable(false)
+        .stencil_test_enable(false)
+        .build();
+
+    let viewports = [
+        vd::Viewport::builder()
+            .x(0f32)
+            .y(0f32)
+            .width(swapchain.extent().width() as f32)
+            .height(swapchain.extent().height() as f32)
+            .min_depth(0f32)
+            .max_depth(1f32)
+            .build()
+    ];
+
+    let scissors = [
+        vd::Rect2d::builder()
+            .offset(
+                vd::Offset2d::builder()
+                    .x(0)
+                    .y(0)
+                    .build()
+            ).extent(swapchain.extent().clone())
+            .build()
+    ];
+
+    let viewport_state = vd::PipelineViewportStateCreateInfo::builder()
+        .viewports(&viewports)
+        .scissors(&scissors)
+        .build();
+
+    let pipeline = vd::GraphicsPipeline::builder()
+        .stages(&[vert_stage, frag_stage])
+        .vertex_input_state(&vert_info)
+        .input_assembly_state(&assembly)
+        .viewport_state(&viewport_state)
+        .rasterization_state(&rasterizer)
+        .multisample_state(&multisampling)
+        .color_blend_state(&blending)
+        .depth_stencil_state(&stencil)
+        .layout(pipeline_layout)
+        .render_pass(render_pass)
+        .subpass(0)
+        .base_pipeline_index(-1)
+        .build(device.clone())?;
+
+    Ok(BackgroundData {
+        buffer,
+        memory,
+        pipeline,
+        _vert: vert_mod,
+        _frag: frag_mod,
+    })
+}
+
+#[cfg(not(debug_assertions))]
+#[allow(unused_variables)]
+fn init_debug(
+    swapchain: &vd::SwapchainKhr,
+    render_pass: &vd::RenderPass,
+    pipeline_layout: &vd::PipelineLayout,
+    samples: vd::SampleCountFlags,
+    device: &vd::Device,
+) -> vd::Result<Option<DebugData>> { Ok(None) }
 
-    Ok((formats.into_vec(), present_modes.into_vec()))
-}
+#[cfg(debug_assertions)]
+fn init_debug(
+    swapchain: &vd::SwapchainKhr,
+    render_pass: &vd::RenderPass,
+    pipeline_layout: &vd::PipelineLayout,
+    samples: vd::SampleCountFlags,
+    device: &vd::Device,
+) -> vd::Result<Option<DebugData>> {
+    // Allocate empty debug vertex buffer
+    let (buffer, memory) = create_debug_buffer(INITIAL_DEBUG_LINES, device)?;
+
+    /* Load debug shaders */
 
-/// Load base vertex and fragment shaders
-fn load_shaders<'a>(device: vd::Device) -> vd::Result<(
-    vd::ShaderModule,
-    vd::ShaderModule,
-    [vd::PipelineShaderStageCreateInfo<'a>; 2],
-)> {
     let path = {
         let mut path = &config::load_section_setting::<String>(
             &config::ENGINE_CONFIG,
@@ -1772,18 +3712,18 @@ fn load_shaders<'a>(device: vd::Device) -> vd::Result<(
         [path, "/"].concat()
     };
 
-    println!("Loading shaders from \"{}\"", path);
+    println!("Loading debug shaders from \"{}\"", path);
 
     let vert_buffer = vd::util::read_spir_v_file(
-        format!("{}{}", path, "base_vert.spv")
+        format!("{}{}", path, "debug_vert.spv")
     )?;
 
     let frag_buffer = vd::util::read_spir_v_file(
-        format!("{}{}", path, "base_frag.spv")
+        format!("{}{}", path, "debug_frag.spv")
     )?;
 
     let vert_mod = vd::ShaderModule::new(device.clone(), &vert_buffer)?;
-    let frag_mod = vd::ShaderModule::new(device, &frag_buffer)?;
+    let frag_mod = vd::ShaderModule::new(device.clone(), &frag_buffer)?;
 
     let main = std::ffi::CStr::from_bytes_with_nul(b"main\0").unwrap();
 
@@ -1799,144 +3739,129 @@ fn load_shaders<'a>(device: vd::Device) -> vd::Result<(
         .name(main)
         .build();
 
-    Ok((
-        vert_mod,
-        frag_mod,
-        [vert_stage, frag_stage],
-    ))
-}
-
-/// Convert model data to concatenated vertex and index buffers
-fn load_models(
-    model_data: Vec<ModelData>,
-    device: &vd::Device,
-    transient_pool: &vd::CommandPool,
-    graphics_family: u32,
-) -> vd::Result<(
-    vd::BufferHandle,
-    vd::DeviceMemoryHandle,
-    vd::BufferHandle,
-    vd::DeviceMemoryHandle,
-    Vec<Model>,
-    Vec<String>,
-)> {
-    /* If there's no model data, make up some
-     * (really only useful for debugging purposes).
-     */
+    /* Create debug pipeline */
 
-    let model_data = if model_data.is_empty() {
-        vec![ModelData::new("", vec![Vertex::zero()], vec![0])]
-    } else { model_data };
+    let assembly = vd::PipelineInputAssemblyStateCreateInfo::builder()
+        .topology(vd::PrimitiveTopology::LineList) // Render lines
+        .primitive_restart_enable(false)
+        .build();
 
-    /* Concatenate model data */
+    let rasterizer = vd::PipelineRasterizationStateCreateInfo::builder()
+        .depth_clamp_enable(false)
+        .rasterizer_discard_enable(false)
+        .polygon_mode(vd::PolygonMode::Line) // Render lines
+        .cull_mode(vd::CullModeFlags::NONE)
+        .depth_bias_enable(false)
+        .line_width(1f32)
+        .build();
 
-    let (vertices_len, indices_len) = {
-        let mut i = 0usize;
-        let mut j = 0usize;
+    let multisampling = vd::PipelineMultisampleStateCreateInfo::builder()
+        .rasterization_samples(samples)
+        .sample_shading_enable(false)
+        .min_sample_shading(1f32)
+        .alpha_to_coverage_enable(false)
+        .alpha_to_one_enable(false)
+        .build();
 
-        for data in &model_data {
-            i += data.vertices.len();
-            j += data.indices.len();
-        }
+    let binding_description = [Vertex::binding_description()];
+    let attribute_descriptions = Vertex::attribute_descriptions();
 
-        (i, j)
-    };
+    let vert_info = vd::PipelineVertexInputStateCreateInfo::builder()
+        .vertex_binding_descriptions(&binding_description)
+        .vertex_attribute_descriptions(&attribute_descriptions)
+        .build();
 
-    let (vertices, indices, models, names) = {
-        let mut vertices = Vec::with_capacity(vertices_len);
-        let mut indices = Vec::with_capacity(indices_len);
-        let mut models = Vec::with_capacity(model_data.len());
-        let mut names = Vec::with_capacity(model_data.len());
+    // Don't blend
+    let attachments = [
+        vd::PipelineColorBlendAttachmentState::builder()
+            .blend_enable(false)
+            .color_write_mask(
+                  vd::ColorComponentFlags::R
+                | vd::ColorComponentFlags::G
+                | vd::ColorComponentFlags::B
+            ).build()
+    ];
 
-        let mut index_offset = 0;
-        let mut vertex_offset = 0;
+    let blending = vd::PipelineColorBlendStateCreateInfo::builder()
+        .logic_op_enable(false)
+        .attachments(&attachments)
+        .blend_constants([0f32; 4])
+        .build();
 
-        for mut data in model_data {
-            let vertex_count = data.vertices.len();
-            vertices.append(&mut data.vertices); // Destructive
+    // Always draw on top
+    let stencil = vd::PipelineDepthStencilStateCreateInfo::builder()
+        .depth_test_enable(false)
+        .depth_write_enable(false)
+        .depth_compare_op(vd::CompareOp::Never)
+        .depth_bounds_test_enable(false)
+        .stencil_test_enable(false)
+        .build();
 
-            let index_count = data.indices.len() as u32;
-            indices.append(&mut data.indices); // Destructive
+    let viewports = [
+        vd::Viewport::builder()
+            .x(0f32)
+            .y(0f32)
+            .width(swapchain.extent().width() as f32)
+            .height(swapchain.extent().height() as f32)
+            .min_depth(0f32)
+            .max_depth(1f32)
+            .build()
+    ];
 
-            let model = Model::new(
-                index_count,
-                index_offset,
-                vertex_count,
-                vertex_offset,
-            );
+    let scissors = [
+        vd::Rect2d::builder()
+            .offset(
+                vd::Offset2d::builder()
+                    .x(0)
+                    .y(0)
+                    .build()
+            ).extent(swapchain.extent().clone())
+            .build()
+    ];
 
-            index_offset += model.index_count;
-            vertex_offset += model.vertex_count as i32;
+    let viewport_state = vd::PipelineViewportStateCreateInfo::builder()
+        .viewports(&viewports)
+        .scissors(&scissors)
+        .build();
 
-            models.push(model);
-            names.push(data.name);
-        }
+    let pipeline = vd::GraphicsPipeline::builder()
+        .stages(&[vert_stage, frag_stage])
+        .vertex_input_state(&vert_info)
+        .input_assembly_state(&assembly)
+        .viewport_state(&viewport_state)
+        .rasterization_state(&rasterizer)
+        .multisample_state(&multisampling)
+        .color_blend_state(&blending)
+        .depth_stencil_state(&stencil)
+        .layout(pipeline_layout)
+        .render_pass(render_pass)
+        .subpass(0)
+        .base_pipeline_index(-1)
+        .build(device.clone())?;
 
-        (vertices, indices, models, names)
+    let data = DebugData {
+        buffer,
+        memory,
+        capacity: INITIAL_DEBUG_LINES,
+        pipeline,
+        _vert: vert_mod,
+        _frag: frag_mod,
     };
 
-    /* Vertex buffer */
-
-    let properties = device.physical_device().memory_properties();
-
-    let (vertex_buffer, vertex_memory) = create_buffers(
-        &vertices,
-        &properties,
-        device,
-        vd::BufferUsageFlags::VERTEX_BUFFER,
-        transient_pool,
-        graphics_family,
-    )?;
-
-    /* Index buffer */
-
-    let (index_buffer, index_memory) = create_buffers(
-        &indices,
-        &properties,
-        device,
-        vd::BufferUsageFlags::INDEX_BUFFER,
-        transient_pool,
-        graphics_family,
-    )?;
-
-    Ok((
-        vertex_buffer,
-        vertex_memory,
-        index_buffer,
-        index_memory,
-        models,
-        names,
-    ))
+    Ok(Some(data))
 }
 
-#[cfg(not(debug_assertions))]
-#[allow(unused_variables)]
-fn init_debug(
-    swapchain: &vd::SwapchainKhr,
-    render_pass: &vd::RenderPass,
-    pipeline_layout: &vd::PipelineLayout,
-    device: &vd::Device,
-) -> vd::Result<Option<DebugData>> { Ok(None) }
-
-#[cfg(debug_assertions)]
-fn init_debug(
+fn init_points(
     swapchain: &vd::SwapchainKhr,
     render_pass: &vd::RenderPass,
     pipeline_layout: &vd::PipelineLayout,
+    samples: vd::SampleCountFlags,
     device: &vd::Device,
-) -> vd::Result<Option<DebugData>> {
-    let properties = device.physical_device().memory_properties();
-
-    // Allocate empty debug vertex buffer
-    let (buffer, memory) = create_buffer(
-        MAX_DEBUG_LINES * 2 * std::mem::size_of::<Vertex>() as u64,
-        vd::BufferUsageFlags::VERTEX_BUFFER,
-        device,
-        vd::MemoryPropertyFlags::HOST_VISIBLE,
-        &properties,
-    )?;
+) -> vd::Result<PointData> {
+    // Allocate empty point vertex buffer
+    let (buffer, memory) = create_point_buffer(INITIAL_POINTS, device)?;
 
-    /* Load debug shaders */
+    /* Load point shaders; fragments are shaded like debug lines */
 
     let path = {
         let mut path = &config::load_section_setting::<String>(
@@ -1948,10 +3873,10 @@ fn init_debug(
         [path, "/"].concat()
     };
 
-    println!("Loading debug shaders from \"{}\"", path);
+    println!("Loading point shaders from \"{}\"", path);
 
     let vert_buffer = vd::util::read_spir_v_file(
-        format!("{}{}", path, "debug_vert.spv")
+        format!("{}{}", path, "point_vert.spv")
     )?;
 
     let frag_buffer = vd::util::read_spir_v_file(
@@ -1975,24 +3900,25 @@ fn init_debug(
         .name(main)
         .build();
 
-    /* Create debug pipeline */
+    /* Create point pipeline */
 
     let assembly = vd::PipelineInputAssemblyStateCreateInfo::builder()
-        .topology(vd::PrimitiveTopology::LineList) // Render lines
+        .topology(vd::PrimitiveTopology::TriangleList)
         .primitive_restart_enable(false)
         .build();
 
+    // Quads always face the camera
     let rasterizer = vd::PipelineRasterizationStateCreateInfo::builder()
         .depth_clamp_enable(false)
         .rasterizer_discard_enable(false)
-        .polygon_mode(vd::PolygonMode::Line) // Render lines
+        .polygon_mode(vd::PolygonMode::Fill)
         .cull_mode(vd::CullModeFlags::NONE)
         .depth_bias_enable(false)
         .line_width(1f32)
         .build();
 
     let multisampling = vd::PipelineMultisampleStateCreateInfo::builder()
-        .rasterization_samples(vd::SampleCountFlags::COUNT_1)
+        .rasterization_samples(samples)
         .sample_shading_enable(false)
         .min_sample_shading(1f32)
         .alpha_to_coverage_enable(false)
@@ -2024,11 +3950,11 @@ fn init_debug(
         .blend_constants([0f32; 4])
         .build();
 
-    // Always draw on top
+    // Occluded by models, but do not occlude each other
     let stencil = vd::PipelineDepthStencilStateCreateInfo::builder()
-        .depth_test_enable(false)
+        .depth_test_enable(true)
         .depth_write_enable(false)
-        .depth_compare_op(vd::CompareOp::Never)
+        .depth_compare_op(vd::CompareOp::Less)
         .depth_bounds_test_enable(false)
         .stencil_test_enable(false)
         .build();
@@ -2075,19 +4001,56 @@ fn init_debug(
         .base_pipeline_index(-1)
         .build(device.clone())?;
 
-    let data = DebugData {
-        buffer,
-        memory,
-        pipeline,
-        _vert: vert_mod,
-        _frag: frag_mod,
-    };
+    Ok(
+        PointData {
+            buffer,
+            memory,
+            capacity: INITIAL_POINTS,
+            pipeline,
+            _vert: vert_mod,
+            _frag: frag_mod,
+        }
+    )
+}
+
+/// Allocate host-visible vertex buffer for `points` points
+fn create_point_buffer(
+    points: u64,
+    device: &vd::Device,
+) -> vd::Result<(vd::BufferHandle, vd::DeviceMemoryHandle)> {
+    let properties = device.physical_device().memory_properties();
 
-    Ok(Some(data))
+    create_buffer(
+        points * 6 * std::mem::size_of::<Vertex>() as u64,
+        vd::BufferUsageFlags::VERTEX_BUFFER,
+        device,
+        vd::MemoryPropertyFlags::HOST_VISIBLE,
+        &properties,
+    )
+}
+
+/// Allocate host-visible vertex buffer for `lines` debug lines
+#[cfg(debug_assertions)]
+fn create_debug_buffer(
+    lines: u64,
+    device: &vd::Device,
+) -> vd::Result<(vd::BufferHandle, vd::DeviceMemoryHandle)> {
+    let properties = device.physical_device().memory_properties();
+
+    create_buffer(
+        lines * 2 * std::mem::size_of::<Vertex>() as u64,
+        vd::BufferUsageFlags::VERTEX_BUFFER,
+        device,
+        vd::MemoryPropertyFlags::HOST_VISIBLE,
+        &properties,
+    )
 }
 
 /// Initialize fixed-function data, including the descriptor set layout
-fn init_fixed<'a>(device: vd::Device) -> vd::Result<(
+fn init_fixed<'a>(
+    device: vd::Device,
+    samples: vd::SampleCountFlags,
+) -> vd::Result<(
     vd::Format,
     vd::PipelineInputAssemblyStateCreateInfo<'a>,
     vd::PipelineRasterizationStateCreateInfo<'a>,
@@ -2147,7 +4110,7 @@ fn init_fixed<'a>(device: vd::Device) -> vd::Result<(
         .build();
 
     let multisampling = vd::PipelineMultisampleStateCreateInfo::builder()
-        .rasterization_samples(vd::SampleCountFlags::COUNT_1)
+        .rasterization_samples(samples)
         .sample_shading_enable(false)
         .min_sample_shading(1f32)
         .alpha_to_coverage_enable(false)
@@ -2284,7 +4247,10 @@ fn init_swapchain(
             .image_color_space(surface_format.color_space())
             .image_extent(swap_extent)
             .image_array_layers(1)
-            .image_usage(vd::ImageUsageFlags::COLOR_ATTACHMENT)
+            .image_usage(
+                  vd::ImageUsageFlags::COLOR_ATTACHMENT
+                | vd::ImageUsageFlags::TRANSFER_SRC // Frame capture
+            )
             .image_sharing_mode(sharing_mode)
             .queue_family_indices(indices)
             .pre_transform(capabilities.current_transform()) // No change
@@ -2339,26 +4305,37 @@ fn init_swapchain(
     Ok((swapchain, command_fences, views))
 }
 
+/// Create a render pass into a color image of the given format,
+/// left in `final_layout` (e.g. for presentation or sampling)
 fn init_render_pass(
-    swapchain:    &vd::SwapchainKhr,
+    color_format: vd::Format,
     depth_format: vd::Format,
+    samples:      vd::SampleCountFlags,
+    final_layout: vd::ImageLayout,
     device:       &vd::Device
 ) -> vd::Result<(vd::RenderPass)> {
+    let multisampled = samples != vd::SampleCountFlags::COUNT_1;
+
     // Clear framebuffer
+    // (multisampled color is resolved instead of stored)
     let color_attachment = vd::AttachmentDescription::builder()
-        .format(swapchain.image_format())
-        .samples(vd::SampleCountFlags::COUNT_1)
+        .format(color_format)
+        .samples(samples)
         .load_op(vd::AttachmentLoadOp::Clear)
-        .store_op(vd::AttachmentStoreOp::Store)
-        .stencil_load_op(vd::AttachmentLoadOp::DontCare)
+        .store_op(
+            if multisampled { vd::AttachmentStoreOp::DontCare }
+            else { vd::AttachmentStoreOp::Store }
+        ).stencil_load_op(vd::AttachmentLoadOp::DontCare)
         .stencil_store_op(vd::AttachmentStoreOp::DontCare)
         .initial_layout(vd::ImageLayout::Undefined)
-        .final_layout(vd::ImageLayout::PresentSrcKhr)
-        .build();
+        .final_layout(
+            if multisampled { vd::ImageLayout::ColorAttachmentOptimal }
+            else { final_layout }
+        ).build();
 
     let depth_attachment = vd::AttachmentDescription::builder()
         .format(depth_format)
-        .samples(vd::SampleCountFlags::COUNT_1)
+        .samples(samples)
         .load_op(vd::AttachmentLoadOp::Clear)
         .store_op(vd::AttachmentStoreOp::DontCare)
         .stencil_load_op(vd::AttachmentLoadOp::DontCare)
@@ -2379,12 +4356,40 @@ fn init_render_pass(
         .layout(vd::ImageLayout::DepthStencilAttachmentOptimal)
         .build();
 
-    let subpass = vd::SubpassDescription::builder()
-        .pipeline_bind_point(vd::PipelineBindPoint::Graphics)
-        .color_attachments(&color_refs)
-        .depth_stencil_attachment(&depth_ref)
+    // Swapchain (or offscreen) image receiving the resolved samples
+    let resolve_attachment = vd::AttachmentDescription::builder()
+        .format(color_format)
+        .samples(vd::SampleCountFlags::COUNT_1)
+        .load_op(vd::AttachmentLoadOp::DontCare)
+        .store_op(vd::AttachmentStoreOp::Store)
+        .stencil_load_op(vd::AttachmentLoadOp::DontCare)
+        .stencil_store_op(vd::AttachmentStoreOp::DontCare)
+        .initial_layout(vd::ImageLayout::Undefined)
+        .final_layout(final_layout)
         .build();
 
+    let resolve_refs = [
+        vd::AttachmentReference::builder()
+            .attachment(2)
+            .layout(vd::ImageLayout::ColorAttachmentOptimal)
+            .build(),
+    ];
+
+    let subpass = if multisampled {
+        vd::SubpassDescription::builder()
+            .pipeline_bind_point(vd::PipelineBindPoint::Graphics)
+            .color_attachments(&color_refs)
+            .resolve_attachments(&resolve_refs)
+            .depth_stencil_attachment(&depth_ref)
+            .build()
+    } else {
+        vd::SubpassDescription::builder()
+            .pipeline_bind_point(vd::PipelineBindPoint::Graphics)
+            .color_attachments(&color_refs)
+            .depth_stencil_attachment(&depth_ref)
+            .build()
+    };
+
     let dependency = vd::SubpassDependency::builder()
         .src_subpass(vd::SUBPASS_EXTERNAL)
         .dst_subpass(0)
@@ -2395,16 +4400,41 @@ fn init_render_pass(
             | vd::AccessFlags::COLOR_ATTACHMENT_WRITE
         ).build();
 
+    // Finish writing color before later passes sample it
+    let sample_dependency = vd::SubpassDependency::builder()
+        .src_subpass(0)
+        .dst_subpass(vd::SUBPASS_EXTERNAL)
+        .src_stage_mask(vd::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+        .dst_stage_mask(vd::PipelineStageFlags::FRAGMENT_SHADER)
+        .src_access_mask(vd::AccessFlags::COLOR_ATTACHMENT_WRITE)
+        .dst_access_mask(vd::AccessFlags::SHADER_READ)
+        .build();
+
+    let sampled = final_layout == vd::ImageLayout::ShaderReadOnlyOptimal;
+
+    let dependencies = if sampled {
+        vec![dependency, sample_dependency]
+    } else {
+        vec![dependency]
+    };
+
+    let attachments = if multisampled {
+        vec![color_attachment, depth_attachment, resolve_attachment]
+    } else {
+        vec![color_attachment, depth_attachment]
+    };
+
     Ok(
         vd::RenderPass::builder()
-            .attachments(&[color_attachment, depth_attachment])
+            .attachments(&attachments)
             .subpasses(&[subpass])
-            .dependencies(&[dependency])
+            .dependencies(&dependencies)
             .build(device.clone())?
     )
 }
 
-fn init_pipeline(
+/// Create a model pipeline for every `MeshPass`, in declaration order
+fn init_pipelines(
     swapchain:       &vd::SwapchainKhr,
     stages:          &[vd::PipelineShaderStageCreateInfo; 2],
     assembly:        &vd::PipelineInputAssemblyStateCreateInfo,
@@ -2413,6 +4443,40 @@ fn init_pipeline(
     pipeline_layout: &vd::PipelineLayout,
     render_pass:     &vd::RenderPass,
     device:          &vd::Device,
+) -> vd::Result<Vec<vd::GraphicsPipeline>> {
+    MESH_PASSES.iter().map(|&pass| {
+        debug_assert!(MESH_PASSES[pass as usize] == pass);
+
+        // The depth prepass writes no color; skip the fragment stage
+        let stages = match pass {
+            MeshPass::Depth => &stages[..1],
+            _ => &stages[..],
+        };
+
+        init_pipeline(
+            swapchain,
+            stages,
+            assembly,
+            rasterizer,
+            multisampling,
+            pipeline_layout,
+            render_pass,
+            pass,
+            device,
+        )
+    }).collect()
+}
+
+fn init_pipeline(
+    swapchain:       &vd::SwapchainKhr,
+    stages:          &[vd::PipelineShaderStageCreateInfo],
+    assembly:        &vd::PipelineInputAssemblyStateCreateInfo,
+    rasterizer:      &vd::PipelineRasterizationStateCreateInfo,
+    multisampling:   &vd::PipelineMultisampleStateCreateInfo,
+    pipeline_layout: &vd::PipelineLayout,
+    render_pass:     &vd::RenderPass,
+    pass:            MeshPass,
+    device:          &vd::Device,
 ) -> vd::Result<(vd::GraphicsPipeline)> {
     /*
      * Fixed functions (these will be allocated on the heap later,
@@ -2438,10 +4502,14 @@ fn init_pipeline(
             .src_alpha_blend_factor(vd::BlendFactor::Zero)
             .alpha_blend_op(vd::BlendOp::Add)
             .color_write_mask(
-                  vd::ColorComponentFlags::R
-                | vd::ColorComponentFlags::G
-                | vd::ColorComponentFlags::B
-                | vd::ColorComponentFlags::A
+                if pass == MeshPass::Depth {
+                    vd::ColorComponentFlags::empty()
+                } else {
+                      vd::ColorComponentFlags::R
+                    | vd::ColorComponentFlags::G
+                    | vd::ColorComponentFlags::B
+                    | vd::ColorComponentFlags::A
+                }
             ).build()
     ];
 
@@ -2452,10 +4520,16 @@ fn init_pipeline(
         .blend_constants([0f32; 4])
         .build();
 
+    // The prepass has already written the closest depth
+    let (depth_write, depth_compare) = match pass {
+        MeshPass::ColorEqual => (false, vd::CompareOp::Equal),
+        _ => (true, vd::CompareOp::Less), // Closer fragments, lower depth
+    };
+
     let stencil = vd::PipelineDepthStencilStateCreateInfo::builder()
         .depth_test_enable(true)
-        .depth_write_enable(true)
-        .depth_compare_op(vd::CompareOp::Less) // Closer fragments, lower depth
+        .depth_write_enable(depth_write)
+        .depth_compare_op(depth_compare)
         .depth_bounds_test_enable(false)
         .stencil_test_enable(false)
         .build();
@@ -2489,6 +4563,16 @@ fn init_pipeline(
         .scissors(&scissors)
         .build();
 
+    // Set while drawing, to render into swapchain and offscreen images
+    let dynamic_states = [
+        vd::DynamicState::Viewport,
+        vd::DynamicState::Scissor,
+    ];
+
+    let dynamic_state = vd::PipelineDynamicStateCreateInfo::builder()
+        .dynamic_states(&dynamic_states)
+        .build();
+
     /* Pipeline */
 
     Ok(
@@ -2497,6 +4581,7 @@ fn init_pipeline(
         .vertex_input_state(&vert_info)
         .input_assembly_state(assembly)
         .viewport_state(&viewport_state)
+        .dynamic_state(&dynamic_state)
         .rasterization_state(rasterizer)
         .multisample_state(multisampling)
         .color_blend_state(&blending)
@@ -2513,6 +4598,7 @@ fn init_pipeline(
 fn init_drawing(
     swapchain:       &vd::SwapchainKhr,
     depth_format:    vd::Format,
+    samples:         vd::SampleCountFlags,
     views:           &[vd::ImageView],
     render_pass:     &vd::RenderPass,
     device:          &vd::Device,
@@ -2522,6 +4608,8 @@ fn init_drawing(
 ) -> vd::Result<(
     vd::Image,
     vd::DeviceMemoryHandle,
+    Option<vd::Image>,
+    Option<vd::DeviceMemoryHandle>,
     Vec<vd::Framebuffer>,
     vd::BufferHandle,
     vd::DeviceMemoryHandle,
@@ -2547,7 +4635,7 @@ fn init_drawing(
         .extent(extent)
         .mip_levels(1)
         .array_layers(1)
-        .samples(vd::SampleCountFlags::COUNT_1)
+        .samples(samples)
         .tiling(vd::ImageTiling::Optimal)
         .usage(vd::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT)
         .sharing_mode(vd::SharingMode::Exclusive)
@@ -2643,12 +4731,78 @@ fn init_drawing(
 
     end_transfer_buffer(&transfer_buffer, device, graphics_family)?;
 
+    /* Multisampled color buffer */
+
+    let color_target = if samples != vd::SampleCountFlags::COUNT_1 {
+        let color_image = vd::Image::builder()
+            .image_type(vd::ImageType::Type2d)
+            .format(swapchain.image_format())
+            .extent(extent)
+            .mip_levels(1)
+            .array_layers(1)
+            .samples(samples)
+            .tiling(vd::ImageTiling::Optimal)
+            .usage(
+                  vd::ImageUsageFlags::COLOR_ATTACHMENT
+                | vd::ImageUsageFlags::TRANSIENT_ATTACHMENT
+            ).sharing_mode(vd::SharingMode::Exclusive)
+            .initial_layout(vd::ImageLayout::Undefined)
+            .build(device.clone())?;
+
+        let requirements = unsafe {
+            device.get_image_memory_requirements(color_image.handle())
+        };
+
+        let color_info = vd::MemoryAllocateInfo::builder()
+            .allocation_size(requirements.size())
+            .memory_type_index(
+                get_memory_type(
+                    requirements.memory_type_bits(),
+                    vd::MemoryPropertyFlags::DEVICE_LOCAL,
+                    properties.memory_types(),
+                )?
+            ).build();
+
+        let color_memory_handle = unsafe {
+            device.allocate_memory(&color_info, None)?
+        };
+
+        unsafe {
+            device.bind_image_memory(
+                color_image.handle(),
+                color_memory_handle,
+                0,
+            )?;
+        }
+
+        let color_view = vd::ImageView::builder()
+            .image(color_image.handle())
+            .view_type(vd::ImageViewType::Type2d)
+            .format(swapchain.image_format())
+            .components(vd::ComponentMapping::default())
+            .subresource_range(
+                vd::ImageSubresourceRange::builder()
+                    .aspect_mask(vd::ImageAspectFlags::COLOR)
+                    .base_mip_level(0)
+                    .level_count(1)
+                    .base_array_layer(0)
+                    .layer_count(1)
+                    .build()
+            ).build(device.clone(), None)?;
+
+        Some((color_image, color_memory_handle, color_view))
+    } else { None };
+
     /* Framebuffers */
 
     let mut framebuffers = Vec::with_capacity(views.len());
 
     for view in views {
-        let attachments = [view, &depth_view];
+        // Render into the multisampled image and resolve to the swapchain
+        let attachments = match color_target {
+            Some((_, _, ref color)) => vec![color, &depth_view, view],
+            None => vec![view, &depth_view],
+        };
 
         let framebuffer = vd::Framebuffer::builder()
             .render_pass(render_pass)
@@ -2668,14 +4822,15 @@ fn init_drawing(
     /* Uniform buffers */
 
     let pool_sizes = {
+        // One set per camera, shared by all models
         let size = vd::DescriptorPoolSize::builder()
             .type_of(vd::DescriptorType::UniformBuffer)
-            .descriptor_count(1) // Shared by all models
+            .descriptor_count(MAX_CAMERAS as u32)
             .build();
 
         let dynamic_size = vd::DescriptorPoolSize::builder()
             .type_of(vd::DescriptorType::UniformBufferDynamic)
-            .descriptor_count(1) // Shared by all models
+            .descriptor_count(MAX_CAMERAS as u32)
             .build();
 
         [size, dynamic_size]
@@ -2684,13 +4839,15 @@ fn init_drawing(
     let descriptor_pool = vd::DescriptorPool::builder()
         .pool_sizes(&pool_sizes)
         .flags(vd::DescriptorPoolCreateFlags::empty())
-        .max_sets(1)
+        .max_sets(MAX_CAMERAS as u32)
         .build(device.clone())?;
 
     // Each set will contain two descriptors
-    let sets = descriptor_pool.allocate_descriptor_sets(&[ubo_layout])?;
+    let sets = descriptor_pool.allocate_descriptor_sets(
+        &[ubo_layout; MAX_CAMERAS]
+    )?;
 
-    debug_assert!(sets.len() == 1);
+    debug_assert!(sets.len() == MAX_CAMERAS);
 
     let minimum_alignment = device
         .physical_device()
@@ -2715,9 +4872,9 @@ fn init_drawing(
         std::mem::size_of::<SharedUBO>() as u64
     );
 
-    // Allocate a buffer for the shared UBO
+    // Allocate a buffer for the shared UBOs
     let (ubo_buffer, ubo_memory) = create_buffer(
-        shared_alignment, // Contains single UBO
+        shared_alignment * MAX_CAMERAS as u64, // Single UBO per camera
         vd::BufferUsageFlags::UNIFORM_BUFFER,
         device,
           vd::MemoryPropertyFlags::HOST_VISIBLE
@@ -2725,11 +4882,14 @@ fn init_drawing(
         &properties,
     )?;
 
-    let shared_info = vd::DescriptorBufferInfo::builder()
-        .buffer(ubo_buffer)
-        .offset(0)
-        .range(shared_alignment)
-        .build();
+    let shared_infos: Vec<vd::DescriptorBufferInfo> = (0..MAX_CAMERAS)
+        .map(|camera| {
+            vd::DescriptorBufferInfo::builder()
+                .buffer(ubo_buffer)
+                .offset(shared_alignment * camera as u64)
+                .range(shared_alignment)
+                .build()
+        }).collect();
 
     /* Dynamic */
 
@@ -2745,6 +4905,22 @@ fn init_drawing(
     // even though it probably will.
     let dynamic_alignment = ubo_alignment(DYNAMIC_UBO_WIDTH as u64);
 
+    let max_range = device
+        .physical_device()
+        .properties()
+        .limits()
+        .max_uniform_buffer_range() as u64;
+
+    if dynamic_alignment > max_range {
+        return Err(format!(
+            "Instance UBO ({} bytes, {} lights) exceeds \
+            GPU uniform buffer range of {} bytes",
+            dynamic_alignment,
+            MAX_INSTANCE_LIGHTS,
+            max_range,
+        ).into());
+    }
+
     let dynamic_size = MAX_INSTANCES * dynamic_alignment;
 
     // Allocate a single buffer for the remaining UBOs
@@ -2762,25 +4938,32 @@ fn init_drawing(
         .range(dynamic_alignment)
         .build();
 
-    // Write shared and dynamic UBOs
-    let writes = [
-        vd::WriteDescriptorSet::builder()
-            .dst_set(sets[0])
-            .dst_binding(0) // First binding
-            .dst_array_element(0)
-            .descriptor_count(1)
-            .descriptor_type(vd::DescriptorType::UniformBuffer)
-            .buffer_info(&shared_info)
-            .build(),
-        vd::WriteDescriptorSet::builder()
-            .dst_set(sets[0])
-            .dst_binding(1) // Second binding
-            .dst_array_element(0)
-            .descriptor_count(1)
-            .descriptor_type(vd::DescriptorType::UniformBufferDynamic)
-            .buffer_info(&dynamic_info)
-            .build(),
-    ];
+    // Write shared and dynamic UBOs; instance data is shared by all sets
+    let mut writes = Vec::with_capacity(2 * MAX_CAMERAS);
+
+    for (set, shared_info) in sets.iter().zip(&shared_infos) {
+        writes.push(
+            vd::WriteDescriptorSet::builder()
+                .dst_set(*set)
+                .dst_binding(0) // First binding
+                .dst_array_element(0)
+                .descriptor_count(1)
+                .descriptor_type(vd::DescriptorType::UniformBuffer)
+                .buffer_info(shared_info)
+                .build()
+        );
+
+        writes.push(
+            vd::WriteDescriptorSet::builder()
+                .dst_set(*set)
+                .dst_binding(1) // Second binding
+                .dst_array_element(0)
+                .descriptor_count(1)
+                .descriptor_type(vd::DescriptorType::UniformBufferDynamic)
+                .buffer_info(&dynamic_info)
+                .build()
+        );
+    }
 
     // No copies (causes segfault?)
     descriptor_pool.update_descriptor_sets(&writes, &[]);
@@ -2788,9 +4971,16 @@ fn init_drawing(
     // Compute font alignment
     let font_alignment = ubo_alignment(std::mem::size_of::<FontUBO>() as u64);
 
+    let (color_image, color_memory_handle) = match color_target {
+        Some((image, memory, _)) => (Some(image), Some(memory)),
+        None => (None, None),
+    };
+
     Ok((
         depth_image,
         depth_memory_handle,
+        color_image,
+        color_memory_handle,
         framebuffers,
         ubo_buffer,
         ubo_memory,
@@ -2804,6 +4994,170 @@ fn init_drawing(
     ))
 }
 
+/// Create a 2d image with bound device memory and a view
+fn create_attachment(
+    width:   u32,
+    height:  u32,
+    format:  vd::Format,
+    samples: vd::SampleCountFlags,
+    usage:   vd::ImageUsageFlags,
+    aspect:  vd::ImageAspectFlags,
+    device:  &vd::Device,
+) -> vd::Result<Attachment> {
+    let image = vd::Image::builder()
+        .image_type(vd::ImageType::Type2d)
+        .format(format)
+        .extent(
+            vd::Extent3d::builder()
+                .width(width)
+                .height(height)
+                .depth(1)
+                .build()
+        ).mip_levels(1)
+        .array_layers(1)
+        .samples(samples)
+        .tiling(vd::ImageTiling::Optimal)
+        .usage(usage)
+        .sharing_mode(vd::SharingMode::Exclusive)
+        .initial_layout(vd::ImageLayout::Undefined)
+        .build(device.clone())?;
+
+    let requirements = unsafe {
+        device.get_image_memory_requirements(image.handle())
+    };
+
+    let properties = device.physical_device().memory_properties();
+
+    let info = vd::MemoryAllocateInfo::builder()
+        .allocation_size(requirements.size())
+        .memory_type_index(
+            get_memory_type(
+                requirements.memory_type_bits(),
+                vd::MemoryPropertyFlags::DEVICE_LOCAL,
+                properties.memory_types(),
+            )?
+        ).build();
+
+    let memory = unsafe {
+        device.allocate_memory(&info, None)?
+    };
+
+    unsafe {
+        device.bind_image_memory(image.handle(), memory, 0)?;
+    }
+
+    let view = vd::ImageView::builder()
+        .image(image.handle())
+        .view_type(vd::ImageViewType::Type2d)
+        .format(format)
+        .components(vd::ComponentMapping::default())
+        .subresource_range(
+            vd::ImageSubresourceRange::builder()
+                .aspect_mask(aspect)
+                .base_mip_level(0)
+                .level_count(1)
+                .base_array_layer(0)
+                .layer_count(1)
+                .build()
+        ).build(device.clone(), None)?;
+
+    Ok(Attachment { view, image, memory })
+}
+
+/// Create an offscreen color image (with depth and multisampled color)
+/// for `render_pass`, which leaves it ready to be sampled
+fn create_offscreen(
+    width:        u32,
+    height:       u32,
+    color_format: vd::Format,
+    depth_format: vd::Format,
+    samples:      vd::SampleCountFlags,
+    render_pass:  &vd::RenderPass,
+    device:       &vd::Device,
+) -> vd::Result<OffscreenTarget> {
+    let color = create_attachment(
+        width,
+        height,
+        color_format,
+        vd::SampleCountFlags::COUNT_1,
+          vd::ImageUsageFlags::COLOR_ATTACHMENT
+        | vd::ImageUsageFlags::SAMPLED
+        | vd::ImageUsageFlags::TRANSFER_SRC, // Captured in tests
+        vd::ImageAspectFlags::COLOR,
+        device,
+    )?;
+
+    let depth = create_attachment(
+        width,
+        height,
+        depth_format,
+        samples,
+        vd::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT,
+        vd::ImageAspectFlags::DEPTH,
+        device,
+    )?;
+
+    let multisampled = if samples != vd::SampleCountFlags::COUNT_1 {
+        Some(
+            create_attachment(
+                width,
+                height,
+                color_format,
+                samples,
+                  vd::ImageUsageFlags::COLOR_ATTACHMENT
+                | vd::ImageUsageFlags::TRANSIENT_ATTACHMENT,
+                vd::ImageAspectFlags::COLOR,
+                device,
+            )?
+        )
+    } else { None };
+
+    // Same attachment order as the swapchain framebuffers
+    let framebuffer = {
+        let attachments = match multisampled {
+            Some(ref multisampled) => {
+                vec![&multisampled.view, &depth.view, &color.view]
+            },
+            None => vec![&color.view, &depth.view],
+        };
+
+        vd::Framebuffer::builder()
+            .render_pass(render_pass)
+            .attachments(&attachments)
+            .width(width)
+            .height(height)
+            .layers(1)
+            .build(device.clone())?
+    };
+
+    let sampler = vd::Sampler::builder()
+        .mag_filter(vd::Filter::Linear)
+        .min_filter(vd::Filter::Linear)
+        .address_mode_u(vd::SamplerAddressMode::ClampToEdge)
+        .address_mode_v(vd::SamplerAddressMode::ClampToEdge)
+        .address_mode_w(vd::SamplerAddressMode::ClampToEdge)
+        .mip_lod_bias(0.)
+        .compare_op(vd::CompareOp::Never)
+        .min_lod(0.)
+        .max_lod(1.)
+        .border_color(vd::BorderColor::FloatOpaqueBlack)
+        .anisotropy_enable(false)
+        .max_anisotropy(1.0f32)
+        .build(device.clone())?;
+
+    Ok(
+        OffscreenTarget {
+            width,
+            height,
+            framebuffer,
+            sampler,
+            color,
+            depth,
+            multisampled,
+        }
+    )
+}
+
 fn init_commands(
     drawing_pool: &vd::CommandPool,
     framebuffers: &[vd::Framebuffer],
@@ -2855,22 +5209,61 @@ fn end_transfer_buffer(
     // Block until transfer completion
     device.wait_idle();
 
-    Ok(())
+    Ok(())
+}
+
+/// Transfer data to the GPU via host and device buffers; \
+/// return buffer created on GPU
+fn create_buffers<T: std::marker::Copy>(
+    data:            &[T],
+    properties:      &vd::PhysicalDeviceMemoryProperties,
+    device:          &vd::Device,
+    usage:           vd::BufferUsageFlags,
+    transient_pool:  &vd::CommandPool,
+    graphics_family: u32,
+) -> vd::Result<(
+    vd::BufferHandle,
+    vd::DeviceMemoryHandle,
+)> {
+    // Length of slice * length of data type
+    let size = std::mem::size_of_val(data) as u64;
+
+    // GPU buffer (destination)
+    // (transfer source allows the buffer to be reallocated later)
+    let (device_buffer, device_memory) = create_buffer(
+        size,
+          usage
+        | vd::BufferUsageFlags::TRANSFER_SRC
+        | vd::BufferUsageFlags::TRANSFER_DST,
+        device,
+        vd::MemoryPropertyFlags::DEVICE_LOCAL,
+        properties,
+    )?;
+
+    stage_buffer(
+        data,
+        device_buffer,
+        0,
+        properties,
+        device,
+        transient_pool,
+        graphics_family,
+    )?;
+
+    Ok((device_buffer, device_memory))
 }
 
-/// Transfer data to the GPU via host and device buffers; \
-/// return buffer created on GPU
-fn create_buffers<T: std::marker::Copy>(
+/// Upload data into part of a device-local buffer through a host buffer,
+/// blocking until the transfer completes
+fn stage_buffer<T: std::marker::Copy>(
     data:            &[T],
+    device_buffer:   vd::BufferHandle,
+    offset:          u64,
     properties:      &vd::PhysicalDeviceMemoryProperties,
     device:          &vd::Device,
-    usage:           vd::BufferUsageFlags,
     transient_pool:  &vd::CommandPool,
     graphics_family: u32,
-) -> vd::Result<(
-    vd::BufferHandle,
-    vd::DeviceMemoryHandle,
-)> {
+) -> vd::Result<()> {
     // Length of slice * length of data type
     let size = std::mem::size_of_val(data) as u64;
 
@@ -2889,22 +5282,13 @@ fn create_buffers<T: std::marker::Copy>(
         copy_buffer(device, host_memory, size, data)?;
     }
 
-    // GPU buffer (destination)
-    let (device_buffer, device_memory) = create_buffer(
-        size,
-        usage | vd::BufferUsageFlags::TRANSFER_DST,
-        device,
-        vd::MemoryPropertyFlags::DEVICE_LOCAL,
-        properties,
-    )?;
-
     let transfer_buffer = get_transfer_buffer(transient_pool)?;
 
     // Copy buffer to GPU
     unsafe {
         let copy = vd::BufferCopy::builder()
             .src_offset(0)
-            .dst_offset(0)
+            .dst_offset(offset)
             .size(size)
             .build();
 
@@ -2924,7 +5308,7 @@ fn create_buffers<T: std::marker::Copy>(
         device.free_memory(host_memory, None);
     }
 
-    Ok((device_buffer, device_memory))
+    Ok(())
 }
 
 /// Allocate (empty) buffer on the GPU
@@ -3960,3 +6344,849 @@ impl TextDisplay {
         Ok(())
     }
 }
+
+/// Unshared 2x2x2 cube faces, two triangles each
+#[cfg(test)]
+pub(crate) fn test_cube() -> (Vec<Vertex>, Vec<u32>) {
+    let corners = [
+        (-1., -1., -1.), (1., -1., -1.), (1., 1., -1.), (-1., 1., -1.),
+        (-1., -1.,  1.), (1., -1.,  1.), (1., 1.,  1.), (-1., 1.,  1.),
+    ];
+
+    let faces = [
+        [0, 3, 2, 1], [4, 5, 6, 7], [0, 4, 7, 3],
+        [1, 2, 6, 5], [3, 7, 6, 2], [0, 1, 5, 4],
+    ];
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for face in &faces {
+        // Split each face into two triangles with duplicated corners
+        for &i in &[0, 1, 2, 0, 2, 3] {
+            let (x, y, z) = corners[face[i]];
+            indices.push(vertices.len() as u32);
+            vertices.push(Vertex {
+                position: alg::Vec3::new(x, y, z),
+                .. Default::default()
+            });
+        }
+    }
+
+    (vertices, indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use std;
+    use alg;
+    use graphics;
+    use components;
+    use render::*;
+
+    #[test]
+    fn bone_weights() {
+        let vertex = Vertex::default();
+        assert!(vertex.bone_indices == [0; 4]);
+        assert!(vertex.bone_weights == [1.0, 0.0, 0.0, 0.0]);
+        assert!(Vertex::zero().bone_weights == vertex.bone_weights);
+
+        let vertex = vertex.with_bones([3, 1, 0, 0], [3.0, 1.0, 0.0, 0.0]);
+        assert!(vertex.bone_indices == [3, 1, 0, 0]);
+
+        let sum: f32 = vertex.bone_weights.iter().sum();
+        let error = (sum - 1.0).abs() + (vertex.bone_weights[0] - 0.75).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn instance_ubo_width() {
+        // Light count trades off against softbody offsets
+        assert!(MAX_SOFTBODY_VERT > 0);
+        assert!(std::mem::size_of::<InstanceUBO>() <= DYNAMIC_UBO_WIDTH);
+        assert!(
+            std::mem::size_of::<[Light; MAX_INSTANCE_LIGHTS]>()
+                == MAX_INSTANCE_LIGHTS * 32
+        );
+    }
+
+    #[test]
+    fn model_builder() {
+        let vertices = vec![
+            Vertex::new_position_color( 0.0,  0.5,  0.0, 1., 1., 0.),
+            Vertex::new_position_color(-0.5, -0.5, -0.5, 1., 0., 1.),
+            Vertex::new_position_color( 0.5, -0.5, -0.5, 1., 0., 0.),
+            Vertex::new_position_color( 0.5, -0.5,  0.5, 1., 1., 0.),
+            Vertex::new_position_color(-0.5, -0.5,  0.5, 1., 1., 1.),
+        ];
+
+        let indices = vec![
+            0, 2, 1,
+            0, 3, 2,
+            0, 4, 3,
+            0, 1, 4,
+            2, 4, 1,
+            3, 4, 2,
+        ];
+
+        let expected = ModelData::new_with_normals(
+            "pyramid",
+            vertices.clone(),
+            indices.clone(),
+            NormalMode::Smooth,
+        );
+
+        let mut builder = ModelBuilder::new("pyramid");
+        builder.normals(NormalMode::Smooth);
+
+        for vertex in vertices {
+            builder.add_vertex(vertex);
+        }
+
+        for triangle in indices.chunks(3) {
+            builder.add_triangle(triangle[0], triangle[1], triangle[2]);
+        }
+
+        let model = builder.build();
+
+        assert!(model.name == expected.name);
+        assert!(model.computed_normals);
+        assert!(model.vertices == expected.vertices);
+        assert!(model.indices == expected.indices);
+        assert!(model.bounds() == expected.bounds());
+
+        // Vertex soup
+        let soup = ModelBuilder::new("triangle")
+            .add_vertex(Vertex::new_position_color(0., 0., 0., 1., 1., 1.))
+            .add_vertex(Vertex::new_position_color(1., 0., 0., 1., 1., 1.))
+            .add_vertex(Vertex::new_position_color(0., 1., 0., 1., 1., 1.))
+            .build();
+
+        assert!(soup.indices == vec![0, 1, 2]);
+        assert!(!soup.computed_normals);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "out of range")]
+    fn model_builder_range() {
+        ModelBuilder::new("triangle")
+            .add_vertex(Vertex::zero())
+            .add_triangle(0, 1, 0);
+    }
+
+    #[test]
+    fn convert_axes() {
+        let vertex = |x, y, z| Vertex {
+            position: alg::Vec3::new(x, y, z),
+            .. Default::default()
+        };
+
+        let mut model = ModelData::new_with_normals(
+            "triangle",
+            vec![vertex(0., 0., 0.), vertex(1., 0., 0.), vertex(0., 1., 0.)],
+            vec![0, 1, 2],
+            NormalMode::Flat,
+        );
+
+        let normal = model.vertices[0].normal;
+
+        model.convert_axes(
+            alg::Axes::new(alg::UpAxis::Z, alg::Handedness::Right),
+            alg::Axes::default(),
+        );
+
+        assert!(model.indices == vec![0, 2, 1]);
+        assert!(model.vertices[2].position == alg::Vec3::fwd());
+        assert!(model.vertices[0].normal == alg::Vec3::up() * normal.z);
+    }
+
+    #[test]
+    fn index_type() {
+        let small = ModelData::new(
+            "small",
+            vec![Vertex::zero(); 3],
+            vec![0, 1, 2],
+        );
+
+        assert!(small.index_type() == IndexType::U16);
+
+        let small = small.with_index_type(IndexType::U32);
+        assert!(small.index_type() == IndexType::U32);
+
+        let large = ModelData::new(
+            "large",
+            vec![Vertex::zero(); 65537],
+            vec![0, 1, 65536],
+        );
+
+        assert!(large.index_type() == IndexType::U32);
+    }
+
+    #[test]
+    fn fog() {
+        let mut parameters = Parameters::new();
+        assert!(parameters.fog_factor(1000.0) == 0.0); // Disabled
+
+        parameters.fog_start = 10.0;
+        parameters.fog_end = 50.0;
+
+        assert!(parameters.fog_factor(5.0) == 0.0);
+        assert!(parameters.fog_factor(100.0) == 1.0);
+
+        let error = (parameters.fog_factor(20.0) - 0.25).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        // Fog follows the mat4 pair in the std140 block
+        assert!(std::mem::size_of::<SharedUBO>() == 160);
+    }
+
+    #[test]
+    fn mesh_passes() {
+        let mut parameters = Parameters::new();
+        assert!(parameters.mesh_passes() == &[MeshPass::Color]);
+
+        parameters.depth_prepass = true;
+        assert!(
+            parameters.mesh_passes()
+                == &[MeshPass::Depth, MeshPass::ColorEqual]
+        );
+
+        // Pipelines are looked up by pass
+        for (i, &pass) in MESH_PASSES.iter().enumerate() {
+            assert!(pass as usize == i);
+        }
+    }
+
+    #[test]
+    fn check_vertices() {
+        // Quad using four of six reserved vertices
+        let model = Model::new(6, 0, 4, 6, 0);
+
+        assert!(model.check_vertices(3).is_err()); // Index out of range
+        assert!(model.check_vertices(4) == Ok(false));
+        assert!(model.check_vertices(6) == Ok(false));
+        assert!(model.check_vertices(7) == Ok(true)); // Must grow
+    }
+
+    #[test]
+    fn stats() {
+        let names = vec!["cube".to_string(), "quad".to_string()];
+        let mut instances = Instances::new(2, &names, None);
+
+        let cube = instances.get_index("cube");
+        let quad = instances.get_index("quad");
+
+        for _ in 0..3 {
+            instances.add(InstanceUBO::default(), cube);
+        }
+
+        let hidden = instances.add(InstanceUBO::default(), quad);
+        instances.add(InstanceUBO::default(), quad);
+        instances.update_meta(hidden, InstanceMeta::new(true));
+
+        let index_counts = [36, 6];
+        let stats = instances.stats(&index_counts, 1);
+
+        assert!(stats.instances == 4);
+        assert!(stats.draw_calls == 4);
+        assert!(stats.triangles == 3 * 12 + 2);
+        assert!(stats.lights_culled == 0);
+
+        // Depth prepass doubles the draws but not the triangles shaded
+        let stats = instances.stats(&index_counts, 2);
+        assert!(stats.draw_calls == 8);
+        assert!(stats.triangles == 3 * 12 + 2);
+    }
+
+    #[test]
+    fn point_vertices() {
+        let point = Point::new(
+            alg::Vec3::new(1.0, 2.0, 3.0),
+            graphics::Color::red(),
+            0.5,
+        );
+
+        let vertices = point.vertices();
+
+        for vertex in &vertices {
+            assert!(vertex.position == point.position);
+            assert!(vertex.color == graphics::Color::red());
+            assert!(vertex.normal.x.abs() == 0.25);
+            assert!(vertex.normal.y.abs() == 0.25);
+        }
+
+        // Two triangles covering the square
+        let area = vertices.chunks(3).fold(0.0, |sum, triangle| {
+            let (a, b, c) = (
+                triangle[0].normal,
+                triangle[1].normal,
+                triangle[2].normal,
+            );
+
+            sum + (b - a).cross(c - a).mag() * 0.5
+        });
+
+        let error = (area - 0.25f32).abs();
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+    }
+
+    #[test]
+    fn swizzle_bgra() {
+        // Two orange pixels as the swapchain stores them
+        let mut pixels = vec![0, 128, 255, 255, 0, 128, 255, 255];
+        super::swizzle_bgra(&mut pixels);
+
+        assert!(pixels == vec![255, 128, 0, 255, 255, 128, 0, 255]);
+    }
+
+    /// Quad in clip space covering `min` to `max` on both axes,
+    /// facing both ways so that culling never hides it
+    fn clip_quad(min: f32, max: f32) -> Vec<Vertex> {
+        clip_quad_colored(min, max, graphics::Color::white())
+    }
+
+    /// Clip space quad with the given vertex color
+    fn clip_quad_colored(
+        min: f32,
+        max: f32,
+        color: graphics::Color,
+    ) -> Vec<Vertex> {
+        [(min, min), (max, min), (max, max), (min, max)].iter()
+            .map(|&(x, y)| Vertex::new_raw(
+                x, y, 0.5,
+                0.0, 0.0, -1.0,
+                color.r, color.g, color.b,
+                0.0, 0.0,
+            )).collect()
+    }
+
+    const CLIP_QUAD_INDICES: [u32; 12] = [0, 1, 2, 2, 3, 0, 0, 2, 1, 2, 0, 3];
+
+    /// Create a window and Vulkan context with the given models
+    fn with_context<F>(model_data: Vec<ModelData>, test: F)
+    where
+        F: FnOnce(&mut Context)
+    {
+        let events = vdw::winit::EventsLoop::new();
+        let window = vdw::winit::WindowBuilder::new()
+            .build(&events)
+            .unwrap();
+
+        let mut context = match Context::new(&window, model_data) {
+            Ok(context) => context,
+            Err(e) => panic!("Could not create Vulkan context: {}", e)
+        };
+
+        test(&mut context);
+        context.device.wait_idle();
+    }
+
+    /// Render a single frame with identity view and projection
+    /// and read it back
+    fn render_frame(
+        context: &mut Context,
+        parameters: &Parameters,
+        instances: &Instances,
+    ) -> (u32, u32, Vec<u8>) {
+        let shared_ubo = SharedUBO::new(alg::Mat4::id(), alg::Mat4::id());
+
+        render_cameras(
+            context,
+            parameters,
+            instances,
+            &[(shared_ubo, true, RenderTarget::Swapchain)],
+        )
+    }
+
+    /// Render a single frame through the given cameras and read it back
+    fn render_cameras(
+        context: &mut Context,
+        parameters: &Parameters,
+        instances: &Instances,
+        cameras: &[(SharedUBO, bool, RenderTarget)],
+    ) -> (u32, u32, Vec<u8>) {
+        let mut texts = components::text::Manager::new(1);
+        let mut labels = components::label::Manager::new(1);
+
+        if let Err(e) = context.update(instances, cameras) {
+            panic!("{}", e);
+        }
+
+        if let Err(e) = context.draw(
+            parameters,
+            instances,
+            &mut texts,
+            &mut labels,
+        ) {
+            panic!("{}", e);
+        }
+
+        match context.capture_frame() {
+            Ok(frame) => frame,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    // Requires a display, a Vulkan device, and compiled shaders;
+    // run with `cargo test -- --ignored --test-threads=1`
+    #[test]
+    #[ignore]
+    fn capture_clear_color() {
+        let quad = ModelData::new(
+            "quad",
+            clip_quad(-0.5, 0.5),
+            CLIP_QUAD_INDICES.to_vec(),
+        );
+
+        with_context(vec![quad], |context| {
+            let mut parameters = Parameters::new();
+            parameters.clear_color = graphics::Color::red();
+
+            // No instances; every pixel is the clear color
+            let instances = Instances::new(
+                context.models.len(),
+                &context.model_names,
+                None,
+            );
+
+            let (width, height, pixels) = render_frame(
+                context,
+                &parameters,
+                &instances,
+            );
+
+            assert!(pixels.len() == (width * height * 4) as usize);
+
+            for pixel in pixels.chunks(4) {
+                assert!(pixel == &[255, 0, 0, 255]);
+            }
+        });
+    }
+
+    // Requires a display, a Vulkan device, and compiled shaders;
+    // run with `cargo test -- --ignored --test-threads=1`
+    #[test]
+    #[ignore]
+    fn capture_model_vertices() {
+        let models = vec![
+            ModelData::new(
+                "quad",
+                clip_quad(-0.5, 0.5),
+                CLIP_QUAD_INDICES.to_vec(),
+            ),
+            ModelData::new(
+                "corner",
+                clip_quad(-1.0, -0.5),
+                CLIP_QUAD_INDICES.to_vec(),
+            ),
+        ];
+
+        with_context(models, |context| {
+            let mut parameters = Parameters::new();
+            parameters.clear_color = graphics::Color::blue();
+
+            let mut instances = Instances::new(
+                context.models.len(),
+                &context.model_names,
+                None,
+            );
+
+            let quad = instances.get_index("quad");
+            let corner = instances.get_index("corner");
+            instances.add(InstanceUBO::default(), quad);
+            instances.add(InstanceUBO::default(), corner);
+
+            // Returns true if the pixel at the given screen fraction is clear
+            let clear = |frame: &(u32, u32, Vec<u8>), x: f32, y: f32| {
+                let (width, height, ref pixels) = *frame;
+                let x = (x * width as f32) as usize;
+                let y = (y * height as f32) as usize;
+                let i = (y * width as usize + x) * 4;
+
+                &pixels[i..i + 4] == &[0, 0, 255, 255]
+            };
+
+            let frame = render_frame(context, &parameters, &instances);
+            assert!(!clear(&frame, 0.5, 0.5));
+            assert!(!clear(&frame, 0.125, 0.125));
+            assert!(clear(&frame, 0.875, 0.875));
+
+            // Move the quad to the bottom right, growing its vertex buffer
+            let mut vertices = clip_quad(0.5, 1.0);
+            vertices.extend(clip_quad(0.5, 1.0));
+            if let Err(e) = context.update_model_vertices(quad, &vertices) {
+                panic!("{}", e);
+            }
+
+            let frame = render_frame(context, &parameters, &instances);
+            assert!(clear(&frame, 0.5, 0.5));
+            assert!(!clear(&frame, 0.875, 0.875));
+
+            // Later model is still drawn from its shifted vertices
+            assert!(!clear(&frame, 0.125, 0.125));
+        });
+    }
+
+    // Requires a display, a Vulkan device, and compiled shaders;
+    // run with `cargo test -- --ignored --test-threads=1`
+    #[test]
+    #[ignore]
+    fn capture_render_list() {
+        let models = vec![
+            ModelData::new(
+                "red",
+                clip_quad_colored(-1.0, -0.5, graphics::Color::red()),
+                CLIP_QUAD_INDICES.to_vec(),
+            ),
+            ModelData::new(
+                "green",
+                clip_quad_colored(0.5, 1.0, graphics::Color::green()),
+                CLIP_QUAD_INDICES.to_vec(),
+            ),
+        ];
+
+        with_context(models, |context| {
+            let mut parameters = Parameters::new();
+            parameters.clear_color = graphics::Color::black();
+
+            let mut instances = Instances::new(
+                context.models.len(),
+                &context.model_names,
+                None,
+            );
+
+            let red = instances.get_index("red");
+            let green = instances.get_index("green");
+            instances.add(InstanceUBO::default(), red);
+            instances.add(InstanceUBO::default(), green);
+
+            // Returns the dominant channel at the given screen fraction
+            let channel = |frame: &(u32, u32, Vec<u8>), x: f32, y: f32| {
+                let (width, height, ref pixels) = *frame;
+                let x = (x * width as f32) as usize;
+                let y = (y * height as f32) as usize;
+                let i = (y * width as usize + x) * 4;
+                let pixel = &pixels[i..i + 3];
+
+                if pixel[0] > pixel[1] && pixel[0] > pixel[2] { 0 }
+                else if pixel[1] > pixel[0] && pixel[1] > pixel[2] { 1 }
+                else { 2 }
+            };
+
+            // Second camera moves the red quad behind the green one
+            let first = SharedUBO::new(alg::Mat4::id(), alg::Mat4::id());
+            let second = SharedUBO::new(
+                alg::Mat4::translation(1.5, 1.5, 0.25),
+                alg::Mat4::id(),
+            );
+
+            let swapchain = RenderTarget::Swapchain;
+
+            // Without a depth clear the nearer green quad wins
+            let frame = render_cameras(
+                context,
+                &parameters,
+                &instances,
+                &[(first, true, swapchain), (second, false, swapchain)],
+            );
+
+            assert!(channel(&frame, 0.125, 0.125) == 0);
+            assert!(channel(&frame, 0.875, 0.875) == 1);
+
+            // Clearing depth draws the second camera on top
+            let frame = render_cameras(
+                context,
+                &parameters,
+                &instances,
+                &[(first, true, swapchain), (second, true, swapchain)],
+            );
+
+            assert!(channel(&frame, 0.125, 0.125) == 0);
+            assert!(channel(&frame, 0.875, 0.875) == 0);
+        });
+    }
+
+    // Requires a display, a Vulkan device, and compiled shaders;
+    // run with `cargo test -- --ignored --test-threads=1`
+    #[test]
+    #[ignore]
+    fn capture_offscreen_target() {
+        let quad = ModelData::new(
+            "quad",
+            clip_quad(-0.5, 0.5),
+            CLIP_QUAD_INDICES.to_vec(),
+        );
+
+        with_context(vec![quad], |context| {
+            let mut parameters = Parameters::new();
+            parameters.clear_color = graphics::Color::red();
+
+            let mut instances = Instances::new(
+                context.models.len(),
+                &context.model_names,
+                None,
+            );
+
+            let quad = instances.get_index("quad");
+            instances.add(InstanceUBO::default(), quad);
+
+            let ubo = SharedUBO::new(alg::Mat4::id(), alg::Mat4::id());
+            let offscreen = RenderTarget::Offscreen { width: 64, height: 32 };
+
+            render_cameras(
+                context,
+                &parameters,
+                &instances,
+                &[
+                    (ubo, true, offscreen),
+                    (ubo, true, RenderTarget::Swapchain),
+                ],
+            );
+
+            assert!(context.target_texture(0).is_some());
+            assert!(context.target_texture(1).is_none());
+
+            let (width, height, pixels) = match context.capture_target(0) {
+                Ok(frame) => frame,
+                Err(e) => panic!("{}", e),
+            };
+
+            assert!(width == 64 && height == 32);
+            assert!(pixels.len() == (width * height * 4) as usize);
+
+            let pixel = |x: usize, y: usize| {
+                let i = (y * width as usize + x) * 4;
+                &pixels[i..i + 4]
+            };
+
+            // Cleared to the clear color around the centered quad
+            assert!(pixel(0, 0) == &[255, 0, 0, 255]);
+            assert!(pixel(63, 31) == &[255, 0, 0, 255]);
+            assert!(pixel(32, 16) != &[255, 0, 0, 255]);
+        });
+    }
+
+    // Requires a display, a Vulkan device, and compiled shaders;
+    // run with `cargo test -- --ignored --test-threads=1`
+    #[test]
+    #[ignore]
+    fn capture_points() {
+        let quad = ModelData::new(
+            "quad",
+            clip_quad(-0.5, 0.5),
+            CLIP_QUAD_INDICES.to_vec(),
+        );
+
+        with_context(vec![quad], |context| {
+            let mut parameters = Parameters::new();
+            parameters.clear_color = graphics::Color::blue();
+
+            // No instances; only the points are drawn
+            let instances = Instances::new(
+                context.models.len(),
+                &context.model_names,
+                None,
+            );
+
+            // Returns true if the pixel at the given screen fraction is clear
+            let clear = |frame: &(u32, u32, Vec<u8>), x: f32, y: f32| {
+                let (width, height, ref pixels) = *frame;
+                let x = (x * width as f32) as usize;
+                let y = (y * height as f32) as usize;
+                let i = (y * width as usize + x) * 4;
+
+                &pixels[i..i + 4] == &[0, 0, 255, 255]
+            };
+
+            // Sizes are in clip space with identity view and projection
+            let point = |x: f32, y: f32| Point::new(
+                alg::Vec3::new(x, y, 0.5),
+                graphics::Color::red(),
+                0.25,
+            );
+
+            if let Err(e) = context.update_points(&[point(-0.5, -0.5)]) {
+                panic!("{}", e);
+            }
+
+            let frame = render_frame(context, &parameters, &instances);
+            assert!(!clear(&frame, 0.25, 0.25));
+            assert!(clear(&frame, 0.5, 0.5));
+            assert!(clear(&frame, 0.75, 0.75));
+
+            // Grow past the initial capacity, filling the opposite corner
+            let points: Vec<Point> = (0..INITIAL_POINTS + 1)
+                .map(|_| point(0.5, 0.5))
+                .collect();
+
+            if let Err(e) = context.update_points(&points) {
+                panic!("{}", e);
+            }
+
+            let frame = render_frame(context, &parameters, &instances);
+            assert!(clear(&frame, 0.25, 0.25));
+            assert!(clear(&frame, 0.5, 0.5));
+            assert!(!clear(&frame, 0.75, 0.75));
+        });
+    }
+
+    // Requires a display, a Vulkan device, and compiled shaders;
+    // run with `cargo test -- --ignored --test-threads=1`
+    #[test]
+    #[ignore]
+    fn interop_buffer() {
+        let quad = ModelData::new(
+            "quad",
+            clip_quad(-0.5, 0.5),
+            CLIP_QUAD_INDICES.to_vec(),
+        );
+
+        with_context(vec![quad], |context| {
+            let interop = match context.interop() {
+                Ok(interop) => interop,
+                Err(e) => panic!("{}", e),
+            };
+
+            assert!(interop.graphics_family == context.graphics_family);
+
+            // Allocate and free a buffer as external code would
+            let properties = interop.device.physical_device()
+                .memory_properties();
+
+            let (buffer, memory) = match create_buffer(
+                256,
+                vd::BufferUsageFlags::VERTEX_BUFFER,
+                &interop.device,
+                vd::MemoryPropertyFlags::HOST_VISIBLE,
+                &properties,
+            ) {
+                Ok(handles) => handles,
+                Err(e) => panic!("{}", e),
+            };
+
+            unsafe {
+                interop.device.destroy_buffer(buffer, None);
+                interop.device.free_memory(memory, None);
+            }
+        });
+    }
+
+    #[test]
+    fn select_samples() {
+        let supported = 1 | 2 | 4 | 8;
+        assert!(super::select_samples(4, supported) == 4);
+        assert!(super::select_samples(1, supported) == 1);
+        assert!(super::select_samples(16, supported) == 8);
+        assert!(super::select_samples(3, supported) == 2);
+
+        // Clamp down to the nearest supported count
+        assert!(super::select_samples(8, 1 | 4) == 4);
+        assert!(super::select_samples(2, 1 | 4) == 1);
+        assert!(super::select_samples(4, 1) == 1);
+    }
+
+    #[test]
+    fn optimize() {
+        let (vertices, indices) = test_cube();
+
+        let triangles = |model: &ModelData| -> Vec<Vec<alg::Vec3>> {
+            model.indices.chunks(3).map(|triangle| {
+                triangle.iter()
+                    .map(|i| model.vertices[*i as usize].position)
+                    .collect()
+            }).collect()
+        };
+
+        let flat = ModelData::new_with_normals(
+            "cube",
+            vertices.clone(),
+            indices.clone(),
+            NormalMode::Flat,
+        );
+
+        // Distinct face normals only allow merging within a face
+        let mut optimized = flat.clone();
+        assert!(optimized.optimize() == 36 - 24);
+        assert!(optimized.vertices.len() == 24);
+        assert!(triangles(&optimized) == triangles(&flat));
+
+        // Without normals every corner is shared
+        let plain = ModelData::new("cube", vertices, indices);
+
+        let mut optimized = plain.clone();
+        assert!(optimized.optimize() == 36 - 8);
+        assert!(optimized.vertices.len() == 8);
+        assert!(optimized.indices.len() == 36);
+        assert!(triangles(&optimized) == triangles(&plain));
+
+        // Corners shared by faces bound to different bones stay apart
+        let mut skinned = plain.clone();
+
+        for (face, vertices) in skinned.vertices.chunks_mut(6).enumerate() {
+            for vertex in vertices {
+                vertex.bone_indices = [face as u8, 0, 0, 0];
+            }
+        }
+
+        let mut optimized = skinned.clone();
+        assert!(optimized.optimize() == 36 - 24);
+        assert!(triangles(&optimized) == triangles(&skinned));
+
+        // Alternating faces blend two bones
+        let mut skinned = plain.clone();
+
+        for (face, vertices) in skinned.vertices.chunks_mut(6).enumerate() {
+            for vertex in vertices {
+                if face % 2 == 1 {
+                    vertex.bone_weights = [0.5, 0.5, 0.0, 0.0];
+                }
+            }
+        }
+
+        let mut optimized = skinned.clone();
+        assert!(optimized.optimize() == 36 - 14);
+        assert!(triangles(&optimized) == triangles(&skinned));
+    }
+
+    #[test]
+    fn bounds() {
+        let (vertices, indices) = test_cube();
+        let plain = ModelData::new("cube", vertices, indices);
+
+        // Bounds contain the half-extent box around the origin
+        let (min, max) = plain.bounds();
+        assert!(min == alg::Vec3::one() * -1.0);
+        assert!(max == alg::Vec3::one());
+
+        let (center, radius) = plain.bounding_sphere();
+        let error = center.mag() + (radius - 3f32.sqrt()).abs();
+
+        eprintln!("Error: {}", error);
+        assert!(error < 0.0001);
+
+        for vertex in &plain.vertices {
+            assert!(vertex.position.dist(center) <= radius + 0.0001);
+        }
+    }
+
+    #[test]
+    fn clear_gradient() {
+        let mut parameters = Parameters::new();
+        parameters.clear_color = graphics::Color::blue();
+
+        // Flat clear
+        assert!(parameters.clear_gradient.is_none());
+        assert!(
+            parameters.clear_colors()
+                == (graphics::Color::blue(), graphics::Color::blue())
+        );
+
+        let gradient = (graphics::Color::cyan(), graphics::Color::orange());
+        parameters.clear_gradient = Some(gradient);
+        assert!(parameters.clear_colors() == gradient);
+    }
+}
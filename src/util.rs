@@ -75,11 +75,103 @@ impl<T> AlignedBuffer<T> {
     }
 }
 
+/// Deterministic random numbers for reproducible simulation
+pub mod rng {
+    use alg;
+
+    // PCG32 stream selector; must be odd once shifted
+    const INCREMENT: u64 = (0xda3e39cb94b95bdb << 1) | 1;
+
+    /// Permuted congruential generator (PCG32). \
+    /// Produces the same sequence on every platform for a given seed.
+    #[derive(Clone, Copy, Debug)]
+    pub struct Rng {
+        state: u64,
+    }
+
+    impl Rng {
+        pub fn from_seed(seed: u64) -> Rng {
+            let mut rng = Rng { state: 0 };
+            rng.next_u32();
+            rng.state = rng.state.wrapping_add(seed);
+            rng.next_u32();
+
+            rng
+        }
+
+        pub fn next_u32(&mut self) -> u32 {
+            let old = self.state;
+
+            self.state = old
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(INCREMENT);
+
+            let shifted = (((old >> 18) ^ old) >> 27) as u32;
+            shifted.rotate_right((old >> 59) as u32)
+        }
+
+        /// Returns value in [0, 1)
+        pub fn next_f32(&mut self) -> f32 {
+            // Top 24 bits fill the mantissa exactly
+            (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+        }
+
+        /// Returns value in [lo, hi)
+        pub fn range(&mut self, lo: f32, hi: f32) -> f32 {
+            lo + (hi - lo) * self.next_f32()
+        }
+
+        /// Returns uniformly distributed direction
+        pub fn unit_vec3(&mut self) -> alg::Vec3 {
+            // Rejection sampling avoids platform-dependent trigonometry
+            loop {
+                let point = alg::Vec3::new(
+                    self.range(-1.0, 1.0),
+                    self.range(-1.0, 1.0),
+                    self.range(-1.0, 1.0),
+                );
+
+                let mag_squared = point.mag_squared();
+
+                if mag_squared > 0.0001 && mag_squared <= 1.0 {
+                    return point / mag_squared.sqrt();
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alg;
     use render;
     use util::*;
+    use util::rng::Rng;
+
+    #[test]
+    fn rng_sequence() {
+        let mut rng = Rng::from_seed(42);
+
+        assert!(rng.next_u32() == 0x713066ea);
+        assert!(rng.next_u32() == 0x3c7a0d56);
+        assert!(rng.next_u32() == 0xf424216a);
+        assert!(rng.next_u32() == 0x25c89145);
+
+        // Same seed, same values
+        let (mut a, mut b) = (Rng::from_seed(7), Rng::from_seed(7));
+
+        for _ in 0..100 {
+            let value = a.range(-2.0, 3.0);
+            assert!(value == b.range(-2.0, 3.0));
+            assert!(value >= -2.0 && value < 3.0);
+
+            let direction = a.unit_vec3();
+            assert!(direction == b.unit_vec3());
+
+            let error = (direction.mag() - 1.0).abs();
+            assert!(error < 0.0001);
+        }
+    }
 
     #[test]
     fn pack_ubo() {
@@ -87,34 +179,24 @@ mod tests {
 
         let make_offset = |vec| render::PaddedVec3::new(vec);
 
-        let offsets = [
-            make_offset(alg::Vec3::new(0., 0.5, 0.)),
-            make_offset(alg::Vec3::new(0.5, -0.5, -0.5)),
-            make_offset(alg::Vec3::new(-0.5, -0.5, -0.5)),
-            make_offset(alg::Vec3::new(0.5, -0.5, 0.5)),
-            make_offset(alg::Vec3::new(-0.5, -0.5, 0.5)),
-            make_offset(alg::Vec3::new(0., 0.5, 0.)),
-            make_offset(alg::Vec3::new(0.5, -0.5, -0.5)),
-            make_offset(alg::Vec3::new(-0.5, -0.5, -0.5)),
-            make_offset(alg::Vec3::new(0.5, -0.5, 0.5)),
-            make_offset(alg::Vec3::new(-0.5, -0.5, 0.5)),
-            make_offset(alg::Vec3::new(0., 0.5, 0.)),
-            make_offset(alg::Vec3::new(0.5, -0.5, -0.5)),
-            make_offset(alg::Vec3::new(-0.5, -0.5, -0.5)),
-            make_offset(alg::Vec3::new(0.5, -0.5, 0.5)),
-            make_offset(alg::Vec3::new(-0.5, -0.5, 0.5)),
-            make_offset(alg::Vec3::new(0., 0.5, 0.)),
-            make_offset(alg::Vec3::new(0.5, -0.5, -0.5)),
-            make_offset(alg::Vec3::new(-0.5, -0.5, -0.5)),
-            make_offset(alg::Vec3::new(0.5, -0.5, 0.5)),
-            make_offset(alg::Vec3::new(-0.5, -0.5, 0.5)),
-            make_offset(alg::Vec3::new(0., 0.5, 0.)),
-            make_offset(alg::Vec3::new(0.5, -0.5, -0.5)),
-            make_offset(alg::Vec3::new(-0.5, -0.5, -0.5)),
-            make_offset(alg::Vec3::new(0.5, -0.5, 0.5)),
-            make_offset(alg::Vec3::new(-0.5, -0.5, 0.5)),
+        let pattern = [
+            alg::Vec3::new(0., 0.5, 0.),
+            alg::Vec3::new(0.5, -0.5, -0.5),
+            alg::Vec3::new(-0.5, -0.5, -0.5),
+            alg::Vec3::new(0.5, -0.5, 0.5),
+            alg::Vec3::new(-0.5, -0.5, 0.5),
         ];
 
+        // Fill however many offsets the configured light count leaves
+        let mut offsets = [
+            render::PaddedVec3::default();
+            render::MAX_SOFTBODY_VERT
+        ];
+
+        for (i, offset) in offsets.iter_mut().enumerate() {
+            *offset = make_offset(pattern[i % pattern.len()]);
+        }
+
         let mut raw = {
             let mut buffer = AlignedBuffer::new(996, 1);
 